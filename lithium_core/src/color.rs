@@ -110,7 +110,12 @@ impl Color {
     ///
     /// Supported formats: `#RGB`, `#RRGGBB`, `#RGBA`, `#RRGGBBAA`.
     pub fn from_css_hex(hex: &[u8]) -> Self {
-        parse_hex_color(hex).unwrap_or(Self::error())
+        Self::try_from_css_hex(hex).unwrap_or(Self::error())
+    }
+
+    /// Same as `from_css_hex` but returns `None` if the code is invalid.
+    pub fn try_from_css_hex(hex: &[u8]) -> Option<Self> {
+        parse_hex_color(hex).ok()
     }
 
     /// Get RGBA values.
@@ -258,8 +263,16 @@ fn parse_hex_byte(digit0: u8, digit1: u8) -> Result<u8, ()> {
 fn parse_hex_digit(digit: u8) -> Result<u8, ()> {
     match digit {
         b'0' ... b'9' => Ok(digit - b'0'),
-        b'a' ... b'f' => Ok(digit - b'a'),
-        b'A' ... b'F' => Ok(digit - b'A'),
+        b'a' ... b'f' => Ok(digit - b'a' + 10),
+        b'A' ... b'F' => Ok(digit - b'A' + 10),
         _ => Err(()),
     }
 }
+
+#[test]
+fn test_css_hex() {
+    assert_eq!(Color::from_css_hex(b"751aff").to_rgba32(), (0x75, 0x1a, 0xff, 0xff));
+    assert_eq!(Color::from_css_hex(b"Fa0C").to_rgba32(), (0xff, 0xaa, 0x00, 0xcc));
+    assert_eq!(Color::try_from_css_hex(b"12345g"), None);
+    assert_eq!(Color::try_from_css_hex(b"12345"), None);
+}
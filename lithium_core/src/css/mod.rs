@@ -11,7 +11,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use cssparser::{Parser, ParserInput, ParseError, Token, Delimiter};
 use {Theme, Color};
-use theme::{ColorId, ElementKind, StyleVariant, ElementStyle};
+use theme::{ColorId, ElementKind, StyleVariant, PseudoClass, ElementStyle, MAX_PSEUDO_CLASSES};
 use self::selector::{Selector, expect_hash_selector, selectors};
 
 pub type CssError<'i> = ParseError<'i, &'static str>;
@@ -47,28 +47,65 @@ pub fn theme(input: &str) -> CssResult<Theme> {
             
             let slice = parser.slice(start_position..end_position);
 
-            for &selector in &selectors {
+            for selector in selectors {
                 styles.push((selector, slice));
             }
         }
     }
     
     // Stable sort by increasing specificity.
-    styles.sort_by_key(|&(selector, _)| selector.specificity());
+    styles.sort_by_key(|&(ref selector, _)| selector.specificity());
 
     let /*mut*/ _colors: HashMap<(StyleVariant, ColorId), Color> = HashMap::new();
-    let mut element_styles: HashMap<(StyleVariant, ElementKind), ElementStyle> = HashMap::new();
+    let mut element_styles: HashMap<(StyleVariant, ElementKind, Vec<PseudoClass>), ElementStyle> = HashMap::new();
 
-    for &(selector, _) in &styles {
-        if let Selector::Full(style_variant, element_kind) = selector {
-            element_styles.entry((style_variant, element_kind)).or_insert(ElementStyle::default());
+    for &(ref selector, _) in &styles {
+        if let Selector::Full(style_variant, element_kind) = *selector {
+            element_styles.entry((style_variant, element_kind, Vec::new())).or_insert(ElementStyle::default());
+        }
+    }
+
+    // Pseudo-classes are not inherited from each other so every combination of pseudo-classes which are mentioned for the
+    // element kind needs its own style, e.g. checked checkbox under the pointer should get both `Checkbox:checked` and
+    // `Checkbox:hover` properties.
+    let mut pseudo_classes: HashMap<ElementKind, (Vec<StyleVariant>, Vec<PseudoClass>)> = HashMap::new();
+
+    for &(ref selector, _) in &styles {
+        if let Selector::PseudoClasses(style_variant, element_kind, ref selector_pseudo_classes) = *selector {
+            let entry = pseudo_classes.entry(element_kind).or_insert((vec![StyleVariant::default()], Vec::new()));
+            entry.0.extend(style_variant);
+            entry.1.extend_from_slice(selector_pseudo_classes);
+        }
+    }
+
+    for (&element_kind, &mut (ref mut style_variants, ref mut kind_pseudo_classes)) in &mut pseudo_classes {
+        style_variants.extend(element_styles.keys()
+            .filter(|&&(_, other_element_kind, _)| other_element_kind == element_kind)
+            .map(|&(style_variant, _, _)| style_variant));
+
+        kind_pseudo_classes.sort();
+        kind_pseudo_classes.dedup();
+
+        if kind_pseudo_classes.len() > MAX_PSEUDO_CLASSES {
+            return error("too many pseudo-classes for a single element kind");
+        }
+
+        for &style_variant in style_variants.iter() {
+            for mask in 1..(1u32 << kind_pseudo_classes.len()) {
+                let subset = kind_pseudo_classes.iter().enumerate()
+                    .filter(|&(i, _)| mask & (1 << i) != 0)
+                    .map(|(_, &pseudo_class)| pseudo_class)
+                    .collect();
+
+                element_styles.entry((style_variant, element_kind, subset)).or_insert(ElementStyle::default());
+            }
         }
     }
 
     // FIXME: quadratic loop
-    for &(selector, style) in &styles {
-        for (&(style_variant, element_kind), ref mut element_style_value) in &mut element_styles {
-            if selector.matches((style_variant, element_kind)) {
+    for &(ref selector, style) in &styles {
+        for (&(style_variant, element_kind, ref pseudo_classes), ref mut element_style_value) in &mut element_styles {
+            if selector.matches((style_variant, element_kind, pseudo_classes)) {
                 element_style(style, element_style_value)?;
             }
         }
@@ -76,8 +113,9 @@ pub fn theme(input: &str) -> CssResult<Theme> {
 
     let mut theme = Theme::empty();
 
-    for (selector, element_style) in element_styles {
-        theme.element_styles.insert(selector, Arc::new(element_style));
+    for ((style_variant, element_kind, pseudo_classes), element_style) in element_styles {
+        let element_kind = pseudo_classes.into_iter().fold(element_kind, ElementKind::with_pseudo_class);
+        theme.element_styles.insert((style_variant, element_kind), Arc::new(element_style));
     }
 
     Ok(theme)
@@ -146,3 +184,49 @@ fn test_parse_theme() {
     assert_eq!(theme.element_style(style_variant("default"), element_kind("OtherWidget")).unwrap().border[0].width, 7.0);
     assert_eq!(theme.element_style(style_variant("error"),   element_kind("OtherWidget")).unwrap().border[0].width, 7.0);
 }
+
+#[test]
+fn test_parse_pseudo_classes() {
+    use theme::{style_variant, element_kind, pseudo_class};
+
+    let css = r#"
+    Checkbox {
+        border-width: 1px;
+        border-color: #123456;
+    }
+
+    Checkbox:hover {
+        border-color: #654321;
+    }
+
+    Checkbox.error {
+        border-width: 4px;
+    }
+
+    Checkbox:checked {
+        border-width: 2px;
+    }
+
+    Checkbox.default, Checkbox.error {}
+    "#;
+
+    let theme = theme(css).unwrap();
+
+    let (default, error) = (style_variant("default"), style_variant("error"));
+    let (hover, checked) = (pseudo_class("hover"), pseudo_class("checked"));
+    let checkbox = element_kind("Checkbox");
+
+    let border = |style_variant, pseudo_classes: &[_]| {
+        theme.element_style_in_state(style_variant, checkbox, pseudo_classes).unwrap().border[0]
+    };
+
+    assert_eq!(border(default, &[]).width, 1.0);
+    assert_eq!(border(default, &[]).color, Color::from_css_hex(b"123456"));
+    assert_eq!(border(default, &[hover]).width, 1.0);
+    assert_eq!(border(default, &[hover]).color, Color::from_css_hex(b"654321"));
+    assert_eq!(border(default, &[checked, hover]).width, 2.0);
+    assert_eq!(border(default, &[checked, hover]).color, Color::from_css_hex(b"654321"));
+    assert_eq!(border(error, &[hover]).width, 4.0);
+    assert_eq!(border(error, &[hover]).color, Color::from_css_hex(b"654321"));
+    assert_eq!(border(error, &[pseudo_class("unknown")]).width, 4.0);
+}
@@ -11,8 +11,8 @@ pub fn property<'i, 'tt>(parser: &mut Parser<'i, 'tt>, property: &str, element_s
         "color" => color,
         "opacity" => opacity,
         "overflow" => overflow,
-        "overflow_x" => overflow_x,
-        "overflow_y" => overflow_y,
+        "overflow-x" => overflow_x,
+        "overflow-y" => overflow_y,
 		"visibility" => visibility,
 		"box-shadow" => box_shadow,
 		"filter" => filter,
@@ -77,18 +77,21 @@ fn opacity<'i, 'tt>(parser: &mut Parser<'i, 'tt>, element_style: &mut ElementSty
     Ok(())
 }
 
+/// One value sets both axes, two values set the horizontal and the vertical overflow.
 fn overflow<'i, 'tt>(parser: &mut Parser<'i, 'tt>, element_style: &mut ElementStyle) -> CssResult<'i, ()> {
-    element_style.overflow = overflow_any(parser)?;
+    let x = overflow_any(parser)?;
+    let y = parser.try(overflow_any).unwrap_or(x);
+    element_style.overflow = Vec2::new(x, y);
     Ok(())
 }
 
 fn overflow_x<'i, 'tt>(parser: &mut Parser<'i, 'tt>, element_style: &mut ElementStyle) -> CssResult<'i, ()> {
-    element_style.overflow = overflow_any(parser)?;
+    element_style.overflow.x = overflow_any(parser)?;
     Ok(())
 }
 
 fn overflow_y<'i, 'tt>(parser: &mut Parser<'i, 'tt>, element_style: &mut ElementStyle) -> CssResult<'i, ()> {
-    element_style.overflow = overflow_any(parser)?;
+    element_style.overflow.y = overflow_any(parser)?;
     Ok(())
 }
 
@@ -98,6 +101,8 @@ fn overflow_any<'i, 'tt>(parser: &mut Parser<'i, 'tt>) -> CssResult<'i, Overflow
     match_ignore_ascii_case! { ident.as_ref(),
         "visible" => Ok(Overflow::Visible),
         "hidden" => Ok(Overflow::Hidden),
+        "scroll" => Ok(Overflow::Scroll),
+        "auto" => Ok(Overflow::Auto),
         _ => error("invalid overflow value"),
     }
 }
@@ -272,4 +277,18 @@ fn test_filter() {
     {
         assert_eq!(filter_function(&mut Parser::new(&mut ParserInput::new(a))).unwrap(), b);
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_overflow() {
+    for &(name, value, x, y) in &[
+        ("overflow",   "hidden",      Overflow::Hidden,  Overflow::Hidden),
+        ("overflow",   "hidden auto", Overflow::Hidden,  Overflow::Auto),
+        ("overflow-y", "scroll",      Overflow::Visible, Overflow::Scroll),
+    ]
+    {
+        let mut element_style = ElementStyle::default();
+        property(&mut Parser::new(&mut ParserInput::new(value)), name, &mut element_style).unwrap();
+        assert_eq!(element_style.overflow, Vec2::new(x, y));
+    }
+}
@@ -1,30 +1,39 @@
 use cssparser::{Parser, Token};
-use theme::{ElementKind, StyleVariant, style_variant, element_kind};
+use theme::{ElementKind, StyleVariant, PseudoClass, style_variant, element_kind, pseudo_class};
 use super::{CssResult, error};
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Eq, PartialEq, Debug)]
 pub enum Selector {
     ElementKind(ElementKind),
     StyleVariant(StyleVariant),
     Full(StyleVariant, ElementKind),
+
+    /// Selector such as `Button:hover` or `Button.error:hover:focus`. Pseudo-classes are sorted and deduplicated.
+    PseudoClasses(Option<StyleVariant>, ElementKind, Vec<PseudoClass>),
 }
 
 impl Selector {
-    pub fn specificity(self) -> u32 {
-        match self {
+    pub fn specificity(&self) -> u32 {
+        match *self {
             Selector::ElementKind(_) => 0,
             Selector::StyleVariant(_) => 1,
             Selector::Full(..) => 2,
+            Selector::PseudoClasses(style_variant, _, ref pseudo_classes) => 1 + style_variant.is_some() as u32 + pseudo_classes.len() as u32,
         }
     }
 
-    pub fn matches(self, (style_variant, element_kind): (StyleVariant, ElementKind)) -> bool {
-        match self {
+    /// Check if selector matches the element in the state described by the sorted list of `pseudo_classes`.
+    pub fn matches(&self, (style_variant, element_kind, pseudo_classes): (StyleVariant, ElementKind, &[PseudoClass])) -> bool {
+        match *self {
             Selector::ElementKind(self_element_kind) => self_element_kind == element_kind,
             Selector::StyleVariant(self_style_variant) => self_style_variant == style_variant,
             Selector::Full(self_style_variant, self_element_kind) =>
                 self_style_variant == style_variant &&
                 self_element_kind == element_kind,
+            Selector::PseudoClasses(self_style_variant, self_element_kind, ref self_pseudo_classes) =>
+                self_style_variant.map_or(true, |self_style_variant| self_style_variant == style_variant) &&
+                self_element_kind == element_kind &&
+                self_pseudo_classes.iter().all(|pseudo_class| pseudo_classes.binary_search(pseudo_class).is_ok()),
         }
     }
 }
@@ -54,6 +63,22 @@ fn selector<'i, 'tt>(parser: &mut Parser<'i, 'tt>) -> CssResult<'i, Selector> {
         None
     };
 
+    let mut pseudo_classes = Vec::new();
+    while parser.try(|parser| parser.expect_colon()).is_ok() {
+        let id = parser.expect_ident()?;
+        pseudo_classes.push(pseudo_class(&id));
+    }
+
+    if !pseudo_classes.is_empty() {
+        pseudo_classes.sort();
+        pseudo_classes.dedup();
+
+        return match kind {
+            Some(kind) => Ok(Selector::PseudoClasses(style_variant, kind, pseudo_classes)),
+            None => error("pseudo-class without element kind"),
+        };
+    }
+
     match (kind, style_variant) {
         (Some(kind), Some(style_variant)) => Ok(Selector::Full(style_variant, kind)),
         (Some(kind), None               ) => Ok(Selector::ElementKind(kind)),
@@ -81,6 +106,14 @@ fn test_parse_selectors() {
         ]
     );
 
+    let mut hover_focus = vec![pseudo_class("hover"), pseudo_class("focus")];
+    hover_focus.sort();
+
+    assert_eq!(selector(&mut Parser::new(&mut ParserInput::new("Test:hover"))).unwrap(), Selector::PseudoClasses(None, element_kind("Test"), vec![pseudo_class("hover")]));
+    assert_eq!(selector(&mut Parser::new(&mut ParserInput::new("Test.style:focus:hover"))).unwrap(), Selector::PseudoClasses(Some(style_variant("style")), element_kind("Test"), hover_focus.clone()));
+    assert_eq!(selector(&mut Parser::new(&mut ParserInput::new("Test:hover:focus:hover"))).unwrap(), Selector::PseudoClasses(None, element_kind("Test"), hover_focus));
+    assert!(selector(&mut Parser::new(&mut ParserInput::new(".style:hover"))).is_err());
+
     assert_eq!(expect_hash_selector(&mut Parser::new(&mut ParserInput::new("#theme")), "theme"), Ok(()));
     assert!(expect_hash_selector(&mut Parser::new(&mut ParserInput::new("#theme")), "theme2").is_err());
 }
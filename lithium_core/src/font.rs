@@ -14,6 +14,9 @@ pub struct Segment {
 
 pub trait Font: Debug + Sync + Send {
     fn shape(&self, text: &str, out: &mut Vec<Segment>);
+
+    /// Distance between baselines of consecutive lines.
+    fn line_height(&self) -> f64;
 }
 
 #[derive(Debug)]
@@ -21,6 +24,10 @@ pub struct ErrorFont;
 
 impl Font for ErrorFont {
     fn shape(&self, _text: &str, _out: &mut Vec<Segment>) {}
+
+    fn line_height(&self) -> f64 {
+        0.0
+    }
 }
 
 /*
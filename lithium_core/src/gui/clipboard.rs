@@ -0,0 +1,21 @@
+/// Access to the system clipboard which is provided by the platform layer.
+pub trait Clipboard {
+    fn get(&mut self) -> Option<String>;
+    fn set(&mut self, text: &str);
+}
+
+/// Clipboard which is not shared with other applications. This is the default one.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct MemoryClipboard {
+    pub text: Option<String>,
+}
+
+impl Clipboard for MemoryClipboard {
+    fn get(&mut self) -> Option<String> {
+        self.text.clone()
+    }
+
+    fn set(&mut self, text: &str) {
+        self.text = Some(text.to_owned());
+    }
+}
@@ -0,0 +1,371 @@
+use {Id, Rect};
+use super::input::Direction;
+
+/// Keeps track of the focused element and moves focus between elements.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Focus {
+    focused: Option<Id>,
+
+    /// Elements which can be focused in the order they were registered during the current frame.
+    focusables: Vec<Focusable>,
+    scopes: Vec<Id>,
+    scope_parents: Vec<(Id, Option<Id>)>,
+    overrides: Vec<(Id, Direction, Option<Id>)>,
+
+    /// Scope which the focus can't leave during this frame.
+    trap: Option<Id>,
+}
+
+/// Element which can receive focus.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Focusable {
+    pub id: Id,
+    pub place: Rect<f64>,
+
+    /// Innermost focus scope containing this element.
+    pub scope: Option<Id>,
+}
+
+impl Focus {
+    pub fn focused(&self) -> Option<Id> {
+        self.focused
+    }
+
+    pub fn is_focused(&self, id: Id) -> bool {
+        self.focused == Some(id)
+    }
+
+    pub fn focus(&mut self, id: Id) {
+        self.focused = Some(id);
+    }
+
+    /// Remove focus from the focused element.
+    pub fn blur(&mut self) {
+        self.focused = None;
+    }
+
+    /// Focused element was registered during this frame inside the focus `scope` or a scope nested in it.
+    pub fn is_focused_within(&self, scope: Id) -> bool {
+        match self.focused.and_then(|id| self.focusables.iter().find(|focusable| focusable.id == id)) {
+            Some(focused) => self.enclosing_scopes(focused.scope).contains(&Some(scope)),
+            None => false,
+        }
+    }
+
+    /// Register element which can be focused during this frame.
+    pub fn focusable(&mut self, id: Id, place: Rect<f64>) {
+        self.focusables.push(Focusable {
+            id,
+            place,
+            scope: self.scopes.last().cloned(),
+        });
+    }
+
+    /// Focusable elements registered during this frame.
+    pub fn focusables(&self) -> &[Focusable] {
+        &self.focusables
+    }
+
+    pub fn push_scope(&mut self, id: Id) {
+        self.scope_parents.push((id, self.scopes.last().cloned()));
+        self.scopes.push(id);
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// Keep the focus inside of the focus `scope` during this frame.
+    ///
+    /// Navigation doesn't leave the scope and moves the focus into it when the focused element is outside. The last trap
+    /// of the frame wins so a modal scope built later (like a dialog opened from another one) takes over.
+    pub fn trap(&mut self, scope: Id) {
+        self.trap = Some(scope);
+    }
+
+    /// Scope trapping the focus during this frame.
+    pub fn trapped(&self) -> Option<Id> {
+        self.trap
+    }
+
+    /// Move focus to the next (or previous if not `forward`) element made focusable inside of the `scope` in the order
+    /// they were registered, wrapping around. This is what Tab and Shift+Tab do.
+    ///
+    /// If the focused element is not in the scope the first (or the last) one receives focus. `None` stands for the
+    /// whole frame.
+    pub fn cycle(&mut self, scope: Option<Id>, forward: bool) {
+        let candidates: Vec<Id> = self.focusables.iter().filter(|focusable| {
+            scope.is_none() || self.enclosing_scopes(focusable.scope).contains(&scope)
+        }).map(|focusable| focusable.id).collect();
+
+        if candidates.is_empty() {
+            return;
+        }
+
+        let len = candidates.len();
+        let next = match self.focused.and_then(|id| candidates.iter().position(|&candidate| candidate == id)) {
+            Some(from) if forward => (from + 1) % len,
+            Some(from) => (from + len - 1) % len,
+            None if forward => 0,
+            None => len - 1,
+        };
+
+        self.focused = Some(candidates[next]);
+    }
+
+    /// Override the result of the navigation from the element `from` in the `direction` during this frame.
+    ///
+    /// `None` prevents the focus from moving.
+    pub fn override_navigation(&mut self, from: Id, direction: Direction, to: Option<Id>) {
+        self.overrides.push((from, direction, to));
+    }
+
+    /// Move focus in the `direction` from the focused element.
+    ///
+    /// Candidates inside the focus scope of the focused element are preferred, if there are none then the enclosing scopes
+    /// are tried up to the trapping scope. If nothing is focused then the first registered element receives focus.
+    pub fn navigate(&mut self, direction: Direction) {
+        let from = match self.focused.and_then(|id| self.focusables.iter().position(|focusable| focusable.id == id)) {
+            Some(from) => from,
+            None => {
+                let trap = self.trap;
+                self.focused = self.focusables.iter().find(|focusable| {
+                    trap.is_none() || self.enclosing_scopes(focusable.scope).contains(&trap)
+                }).map(|focusable| focusable.id);
+                return;
+            }
+        };
+
+        if let Some(trap) = self.trap {
+            if !self.enclosing_scopes(self.focusables[from].scope).contains(&Some(trap)) {
+                self.cycle(Some(trap), true);
+                return;
+            }
+        }
+
+        let from = self.focusables[from];
+
+        if let Some(&(_, _, to)) = self.overrides.iter().rev().find(|&&(id, d, _)| id == from.id && d == direction) {
+            if to.is_some() {
+                self.focused = to;
+            }
+            return;
+        }
+
+        let mut candidates = Vec::with_capacity(self.focusables.len());
+
+        // Try the scope of the focused element, then its enclosing scopes and finally all of the elements.
+        let mut scopes = self.enclosing_scopes(from.scope);
+        if let Some(trap) = self.trap.and_then(|trap| scopes.iter().position(|&scope| scope == Some(trap))) {
+            scopes.truncate(trap + 1);
+        }
+
+        for scope in &scopes {
+            candidates.clear();
+            candidates.extend(self.focusables.iter().filter(|focusable| {
+                focusable.id != from.id && (scope.is_none() || self.enclosing_scopes(focusable.scope).contains(scope))
+            }).cloned());
+
+            if let Some(to) = find_in_direction(from.place, direction, &candidates) {
+                self.focused = Some(to);
+                return;
+            }
+        }
+    }
+
+    /// The `scope` followed by the scopes containing it and `None` which stands for the whole frame.
+    fn enclosing_scopes(&self, scope: Option<Id>) -> Vec<Option<Id>> {
+        let mut scopes = vec![scope];
+        while let Some(Some(scope)) = scopes.last().cloned() {
+            let parent = self.scope_parents.iter().find(|&&(id, _)| id == scope).and_then(|&(_, parent)| parent);
+            scopes.push(parent);
+        }
+        scopes
+    }
+
+    /// Forget elements registered during this frame.
+    pub fn advance(&mut self) {
+        self.focusables.clear();
+        self.scopes.clear();
+        self.scope_parents.clear();
+        self.overrides.clear();
+        self.trap = None;
+    }
+}
+
+/// Find the best element to move to from the element placed at `from` in the `direction`.
+///
+/// Only candidates which lie in the `direction` are considered. Candidates overlapping with `from` along the other axis are
+/// preferred. Among them the distance along the `direction` matters much more than the offset along the other axis.
+pub fn find_in_direction(from: Rect<f64>, direction: Direction, candidates: &[Focusable]) -> Option<Id> {
+    // Rotate everything so that the direction becomes `Right`.
+    let rotate = |rect: Rect<f64>| match direction {
+        Direction::Right => rect,
+        Direction::Left => Rect { left: -rect.right, right: -rect.left, top: rect.top, bottom: rect.bottom },
+        Direction::Down => Rect { left: rect.top, right: rect.bottom, top: rect.left, bottom: rect.right },
+        Direction::Up => Rect { left: -rect.bottom, right: -rect.top, top: rect.left, bottom: rect.right },
+    };
+
+    let from = rotate(from);
+
+    let mut best: Option<(bool, f64, Id)> = None;
+
+    for candidate in candidates {
+        let to = rotate(candidate.place);
+
+        let is_candidate = (from.left < to.left || from.right <= to.left) && from.right < to.right;
+        if !is_candidate {
+            continue;
+        }
+
+        let in_beam = to.top < from.bottom && from.top < to.bottom;
+
+        let major = (to.left - from.right).max(0.0);
+        let minor = (to.top + to.bottom)*0.5 - (from.top + from.bottom)*0.5;
+        let distance = 13.0*major*major + minor*minor;
+
+        let better = match best {
+            None => true,
+            Some((best_in_beam, best_distance, _)) => (in_beam && !best_in_beam) || (in_beam == best_in_beam && distance < best_distance),
+        };
+
+        if better {
+            best = Some((in_beam, distance, candidate.id));
+        }
+    }
+
+    best.map(|(_, _, id)| id)
+}
+
+#[test]
+fn test_find_in_direction() {
+    // 0 1 2
+    // 3 4 5
+    //     6
+    let cell = |x: f64, y: f64| Rect { left: x*100.0, right: x*100.0 + 80.0, top: y*50.0, bottom: y*50.0 + 40.0 };
+    let cells = [cell(0.0, 0.0), cell(1.0, 0.0), cell(2.0, 0.0), cell(0.0, 1.0), cell(1.0, 1.0), cell(2.0, 1.0), cell(2.0, 2.0)];
+    let ids: Vec<Id> = cells.iter().map(|_| Id::unique()).collect();
+
+    let candidates = |except: usize| -> Vec<Focusable> {
+        cells.iter().zip(&ids).enumerate().filter(|&(i, _)| i != except).map(|(_, (&place, &id))| {
+            Focusable { id, place, scope: None }
+        }).collect()
+    };
+
+    let find = |from: usize, direction| {
+        find_in_direction(cells[from], direction, &candidates(from)).map(|id| ids.iter().position(|&i| i == id).unwrap())
+    };
+
+    assert_eq!(find(4, Direction::Left), Some(3));
+    assert_eq!(find(4, Direction::Right), Some(5));
+    assert_eq!(find(4, Direction::Up), Some(1));
+    assert_eq!(find(4, Direction::Down), Some(6));
+    assert_eq!(find(0, Direction::Left), None);
+    assert_eq!(find(0, Direction::Up), None);
+    assert_eq!(find(6, Direction::Left), Some(4));
+    assert_eq!(find(6, Direction::Up), Some(5));
+    assert_eq!(find(2, Direction::Down), Some(5));
+}
+
+#[test]
+fn test_navigate_scopes_and_overrides() {
+    let rect = |x: f64, y: f64| Rect { left: x, right: x + 10.0, top: y, bottom: y + 10.0 };
+    let (a, b, c, d, scope) = (Id::unique(), Id::unique(), Id::unique(), Id::unique(), Id::unique());
+
+    let register = |focus: &mut Focus| {
+        focus.focusable(a, rect(0.0, 0.0));
+        focus.push_scope(scope);
+        focus.focusable(b, rect(100.0, 0.0));
+        focus.focusable(c, rect(100.0, 200.0));
+        focus.pop_scope();
+        focus.focusable(d, rect(100.0, 20.0));
+    };
+
+    let mut focus = Focus::default();
+    register(&mut focus);
+    focus.navigate(Direction::Down);
+    assert_eq!(focus.focused(), Some(a));
+    focus.advance();
+
+    register(&mut focus);
+    focus.navigate(Direction::Right);
+    assert_eq!(focus.focused(), Some(b));
+    focus.advance();
+
+    // `d` is closer but `c` is inside of the same scope.
+    register(&mut focus);
+    focus.navigate(Direction::Down);
+    assert_eq!(focus.focused(), Some(c));
+    assert!(focus.is_focused_within(scope));
+    focus.advance();
+
+    // Nothing in the scope so the search continues outside of it.
+    register(&mut focus);
+    focus.navigate(Direction::Left);
+    assert_eq!(focus.focused(), Some(a));
+    assert!(!focus.is_focused_within(scope));
+    focus.advance();
+
+    register(&mut focus);
+    focus.override_navigation(a, Direction::Right, Some(c));
+    focus.navigate(Direction::Right);
+    assert_eq!(focus.focused(), Some(c));
+    focus.advance();
+
+    register(&mut focus);
+    focus.override_navigation(c, Direction::Up, None);
+    focus.navigate(Direction::Up);
+    assert_eq!(focus.focused(), Some(c));
+}
+
+#[test]
+fn test_trap_and_cycle() {
+    let rect = |x: f64, y: f64| Rect { left: x, right: x + 10.0, top: y, bottom: y + 10.0 };
+    let (a, b, c, d, scope) = (Id::unique(), Id::unique(), Id::unique(), Id::unique(), Id::unique());
+
+    let register = |focus: &mut Focus| {
+        focus.focusable(a, rect(0.0, 0.0));
+        focus.push_scope(scope);
+        focus.trap(scope);
+        focus.focusable(b, rect(100.0, 0.0));
+        focus.focusable(c, rect(100.0, 100.0));
+        focus.pop_scope();
+        focus.focusable(d, rect(100.0, 50.0));
+    };
+
+    // Focus outside of the trap is moved into it.
+    let mut focus = Focus::default();
+    focus.focus(a);
+    register(&mut focus);
+    focus.navigate(Direction::Right);
+    assert_eq!(focus.focused(), Some(b));
+    focus.advance();
+
+    // `d` is closer but outside of the trap, there is nothing else above `b`.
+    register(&mut focus);
+    focus.navigate(Direction::Down);
+    assert_eq!(focus.focused(), Some(c));
+    focus.navigate(Direction::Up);
+    assert_eq!(focus.focused(), Some(b));
+    focus.navigate(Direction::Left);
+    assert_eq!(focus.focused(), Some(b));
+    focus.advance();
+
+    register(&mut focus);
+    focus.cycle(Some(scope), true);
+    assert_eq!(focus.focused(), Some(c));
+    focus.cycle(Some(scope), true);
+    assert_eq!(focus.focused(), Some(b));
+    focus.cycle(None, false);
+    assert_eq!(focus.focused(), Some(a));
+    focus.cycle(None, false);
+    assert_eq!(focus.focused(), Some(d));
+    focus.advance();
+
+    // Without the trap navigation leaves the scope.
+    focus.focus(b);
+    focus.focusable(b, rect(100.0, 0.0));
+    focus.focusable(a, rect(0.0, 0.0));
+    focus.navigate(Direction::Left);
+    assert_eq!(focus.focused(), Some(a));
+}
@@ -0,0 +1,134 @@
+use std::mem::swap;
+use {Id, Vec2};
+use super::scene::Command;
+
+/// Tracks which elements are under the pointer.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Hover {
+    /// Hovered elements from the outermost to the innermost.
+    path: Vec<Id>,
+    previous_path: Vec<Id>,
+}
+
+impl Hover {
+    /// Hovered elements from the outermost to the innermost.
+    pub fn path(&self) -> &[Id] {
+        &self.path
+    }
+
+    pub fn is_hovered(&self, id: Id) -> bool {
+        self.path.contains(&id)
+    }
+
+    /// Element became hovered during the last frame.
+    pub fn entered(&self, id: Id) -> bool {
+        self.path.contains(&id) && !self.previous_path.contains(&id)
+    }
+
+    /// Element stopped being hovered during the last frame.
+    pub fn left(&self, id: Id) -> bool {
+        !self.path.contains(&id) && self.previous_path.contains(&id)
+    }
+
+    /// Find elements under the `position` among elements from the `commands`.
+    ///
+    /// Element is hovered if it contains the `position` or if any of its children is hovered. If several siblings are
+    /// hovered only the last one is used since it is drawn on top of the others.
+    pub fn update(&mut self, commands: &[Command], position: Option<Vec2<f64>>) {
+        swap(&mut self.path, &mut self.previous_path);
+        self.path.clear();
+
+        let position = if let Some(position) = position {
+            position
+        } else {
+            return;
+        };
+
+        // Hovered path (from the innermost element) among the children of each of the currently open elements.
+        let mut stack: Vec<Option<Vec<Id>>> = vec![None];
+
+        for command in commands {
+            match *command {
+                Command::StartElement => stack.push(None),
+                Command::CloseElement(ref element) => {
+                    let children_path = stack.pop().unwrap_or(None);
+                    let contains = element.place.contains(position);
+
+                    let path = match children_path {
+                        Some(mut path) => {
+                            if contains || !element.style.clips() {
+                                path.push(element.id);
+                                Some(path)
+                            } else {
+                                None
+                            }
+                        }
+                        None if contains => Some(vec![element.id]),
+                        None => None,
+                    };
+
+                    if path.is_some() {
+                        if let Some(parent_path) = stack.last_mut() {
+                            *parent_path = path;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(Some(path)) = stack.into_iter().next() {
+            self.path = path;
+            self.path.reverse();
+        }
+    }
+}
+
+#[test]
+fn test_hover_path() {
+    use std::sync::Arc;
+    use Rect;
+    use theme::{ElementStyle, element_kind};
+    use theme::element_style::Overflow;
+    use super::scene::Element;
+
+    let close = |id, place, overflow| Command::CloseElement(Element {
+        id,
+        place,
+        kind: element_kind("Test"),
+        style: Arc::new(ElementStyle { overflow: Vec2::new(overflow, overflow), ..ElementStyle::default() }),
+    });
+
+    let (root, first, second, outside) = (Id::unique(), Id::unique(), Id::unique(), Id::unique());
+
+    let commands = vec![
+        Command::StartElement,
+            Command::StartElement,
+            close(first, Rect { left: 0.0, top: 0.0, right: 50.0, bottom: 50.0 }, Overflow::Visible),
+            Command::StartElement,
+                Command::StartElement,
+                close(outside, Rect { left: 200.0, top: 0.0, right: 300.0, bottom: 50.0 }, Overflow::Visible),
+            close(second, Rect { left: 25.0, top: 0.0, right: 100.0, bottom: 50.0 }, Overflow::Visible),
+        close(root, Rect { left: 0.0, top: 0.0, right: 100.0, bottom: 100.0 }, Overflow::Hidden),
+    ];
+
+    let mut hover = Hover::default();
+
+    hover.update(&commands, Some(Vec2::new(10.0, 10.0)));
+    assert_eq!(hover.path(), &[root, first]);
+    assert!(hover.entered(root) && hover.entered(first));
+
+    hover.update(&commands, Some(Vec2::new(30.0, 10.0)));
+    assert_eq!(hover.path(), &[root, second]);
+    assert!(hover.left(first) && hover.entered(second) && !hover.entered(root));
+
+    hover.update(&commands, Some(Vec2::new(250.0, 10.0)));
+    assert_eq!(hover.path(), &[] as &[Id]);
+    assert!(hover.left(root));
+
+    hover.update(&commands, Some(Vec2::new(30.0, 70.0)));
+    assert_eq!(hover.path(), &[root]);
+
+    hover.update(&commands, None);
+    assert!(!hover.is_hovered(root) && hover.left(root));
+}
@@ -1,15 +1,46 @@
 use Vec2;
-use super::{MouseButton, Key};
+use super::{MouseButton, Key, ScanCode};
 
+/// Input event passed to `Input::event`.
+///
+/// Text from the input method is not an event since it is not `Copy`, it is passed to `Input::ime_preedit` and
+/// `Input::ime_commit` instead.
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Event {
     MouseMoved(Vec2<f64>),
     MouseEntered,
     MouseLeft,
     MouseButton(MouseButton, bool),
+
+    /// Mouse wheel or touchpad scrolling. Positive `y` means scrolling up and positive `x` means scrolling to the right.
+    MouseWheel(WheelDelta),
     Touch(TouchEvent),
-    Key(Key, bool),
+    Key(KeyEvent),
     Char(char),
+
+    /// Request to move focus in the direction, for example from a D-pad or a remote control.
+    Navigate(Direction),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KeyEvent {
+    /// Key according to the current keyboard layout or `None` if it is not known.
+    pub key: Option<Key>,
+
+    /// Physical key.
+    pub scan_code: ScanCode,
+
+    /// Key was pressed or released. Pressing the key which is already pressed is treated as a repeat.
+    pub pressed: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WheelDelta {
+    /// Number of lines (or wheel notches) to scroll, usually produced by a mouse wheel.
+    Lines(Vec2<f64>),
+
+    /// Exact distance to scroll, usually produced by a touchpad.
+    Pixels(Vec2<f64>),
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -33,3 +64,11 @@ pub enum TouchPhase {
     /// Touch was cancelled by some other means, for example window has lost focus.
     Cancelled,
 }
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
@@ -1,3 +1,4 @@
+use std::time::Duration;
 use super::ButtonState;
 
 #[derive(Clone, Default, Eq, PartialEq, Hash, Debug)]
@@ -5,8 +6,59 @@ pub struct Keyboard {
     just_pressed: Vec<Key>,
     pressed: Vec<Key>,
     just_released: Vec<Key>,
+    just_repeated: Vec<Key>,
+
+    just_pressed_scan_codes: Vec<ScanCode>,
+    pressed_scan_codes: Vec<ScanCode>,
+    just_released_scan_codes: Vec<ScanCode>,
+
+    repeat: KeyRepeat,
+    time: Duration,
+    /// Key which is being held along with the time of its next repeat.
+    held: Option<(Key, Duration)>,
 
     input: String,
+    composition: Option<Preedit>,
+}
+
+/// Platform-specific code of the physical key which doesn't depend on the keyboard layout.
+///
+/// Useful for shortcuts which depend on the key location, e.g. WASD keys in games.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Debug)]
+pub struct ScanCode(pub u32);
+
+/// How repeated key presses are generated while the key is held.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum KeyRepeat {
+    /// Use repeated presses reported by the platform which follow user's system settings.
+    Platform,
+
+    /// Ignore repeated presses reported by the platform and generate them from the time passed to `Input::set_time`.
+    Custom {
+        /// Time between the key press and the first repeat.
+        delay: Duration,
+
+        /// Time between subsequent repeats.
+        interval: Duration,
+    },
+
+    /// Don't repeat keys.
+    Disabled,
+}
+
+/// Text which is being composed by the input method.
+#[derive(Clone, Default, Eq, PartialEq, Hash, Debug)]
+pub struct Preedit {
+    pub text: String,
+
+    /// Byte range of the cursor inside of the `text` if it should be shown.
+    pub cursor: Option<(usize, usize)>,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        KeyRepeat::Platform
+    }
 }
 
 impl Keyboard {
@@ -14,18 +66,94 @@ impl Keyboard {
         self.just_released.clear();
         self.pressed.extend_from_slice(&self.just_pressed);
         self.just_pressed.clear();
+        self.just_repeated.clear();
+
+        self.just_released_scan_codes.clear();
+        self.pressed_scan_codes.extend_from_slice(&self.just_pressed_scan_codes);
+        self.just_pressed_scan_codes.clear();
+
         self.input.clear();
     }
 
-    pub fn press(&mut self, key: Key) {
-        self.just_pressed.push(key);
+    /// Register key press. Pressing already pressed key is treated as a repeat.
+    ///
+    /// `key` is `None` if the physical key doesn't correspond to any known key.
+    pub fn press(&mut self, key: Option<Key>, scan_code: ScanCode) {
+        let repeated = match key {
+            Some(key) => self.is_pressed(key),
+            None => self.is_scan_code_pressed(scan_code),
+        };
+
+        if repeated {
+            if let (Some(key), KeyRepeat::Platform) = (key, self.repeat) {
+                self.just_repeated.push(key);
+            }
+
+            return;
+        }
+
+        self.just_pressed_scan_codes.push(scan_code);
+
+        if let Some(key) = key {
+            self.just_pressed.push(key);
+
+            if let KeyRepeat::Custom { delay, .. } = self.repeat {
+                self.held = Some((key, self.time + delay));
+            }
+        }
+    }
+
+    pub fn release(&mut self, key: Option<Key>, scan_code: ScanCode) {
+        self.just_released_scan_codes.push(scan_code);
+
+        self.pressed_scan_codes.retain(|&s| s != scan_code);
+        self.just_pressed_scan_codes.retain(|&s| s != scan_code);
+
+        if let Some(key) = key {
+            self.just_released.push(key);
+
+            self.pressed.retain(|&k| k != key);
+            self.just_pressed.retain(|&k| k != key);
+
+            if self.held.map(|(held, _)| held) == Some(key) {
+                self.held = None;
+            }
+        }
     }
 
-    pub fn release(&mut self, key: Key) {
-        self.just_released.push(key);
+    /// Set current time which is used to generate repeats when `KeyRepeat::Custom` is used.
+    pub fn set_time(&mut self, time: Duration) {
+        self.time = time;
+
+        if let (KeyRepeat::Custom { interval, .. }, Some((key, mut next))) = (self.repeat, self.held) {
+            // Limit the number of repeats so that a long pause doesn't produce a flood of them.
+            let mut repeats = 0;
+            while next <= time && repeats < 100 {
+                self.just_repeated.push(key);
+                next += interval;
+                repeats += 1;
+            }
 
-        self.pressed.retain(|&k| k != key);
-        self.just_pressed.retain(|&k| k != key);
+            if next <= time {
+                next = time + interval;
+            }
+
+            self.held = Some((key, next));
+        }
+    }
+
+    /// Key is held and will be repeated by `KeyRepeat::Custom` once enough time passes, even if no events arrive.
+    pub fn repeat_pending(&self) -> bool {
+        self.held.is_some()
+    }
+
+    pub fn set_repeat(&mut self, repeat: KeyRepeat) {
+        self.repeat = repeat;
+        self.held = None;
+    }
+
+    pub fn repeat(&self) -> KeyRepeat {
+        self.repeat
     }
 
     pub fn enter_char(&mut self, char: char) {
@@ -36,6 +164,39 @@ impl Keyboard {
         self.input += str;
     }
 
+    /// Update text which is being composed by the input method.
+    ///
+    /// Empty text means that composition has ended.
+    pub fn preedit(&mut self, text: &str, cursor: Option<(usize, usize)>) {
+        if text.is_empty() {
+            self.composition = None;
+        } else {
+            self.composition = Some(Preedit {
+                text: text.to_owned(),
+                cursor,
+            });
+        }
+    }
+
+    /// Commit text composed by the input method.
+    pub fn commit(&mut self, text: &str) {
+        self.composition = None;
+        self.enter_str(text);
+    }
+
+    /// Text entered during this frame.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// Text which is being composed by the input method, it should be displayed by the focused text widget at the caret
+    /// position.
+    ///
+    /// Unlike `input` this is not reset each frame.
+    pub fn composition(&self) -> Option<&Preedit> {
+        self.composition.as_ref()
+    }
+
     pub fn key_state(&self, key: Key) -> ButtonState {
         if self.pressed.contains(&key) {
             ButtonState::Pressed
@@ -64,12 +225,84 @@ impl Keyboard {
     pub fn all_just_released(&self) -> &[Key] {
         &self.just_released
     }
+
+    /// Key was repeated during this frame because it is being held.
+    pub fn is_repeated(&self, key: Key) -> bool {
+        self.just_repeated.contains(&key)
+    }
+
+    /// Keys repeated during this frame. The same key may be present several times.
+    pub fn all_just_repeated(&self) -> &[Key] {
+        &self.just_repeated
+    }
+
+    /// Number of times the key was pressed or repeated during this frame.
+    ///
+    /// This is what should be used for actions like moving the text caret.
+    pub fn presses(&self, key: Key) -> usize {
+        let pressed = if self.key_state(key) == ButtonState::JustPressed { 1 } else { 0 };
+        pressed + self.just_repeated.iter().filter(|&&k| k == key).count()
+    }
+
+    pub fn scan_code_state(&self, scan_code: ScanCode) -> ButtonState {
+        if self.pressed_scan_codes.contains(&scan_code) {
+            ButtonState::Pressed
+        } else if self.just_pressed_scan_codes.contains(&scan_code) {
+            ButtonState::JustPressed
+        } else if self.just_released_scan_codes.contains(&scan_code) {
+            ButtonState::JustReleased
+        } else {
+            ButtonState::Released
+        }
+    }
+
+    pub fn is_scan_code_pressed(&self, scan_code: ScanCode) -> bool {
+        self.scan_code_state(scan_code).is_pressed()
+    }
+
+    pub fn all_pressed_scan_codes(&self) -> &[ScanCode] {
+        &self.pressed_scan_codes
+    }
+
+    pub fn all_just_pressed_scan_codes(&self) -> &[ScanCode] {
+        &self.just_pressed_scan_codes
+    }
+
+    pub fn all_just_released_scan_codes(&self) -> &[ScanCode] {
+        &self.just_released_scan_codes
+    }
 }
 
-// from https://github.com/tomaka/winit/blob/master/src/events.rs
-#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
-#[repr(u8)]
-pub enum Key {
+/// Define the `Key` enum along with the names of the keys so the names can't get out of sync with the variants.
+macro_rules! keys {
+    ($($(#[$attr:meta])* $key:ident,)*) => {
+        // from https://github.com/tomaka/winit/blob/master/src/events.rs
+        #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+        #[repr(u8)]
+        pub enum Key {
+            $($(#[$attr])* $key,)*
+        }
+
+        impl Key {
+            /// Name of the key which is the same as the name of its variant, it is used in recordings of events.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Key::$key => stringify!($key),)*
+                }
+            }
+
+            /// Get key from the name returned by `Key::name`.
+            pub fn from_name(name: &str) -> Option<Key> {
+                match name {
+                    $(stringify!($key) => Some(Key::$key),)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+keys! {
     /// The '1' key over the letters.
     Key1,
     /// The '2' key over the letters.
@@ -250,4 +483,55 @@ pub enum Key {
     WebSearch,
     WebStop,
     Yen,
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_key_repeat() {
+    let ms = |ms| Duration::from_millis(ms);
+
+    let mut keyboard = Keyboard::default();
+    keyboard.press(Some(Key::Left), ScanCode(1));
+    assert_eq!(keyboard.presses(Key::Left), 1);
+    keyboard.advance();
+    keyboard.press(Some(Key::Left), ScanCode(1));
+    keyboard.press(Some(Key::Left), ScanCode(1));
+    assert_eq!(keyboard.presses(Key::Left), 2);
+    assert!(keyboard.is_repeated(Key::Left));
+    assert_eq!(keyboard.all_just_pressed_scan_codes(), &[]);
+    assert!(keyboard.is_scan_code_pressed(ScanCode(1)));
+    keyboard.release(Some(Key::Left), ScanCode(1));
+    keyboard.advance();
+
+    keyboard.set_repeat(KeyRepeat::Custom { delay: ms(500), interval: ms(100) });
+    keyboard.set_time(ms(1000));
+    keyboard.press(Some(Key::Backspace), ScanCode(14));
+    keyboard.advance();
+
+    let mut total = 0;
+    for &(time, presses) in &[(1200, 0), (1499, 0), (1500, 1), (1550, 0), (1820, 3)] {
+        // Repeats reported by the platform must be ignored.
+        keyboard.press(Some(Key::Backspace), ScanCode(14));
+        keyboard.set_time(ms(time));
+        assert_eq!(keyboard.presses(Key::Backspace), presses);
+        total += presses;
+        keyboard.advance();
+    }
+    assert_eq!(total, 4);
+
+    keyboard.release(Some(Key::Backspace), ScanCode(14));
+    keyboard.advance();
+    keyboard.set_time(ms(5000));
+    assert_eq!(keyboard.presses(Key::Backspace), 0);
+    assert_eq!(keyboard.scan_code_state(ScanCode(14)), ButtonState::Released);
+}
+
+#[test]
+fn test_key_names() {
+    for &key in &[Key::Key1, Key::A, Key::Escape, Key::NumpadEnter, Key::Yen] {
+        assert_eq!(Key::from_name(key.name()), Some(key));
+    }
+
+    assert_eq!(Key::A.name(), "A");
+    assert_eq!(Key::from_name("Key"), None);
+    assert_eq!(Key::from_name("a"), None);
+}
@@ -1,14 +1,17 @@
-use {Id, Vec2};
+use std::time::Duration;
+use {Id, Vec2, Rect};
 
 mod keyboard;
 mod mouse;
 mod touch;
 mod event;
+pub mod record;
 
-pub use self::keyboard::{Keyboard, Key};
+pub use self::keyboard::{Keyboard, Key, ScanCode, KeyRepeat, Preedit};
 pub use self::mouse::{Mouse, MouseButton};
 pub use self::touch::{Touch};
-pub use self::event::{Event, TouchEvent, TouchPhase};
+pub use self::event::{Event, KeyEvent, WheelDelta, TouchEvent, TouchPhase, Direction};
+pub use self::record::{Recorder, Replayer};
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Debug)]
 pub enum ButtonState {
@@ -24,9 +27,24 @@ pub struct Input {
     pub touches: Vec<Touch>,
     pub keyboard: Keyboard,
 
+    /// Treat arrow keys as directional navigation in addition to regular key presses.
+    pub arrow_key_navigation: bool,
+    navigation: Vec<Direction>,
+
     mouse_grabber: Option<Id>,
     mouse_returned: bool,
     mouse_grabbed: bool,
+
+    /// Popups from the previous frame with their places in the order they are painted.
+    popups: Vec<(Id, Rect<f64>)>,
+    next_popups: Vec<Id>,
+
+    /// Popups which are being built, the innermost is the last.
+    open_popups: Vec<Id>,
+
+    ime_caret: Option<Rect<f64>>,
+    time: Duration,
+    animating: bool,
 }
 
 impl ButtonState {
@@ -65,13 +83,17 @@ impl Input {
         }
     }
 
+    /// Take the mouse if the pointer is in the area accepted by `f`.
+    ///
+    /// Popups get the mouse first: while the pointer is over a popup the mouse is returned only to the widgets inside of
+    /// it, and widgets inside of a popup receive the mouse only while the pointer is over their popup.
     pub fn get_mouse<F: Fn(Vec2<f64>) -> bool>(&mut self, f: F) -> Option<Mouse> {
         if self.mouse_returned {
             return None;
         }
 
         if let Some(mouse) = self.mouse {
-            if f(mouse.position) {
+            if f(mouse.position) && self.reaches(mouse.position) {
                 self.mouse_returned = true;
                 return Some(mouse);
             }
@@ -80,6 +102,138 @@ impl Input {
         None
     }
 
+    /// Take the mouse wheel movement in pixels if the pointer is in the area accepted by `f`. Lines are converted using
+    /// the `line_height`.
+    ///
+    /// Unlike `get_mouse` this works even when the mouse was taken by some other widget so that scrollable containers
+    /// receive the wheel while the pointer is over their content. Nested widgets appear first and take the wheel before
+    /// their containers do.
+    pub fn take_wheel<F: Fn(Vec2<f64>) -> bool>(&mut self, line_height: f64, f: F) -> Option<Vec2<f64>> {
+        let accepted = self.mouse.map(|mouse| f(mouse.position) && self.reaches(mouse.position)).unwrap_or(false);
+
+        if let Some(ref mut mouse) = self.mouse {
+            let wheel = mouse.wheel(line_height);
+
+            if wheel != Vec2::zero() && accepted {
+                mouse.wheel_lines = Vec2::zero();
+                mouse.wheel_pixels = Vec2::zero();
+                return Some(wheel);
+            }
+        }
+
+        None
+    }
+
+    /// State of the mouse regardless of whether it was taken by some widget.
+    ///
+    /// This is only for observing the mouse, for example to find out which part of a child widget was clicked.
+    pub fn peek_mouse(&self) -> Option<Mouse> {
+        self.mouse
+    }
+
+    /// Position of the mouse pointer regardless of whether it was taken by some widget.
+    pub fn mouse_position(&self) -> Option<Vec2<f64>> {
+        self.mouse.map(|mouse| mouse.position)
+    }
+
+    /// Widgets built from now on are inside of the popup `id`.
+    pub fn start_popup(&mut self, id: Id) {
+        self.open_popups.push(id);
+        self.next_popups.push(id);
+    }
+
+    pub fn end_popup(&mut self) {
+        self.open_popups.pop();
+    }
+
+    /// Remember the popups built during this frame with their places given by `place` for the next frame.
+    ///
+    /// `Gui::advance` calls this once the layout of the frame is solved.
+    pub fn advance_popups<F: Fn(Id) -> Rect<f64>>(&mut self, place: F) {
+        self.popups = self.next_popups.drain(..).map(|id| (id, place(id))).collect();
+        self.open_popups.clear();
+    }
+
+    /// Popups from the previous frame with their places in the order they are painted.
+    pub fn popups(&self) -> &[(Id, Rect<f64>)] {
+        &self.popups
+    }
+
+    /// Topmost popup from the previous frame which contains the `position`.
+    pub fn popup_at(&self, position: Vec2<f64>) -> Option<Id> {
+        self.popups.iter().rev().find(|&&(_, place)| place.contains(position)).map(|&(id, _)| id)
+    }
+
+    /// The `position` is in the topmost layer for the widgets being built, either in their popup or outside of all
+    /// popups for the widgets which are not in any.
+    pub(crate) fn reaches(&self, position: Vec2<f64>) -> bool {
+        self.popup_at(position) == self.open_popups.last().cloned()
+    }
+
+    /// Set time at which the current frame started. The platform layer should call this each frame after passing events.
+    ///
+    /// Time is counted from an arbitrary point and is used for things like key repeats and animations.
+    pub fn set_time(&mut self, time: Duration) {
+        self.time = time;
+        self.keyboard.set_time(time);
+    }
+
+    pub fn time(&self) -> Duration {
+        self.time
+    }
+
+    /// Report position of the text caret of the focused text widget.
+    ///
+    /// This should be called each frame by the widget which accepts text input. Platform layer uses this to position the
+    /// candidate window of the input method.
+    pub fn set_ime_caret(&mut self, caret: Rect<f64>) {
+        self.ime_caret = Some(caret);
+    }
+
+    /// Text which is being composed by the input method but is not yet committed.
+    ///
+    /// `cursor` is the byte range of the cursor inside of the text. Empty text means that composition has ended.
+    pub fn ime_preedit(&mut self, text: &str, cursor: Option<(usize, usize)>) {
+        self.keyboard.preedit(text, cursor);
+    }
+
+    /// Text which was committed by the input method.
+    pub fn ime_commit(&mut self, text: &str) {
+        self.keyboard.commit(text);
+    }
+
+    /// Position of the text caret reported during this frame or `None` if no widget is accepting text input.
+    pub fn ime_caret(&self) -> Option<Rect<f64>> {
+        self.ime_caret
+    }
+
+    /// Request the next frame to be shown without waiting for events because something is animating.
+    ///
+    /// This should be called each frame while the animation runs. Platform layer may wait for events when no widget
+    /// requested it during the frame, see `Gui::next_frame_requested`.
+    pub fn request_animation_frame(&mut self) {
+        self.animating = true;
+    }
+
+    /// Some widget is animating and requested the next frame during this frame.
+    pub fn animation_frame_requested(&self) -> bool {
+        self.animating
+    }
+
+    /// Directional navigation requested during this frame which was not taken by any widget.
+    pub fn navigation(&self) -> &[Direction] {
+        &self.navigation
+    }
+
+    /// Take the pending navigation in the `direction` so that focus is not moved by it.
+    ///
+    /// Widgets which use directions themselves (for example lists moving the selection) should call this while focused.
+    pub fn take_navigation(&mut self, direction: Direction) -> bool {
+        let len = self.navigation.len();
+        self.navigation.retain(|&d| d != direction);
+        self.navigation.len() != len
+    }
+
     pub fn advance(&mut self) {
         if let Some(ref mut mouse) = self.mouse {
             mouse.advance();
@@ -99,6 +253,10 @@ impl Input {
             self.mouse_grabber = None;
         }
         self.mouse_grabbed = false;
+
+        self.ime_caret = None;
+        self.animating = false;
+        self.navigation.clear();
     }
 
     pub fn event(&mut self, event: &Event) {
@@ -127,6 +285,14 @@ impl Input {
                     }
                 }
             }
+            Event::MouseWheel(delta) => {
+                if let Some(ref mut mouse) = self.mouse {
+                    match delta {
+                        WheelDelta::Lines(lines) => mouse.wheel_lines += lines,
+                        WheelDelta::Pixels(pixels) => mouse.wheel_pixels += pixels,
+                    }
+                }
+            }
             Event::Touch(touch_event) => {
                 match touch_event.phase {
                     event::TouchPhase::Started => {
@@ -157,16 +323,45 @@ impl Input {
                     }
                 }
             },
-            Event::Key(key, pressed) => {
-                if pressed {
-                    self.keyboard.press(key);
+            Event::Key(key_event) => {
+                if key_event.pressed {
+                    if self.arrow_key_navigation {
+                        let direction = match key_event.key {
+                            Some(Key::Up) => Some(Direction::Up),
+                            Some(Key::Down) => Some(Direction::Down),
+                            Some(Key::Left) => Some(Direction::Left),
+                            Some(Key::Right) => Some(Direction::Right),
+                            _ => None,
+                        };
+
+                        self.navigation.extend(direction);
+                    }
+
+                    self.keyboard.press(key_event.key, key_event.scan_code);
                 } else {
-                    self.keyboard.release(key);
+                    self.keyboard.release(key_event.key, key_event.scan_code);
                 }
             }
             Event::Char(char) => {
                 self.keyboard.enter_char(char);
             },
+            Event::Navigate(direction) => {
+                self.navigation.push(direction);
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_animation_frame_request() {
+    let mut input = Input::default();
+    assert!(!input.animation_frame_requested());
+
+    input.request_animation_frame();
+    input.request_animation_frame();
+    assert!(input.animation_frame_requested());
+
+    // The request lasts for a single frame and has to be repeated while the animation runs.
+    input.advance();
+    assert!(!input.animation_frame_requested());
+}
@@ -10,6 +10,12 @@ pub struct Mouse {
     pub secondary_button: ButtonState,
     pub x1_button: ButtonState,
     pub x2_button: ButtonState,
+
+    /// Scrolling in lines accumulated during this frame.
+    pub wheel_lines: Vec2<f64>,
+
+    /// Scrolling in pixels accumulated during this frame.
+    pub wheel_pixels: Vec2<f64>,
 }
 
 impl Mouse {
@@ -19,13 +25,21 @@ impl Mouse {
         self.secondary_button.advance();
         self.x1_button.advance();
         self.x2_button.advance();
+
+        self.wheel_lines = Vec2::zero();
+        self.wheel_pixels = Vec2::zero();
+    }
+
+    /// Scrolling during this frame in pixels assuming that a line is `line_height` pixels high.
+    pub fn wheel(&self, line_height: f64) -> Vec2<f64> {
+        self.wheel_lines*line_height + self.wheel_pixels
     }
 
     pub fn button_mut(&mut self, button: MouseButton) -> &mut ButtonState {
         match button {
             MouseButton::Primary => &mut self.primary_button,
-            MouseButton::Secondary => &mut self.middle_button,
-            MouseButton::Middle => &mut self.secondary_button,
+            MouseButton::Secondary => &mut self.secondary_button,
+            MouseButton::Middle => &mut self.middle_button,
             MouseButton::X1 => &mut self.x1_button,
             MouseButton::X2 => &mut self.x2_button,
         }
@@ -63,3 +77,16 @@ pub enum MouseButton {
     /// Button present on some mouses which causes forward navigation in a browser.
     X2,
 }
+
+#[test]
+fn test_mouse_buttons() {
+    let mut mouse = Mouse::default();
+    mouse.press(MouseButton::Secondary);
+    assert_eq!(mouse.secondary_button, ButtonState::JustPressed);
+    assert_eq!(mouse.middle_button, ButtonState::Released);
+
+    mouse.press(MouseButton::Middle);
+    mouse.release(MouseButton::Secondary);
+    assert_eq!(mouse.middle_button, ButtonState::JustPressed);
+    assert_eq!(mouse.secondary_button, ButtonState::JustReleased);
+}
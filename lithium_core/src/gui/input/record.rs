@@ -0,0 +1,535 @@
+//! Recording and replaying of the input events.
+//!
+//! `Recorder` writes all events and input method text passed to it into a simple line-based text format together with
+//! the frame number and the time elapsed since the start of the recording. `Replayer` reads this format back and feeds
+//! the events into `Input` frame by frame.
+//!
+//! Since `Gui` does not require a window to work, this allows turning recorded interaction into a regression test:
+//!
+//! ```ignore
+//! let mut gui = Gui::new(theme);
+//! let mut replayer = Replayer::new(BufReader::new(File::open("bug.events")?))?;
+//!
+//! while replayer.advance(&mut gui.input)? {
+//!     let place = widget.appear(&mut gui);
+//!     // Constrain `place` to the window size here.
+//!     gui.advance();
+//! }
+//! ```
+//!
+//! Each line of the file describes a single event: `<frame> <time> <event> <arguments...>`. The special `advance` event marks
+//! the end of the frame.
+
+use std::io::{self, Write, BufRead};
+use std::str::SplitWhitespace;
+use std::time::{Duration, Instant};
+use Vec2;
+use super::{Input, Event, KeyEvent, WheelDelta, TouchEvent, TouchPhase, MouseButton, Key, ScanCode, Direction};
+
+const HEADER: &str = "lithium-events 2";
+
+/// Writes events into the text format which can be read back with `Replayer`.
+pub struct Recorder<W: Write> {
+    writer: W,
+    start: Instant,
+    frame: u64,
+}
+
+/// Single recorded input, either an event or text from the input method which is passed to `Input` separately.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Entry {
+    Event(Event),
+
+    /// Arguments of `Input::ime_preedit`.
+    ImePreedit(String, Option<(usize, usize)>),
+
+    /// Argument of `Input::ime_commit`.
+    ImeCommit(String),
+}
+
+/// Input which was recorded during a single frame.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Frame {
+    pub number: u64,
+
+    /// Time in seconds since the start of the recording at which the frame has ended.
+    pub time: f64,
+
+    /// Entries along with the time in seconds since the start of the recording at which they were received.
+    pub entries: Vec<(f64, Entry)>,
+}
+
+/// Reads events written by `Recorder`.
+pub struct Replayer<R: BufRead> {
+    lines: io::Lines<R>,
+    line: usize,
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writeln!(writer, "{}", HEADER)?;
+
+        Ok(Recorder {
+            writer,
+            start: Instant::now(),
+            frame: 0,
+        })
+    }
+
+    /// Record event. This should be called for every event passed to `Input::event`.
+    pub fn record(&mut self, event: &Event) -> io::Result<()> {
+        let time = self.elapsed();
+        self.record_at(time, &Entry::Event(*event))
+    }
+
+    /// Record text passed to `Input::ime_preedit`.
+    pub fn record_preedit(&mut self, text: &str, cursor: Option<(usize, usize)>) -> io::Result<()> {
+        let time = self.elapsed();
+        self.record_at(time, &Entry::ImePreedit(text.to_owned(), cursor))
+    }
+
+    /// Record text passed to `Input::ime_commit`.
+    pub fn record_commit(&mut self, text: &str) -> io::Result<()> {
+        let time = self.elapsed();
+        self.record_at(time, &Entry::ImeCommit(text.to_owned()))
+    }
+
+    /// Record entry which was received at the specified time (in seconds since the start of the recording).
+    pub fn record_at(&mut self, time: f64, entry: &Entry) -> io::Result<()> {
+        write!(self.writer, "{} {} ", self.frame, time)?;
+        write_entry(&mut self.writer, entry)?;
+        writeln!(self.writer)
+    }
+
+    /// Mark the end of the current frame. This should be called along with `Gui::advance`.
+    pub fn advance(&mut self) -> io::Result<()> {
+        let time = self.elapsed();
+        writeln!(self.writer, "{} {} advance", self.frame, time)?;
+        self.frame += 1;
+
+        Ok(())
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn elapsed(&self) -> f64 {
+        let elapsed = self.start.elapsed();
+        elapsed.as_secs() as f64 + (elapsed.subsec_nanos() as f64)*1e-9
+    }
+}
+
+impl<R: BufRead> Replayer<R> {
+    pub fn new(reader: R) -> io::Result<Self> {
+        let mut replayer = Replayer {
+            lines: reader.lines(),
+            line: 0,
+        };
+
+        match replayer.next_line()? {
+            Some(ref header) if header.trim() == HEADER => Ok(replayer),
+            _ => Err(replayer.error("not a recording of lithium events")),
+        }
+    }
+
+    /// Read all entries of the next frame.
+    ///
+    /// Returns `None` if the end of the recording was reached.
+    pub fn next_frame(&mut self) -> io::Result<Option<Frame>> {
+        let mut frame: Option<Frame> = None;
+
+        while let Some(line) = self.next_line()? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+
+            let number = parse_number(&mut words).map_err(|err| self.error(err))?;
+            let time = parse_number(&mut words).map_err(|err| self.error(err))?;
+
+            let frame = frame.get_or_insert_with(|| Frame {
+                number,
+                time,
+                entries: Vec::new(),
+            });
+
+            if frame.number != number {
+                return Err(self.error("frame ended without `advance`"));
+            }
+
+            if words.clone().next() == Some("advance") {
+                frame.time = time;
+                break;
+            }
+
+            let entry = parse_entry(&mut words).map_err(|err| self.error(err))?;
+            frame.entries.push((time, entry));
+        }
+
+        Ok(frame)
+    }
+
+    /// Feed all entries of the next frame into `input` and set its time to the time of the frame.
+    ///
+    /// Returns `false` if the end of the recording was reached.
+    pub fn advance(&mut self, input: &mut Input) -> io::Result<bool> {
+        if let Some(frame) = self.next_frame()? {
+            for &(_, ref entry) in &frame.entries {
+                match *entry {
+                    Entry::Event(ref event) => input.event(event),
+                    Entry::ImePreedit(ref text, cursor) => input.ime_preedit(text, cursor),
+                    Entry::ImeCommit(ref text) => input.ime_commit(text),
+                }
+            }
+
+            let nanos = (frame.time.max(0.0)*1e9).round() as u64;
+            input.set_time(Duration::new(nanos/1_000_000_000, (nanos % 1_000_000_000) as u32));
+
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn next_line(&mut self) -> io::Result<Option<String>> {
+        self.line += 1;
+        self.lines.next().map_or(Ok(None), |line| line.map(Some))
+    }
+
+    fn error(&self, err: &str) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {}", self.line, err))
+    }
+}
+
+fn write_entry<W: Write>(writer: &mut W, entry: &Entry) -> io::Result<()> {
+    match *entry {
+        Entry::Event(ref event) => write_event(writer, event),
+        Entry::ImePreedit(ref text, cursor) => {
+            write!(writer, "ime-preedit ")?;
+            write_str(writer, text)?;
+
+            if let Some((start, end)) = cursor {
+                write!(writer, " {} {}", start, end)
+            } else {
+                write!(writer, " -")
+            }
+        }
+        Entry::ImeCommit(ref text) => {
+            write!(writer, "ime-commit ")?;
+            write_str(writer, text)
+        }
+    }
+}
+
+fn write_event<W: Write>(writer: &mut W, event: &Event) -> io::Result<()> {
+    match *event {
+        Event::MouseMoved(position) => write!(writer, "mouse-moved {} {}", position.x, position.y),
+        Event::MouseEntered => write!(writer, "mouse-entered"),
+        Event::MouseLeft => write!(writer, "mouse-left"),
+        Event::MouseButton(button, pressed) => write!(writer, "mouse-button {} {}", mouse_button_name(button), pressed as u8),
+        Event::MouseWheel(WheelDelta::Lines(delta)) => write!(writer, "mouse-wheel lines {} {}", delta.x, delta.y),
+        Event::MouseWheel(WheelDelta::Pixels(delta)) => write!(writer, "mouse-wheel pixels {} {}", delta.x, delta.y),
+        Event::Touch(touch) => {
+            write!(writer, "touch {} {} {} {}", touch_phase_name(touch.phase), touch.position.x, touch.position.y, touch.id)
+        }
+        Event::Key(key_event) => {
+            if let Some(key) = key_event.key {
+                write!(writer, "key {} ", key.name())?;
+            } else {
+                write!(writer, "key - ")?;
+            }
+
+            write!(writer, "{} {}", key_event.scan_code.0, key_event.pressed as u8)
+        }
+        Event::Char(char) => write!(writer, "char {}", char as u32),
+        Event::Navigate(direction) => write!(writer, "navigate {}", direction_name(direction)),
+    }
+}
+
+// Strings are prefixed with `'` so that empty string is still a separate word. Whitespace, `%` and control characters are
+// percent-encoded.
+fn write_str<W: Write>(writer: &mut W, str: &str) -> io::Result<()> {
+    write!(writer, "'")?;
+
+    for char in str.chars() {
+        if char == '%' || char.is_whitespace() || char.is_control() {
+            let mut buffer = [0; 4];
+            for byte in char.encode_utf8(&mut buffer).bytes() {
+                write!(writer, "%{:02X}", byte)?;
+            }
+        } else {
+            write!(writer, "{}", char)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_str(words: &mut SplitWhitespace) -> Result<String, &'static str> {
+    let word = words.next().ok_or("expected string")?;
+    if !word.starts_with('\'') {
+        return Err("expected string");
+    }
+
+    let mut bytes = Vec::with_capacity(word.len());
+    let mut word_bytes = word[1..].bytes();
+
+    while let Some(byte) = word_bytes.next() {
+        if byte == b'%' {
+            let high = word_bytes.next().and_then(|digit| (digit as char).to_digit(16));
+            let low = word_bytes.next().and_then(|digit| (digit as char).to_digit(16));
+
+            match (high, low) {
+                (Some(high), Some(low)) => bytes.push((high*16 + low) as u8),
+                _ => return Err("invalid escape sequence"),
+            }
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    String::from_utf8(bytes).map_err(|_| "invalid UTF-8 in string")
+}
+
+fn parse_entry(words: &mut SplitWhitespace) -> Result<Entry, &'static str> {
+    let entry = match words.clone().next() {
+        Some("ime-preedit") => {
+            words.next();
+            let text = parse_str(words)?;
+            let cursor = if words.clone().next() == Some("-") {
+                words.next();
+                None
+            } else {
+                Some((parse_number(words)?, parse_number(words)?))
+            };
+
+            Entry::ImePreedit(text, cursor)
+        }
+        Some("ime-commit") => {
+            words.next();
+            Entry::ImeCommit(parse_str(words)?)
+        }
+        _ => Entry::Event(parse_event(words)?),
+    };
+
+    if words.next().is_some() {
+        return Err("unexpected data after the event");
+    }
+
+    Ok(entry)
+}
+
+fn parse_event(words: &mut SplitWhitespace) -> Result<Event, &'static str> {
+    let event = match words.next() {
+        Some("mouse-moved") => Event::MouseMoved(Vec2::new(parse_number(words)?, parse_number(words)?)),
+        Some("mouse-entered") => Event::MouseEntered,
+        Some("mouse-left") => Event::MouseLeft,
+        Some("mouse-button") => {
+            let button = match words.next() {
+                Some("primary") => MouseButton::Primary,
+                Some("secondary") => MouseButton::Secondary,
+                Some("middle") => MouseButton::Middle,
+                Some("x1") => MouseButton::X1,
+                Some("x2") => MouseButton::X2,
+                _ => return Err("invalid mouse button"),
+            };
+
+            Event::MouseButton(button, parse_bool(words)?)
+        }
+        Some("mouse-wheel") => {
+            let lines = match words.next() {
+                Some("lines") => true,
+                Some("pixels") => false,
+                _ => return Err("invalid wheel delta"),
+            };
+
+            let delta = Vec2::new(parse_number(words)?, parse_number(words)?);
+            Event::MouseWheel(if lines { WheelDelta::Lines(delta) } else { WheelDelta::Pixels(delta) })
+        }
+        Some("touch") => {
+            let phase = match words.next() {
+                Some("started") => TouchPhase::Started,
+                Some("moved") => TouchPhase::Moved,
+                Some("ended") => TouchPhase::Ended,
+                Some("cancelled") => TouchPhase::Cancelled,
+                _ => return Err("invalid touch phase"),
+            };
+
+            Event::Touch(TouchEvent {
+                phase,
+                position: Vec2::new(parse_number(words)?, parse_number(words)?),
+                id: parse_number(words)?,
+            })
+        }
+        Some("key") => {
+            let key = if words.clone().next() == Some("-") {
+                words.next();
+                None
+            } else {
+                Some(words.next().and_then(Key::from_name).ok_or("invalid key name")?)
+            };
+
+            Event::Key(KeyEvent {
+                key,
+                scan_code: ScanCode(parse_number(words)?),
+                pressed: parse_bool(words)?,
+            })
+        }
+        Some("char") => {
+            let char = ::std::char::from_u32(parse_number(words)?).ok_or("invalid character")?;
+            Event::Char(char)
+        }
+        Some("navigate") => {
+            let direction = match words.next() {
+                Some("up") => Direction::Up,
+                Some("down") => Direction::Down,
+                Some("left") => Direction::Left,
+                Some("right") => Direction::Right,
+                _ => return Err("invalid direction"),
+            };
+
+            Event::Navigate(direction)
+        }
+        Some(_) => return Err("unknown event"),
+        None => return Err("expected event"),
+    };
+
+    Ok(event)
+}
+
+fn parse_number<T: ::std::str::FromStr>(words: &mut SplitWhitespace) -> Result<T, &'static str> {
+    words.next().and_then(|word| word.parse().ok()).ok_or("expected number")
+}
+
+fn parse_bool(words: &mut SplitWhitespace) -> Result<bool, &'static str> {
+    match words.next() {
+        Some("0") => Ok(false),
+        Some("1") => Ok(true),
+        _ => Err("expected 0 or 1"),
+    }
+}
+
+fn mouse_button_name(button: MouseButton) -> &'static str {
+    match button {
+        MouseButton::Primary => "primary",
+        MouseButton::Secondary => "secondary",
+        MouseButton::Middle => "middle",
+        MouseButton::X1 => "x1",
+        MouseButton::X2 => "x2",
+    }
+}
+
+fn touch_phase_name(phase: TouchPhase) -> &'static str {
+    match phase {
+        TouchPhase::Started => "started",
+        TouchPhase::Moved => "moved",
+        TouchPhase::Ended => "ended",
+        TouchPhase::Cancelled => "cancelled",
+    }
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::Up => "up",
+        Direction::Down => "down",
+        Direction::Left => "left",
+        Direction::Right => "right",
+    }
+}
+
+#[test]
+fn test_record_and_replay() {
+    let frames = vec![
+        vec![
+            Entry::Event(Event::MouseEntered),
+            Entry::Event(Event::MouseMoved(Vec2::new(10.5, -0.1))),
+            Entry::Event(Event::MouseButton(MouseButton::Secondary, true)),
+            Entry::Event(Event::MouseWheel(WheelDelta::Lines(Vec2::new(0.0, -2.0)))),
+            Entry::Event(Event::MouseWheel(WheelDelta::Pixels(Vec2::new(1.5, 0.0)))),
+        ],
+        vec![],
+        vec![
+            Entry::Event(Event::Touch(TouchEvent { phase: TouchPhase::Moved, position: Vec2::new(1.0/3.0, 1e10), id: 17 })),
+            Entry::Event(Event::Key(KeyEvent { key: Some(Key::Yen), scan_code: ScanCode(125), pressed: true })),
+            Entry::Event(Event::Key(KeyEvent { key: Some(Key::Key1), scan_code: ScanCode(2), pressed: false })),
+            Entry::Event(Event::Key(KeyEvent { key: None, scan_code: ScanCode(0xE05B), pressed: true })),
+            Entry::Event(Event::Char(' ')),
+            Entry::Event(Event::Char('\n')),
+            Entry::Event(Event::Char('ё')),
+            Entry::Event(Event::MouseLeft),
+        ],
+        vec![
+            Entry::ImePreedit("にほん".to_owned(), Some((3, 9))),
+            Entry::ImePreedit("a b%\t".to_owned(), None),
+            Entry::ImePreedit(String::new(), None),
+            Entry::ImeCommit("日本".to_owned()),
+            Entry::ImeCommit(String::new()),
+            Entry::Event(Event::Navigate(Direction::Left)),
+        ],
+    ];
+
+    let mut recorder = Recorder::new(Vec::new()).unwrap();
+    for (i, entries) in frames.iter().enumerate() {
+        for entry in entries {
+            recorder.record_at(i as f64*0.25, entry).unwrap();
+        }
+        recorder.advance().unwrap();
+    }
+
+    let data = recorder.into_inner();
+    let mut replayer = Replayer::new(&data[..]).unwrap();
+
+    for (i, entries) in frames.iter().enumerate() {
+        let frame = replayer.next_frame().unwrap().unwrap();
+        assert_eq!(frame.number, i as u64);
+        assert_eq!(frame.entries, entries.iter().map(|entry| (i as f64*0.25, entry.clone())).collect::<Vec<_>>());
+    }
+
+    assert_eq!(replayer.next_frame().unwrap(), None);
+
+    // Text from the input method is passed to the input.
+    let mut recorder = Recorder::new(Vec::new()).unwrap();
+    recorder.record_preedit("にほん", None).unwrap();
+    recorder.advance().unwrap();
+    recorder.record_commit("日本").unwrap();
+    recorder.advance().unwrap();
+
+    let data = recorder.into_inner();
+    let mut replayer = Replayer::new(&data[..]).unwrap();
+    let mut input = Input::default();
+
+    assert!(replayer.advance(&mut input).unwrap());
+    assert_eq!(input.keyboard.composition().map(|preedit| &preedit.text[..]), Some("にほん"));
+    input.advance();
+    assert!(replayer.advance(&mut input).unwrap());
+    assert_eq!(input.keyboard.composition(), None);
+    assert_eq!(input.keyboard.input(), "日本");
+}
+
+#[test]
+fn test_replay_errors() {
+    assert!(Replayer::new(&b"not a recording\n"[..]).is_err());
+    assert!(Replayer::new(&b"lithium-events 1\n0 0 key 30 1 1\n"[..]).is_err());
+
+    for &data in &[
+        &b"lithium-events 2\n0 0 mouse-moved 1\n"[..],
+        &b"lithium-events 2\n0 0 key 30 1 1\n"[..],
+        &b"lithium-events 2\n0 0 key Key 1 1\n"[..],
+        &b"lithium-events 2\n0 0 key 1 1\n"[..],
+        &b"lithium-events 2\n0 0 mouse-left 1\n"[..],
+        &b"lithium-events 2\n0 0 mouse-left\n1 0 advance\n"[..],
+        &b"lithium-events 2\n0 0 ime-commit text\n"[..],
+        &b"lithium-events 2\n0 0 ime-commit '%4\n"[..],
+        &b"lithium-events 2\n0 0 ime-preedit 'text 1\n"[..],
+        &b"lithium-events 2\n0 0 navigate forward\n"[..],
+    ] {
+        let mut replayer = Replayer::new(data).unwrap();
+        assert!(replayer.next_frame().is_err());
+    }
+}
@@ -12,6 +12,9 @@ pub struct Layout {
 
     previous_constraints: Constraints,
     new_constraints: Constraints,
+
+    /// Constraints of the last frame differ from the ones of the frame before it.
+    changed: bool,
 }
 
 impl Default for Layout {
@@ -22,6 +25,8 @@ impl Default for Layout {
 
             previous_constraints: Constraints::default(),
             new_constraints: Constraints::default(),
+
+            changed: false,
         }
     }
 }
@@ -38,6 +43,7 @@ impl Layout {
 
         let mut previous_i = 0;
         let mut new_i = 0;
+        let mut changed = false;
 
         while previous_i < self.previous_constraints.constraints.len() && new_i < self.new_constraints.constraints.len() {
             let previous_constraint = &self.previous_constraints.constraints[previous_i];
@@ -57,15 +63,18 @@ impl Layout {
                         new_constraint.strength,
                     ));
                     new_i += 1;
+                    changed = true;
                 }
                 Ordering::Less => {
                     self.solver.remove_constraint(previous_constraint.tag.unwrap());
                     previous_i += 1;
+                    changed = true;
                 }
             }
         }
 
         for constraint in &mut self.new_constraints.constraints[new_i..] {
+            changed = true;
             constraint.tag = Some(self.solver.add_constraint(
                 constraint.positive,
                 constraint.constant,
@@ -75,17 +84,36 @@ impl Layout {
         }
 
         for constraint in &self.previous_constraints.constraints[previous_i..] {
+            changed = true;
             self.solver.remove_constraint(constraint.tag.unwrap());
         }
 
         self.previous_constraints.clear();
         swap(&mut self.previous_constraints, &mut self.new_constraints);
+        self.changed = changed;
+    }
+
+    /// Constraints were added or removed by the last `advance`, so the solved values may differ from the ones used during
+    /// the frame before it. When nothing changed the layout has settled.
+    pub fn changed(&self) -> bool {
+        self.changed
     }
 
     pub fn constraint<E: Expression>(&mut self, constraint: Constraint<E>) {
         self.new_constraints.push(constraint, self.current_strength);
     }
 
+    /// Add the constraints created by `f` with the `strength`, by default constraints are `Strength::Medium`.
+    ///
+    /// When constraints conflict the stronger ones are satisfied, for example a weak preferred width gives way to
+    /// minimum and maximum widths.
+    pub fn with_strength<F: FnOnce(&mut Layout)>(&mut self, strength: Strength, f: F) {
+        let prev_strength = self.current_strength;
+        self.current_strength = strength;
+        f(self);
+        self.current_strength = prev_strength;
+    }
+
     pub fn keep(&mut self, var: Var) {
         let prev_value = self.prev_value(var);
 
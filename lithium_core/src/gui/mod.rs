@@ -1,17 +1,30 @@
 use self::layout::Layout;
 use self::input::Input;
 use self::scene::{Scene, Element};
-use {Id, Rect, Theme};
-use theme::{ElementKind, StyleVariant};
+use self::hover::Hover;
+use self::focus::Focus;
+use self::clipboard::{Clipboard, MemoryClipboard};
+use self::input::Direction;
+use {Id, Rect, Vec2, Theme};
+use theme::{ElementKind, StyleVariant, PseudoClass};
 
 pub mod layout;
 pub mod input;
 pub mod scene;
+pub mod hover;
+pub mod focus;
+pub mod clipboard;
 
 pub struct Gui {
     pub layout: Layout,
     pub scene: Scene,
     pub input: Input,
+    pub hover: Hover,
+    pub focus: Focus,
+    pub clipboard: Box<Clipboard>,
+
+    window_size: Option<Vec2<f64>>,
+    next_frame: bool,
 }
 
 impl Gui {
@@ -20,13 +33,76 @@ impl Gui {
             layout: Layout::default(),
             scene: Scene::new(default_theme),
             input: Input::default(),
+            hover: Hover::default(),
+            focus: Focus::default(),
+            clipboard: Box::new(MemoryClipboard::default()),
+
+            window_size: None,
+            next_frame: false,
         }
     }
 
+    /// Set size of the window in which the top-level widget is shown. The platform layer should call this each frame.
+    pub fn set_window_size(&mut self, size: Vec2<f64>) {
+        self.window_size = Some(size);
+    }
+
+    /// Place of the window if its size is known. Popups are kept inside of it.
+    pub fn window(&self) -> Option<Rect<f64>> {
+        self.window_size.map(|size| Rect { left: 0.0, top: 0.0, right: size.x, bottom: size.y })
+    }
+
     pub fn element<F, R>(&mut self, id: Id, kind: ElementKind, f: F) -> R
         where F: FnOnce(&mut Gui) -> R
     {
-        let style = self.scene.element_style(kind);
+        self.element_in_state(id, kind, &[], f)
+    }
+
+    /// Same as `element` but the style is chosen according to the state of the element described by `pseudo_classes`.
+    ///
+    /// The `hover` and `focus` pseudo-classes are added automatically when the element is hovered or focused.
+    pub fn element_in_state<F, R>(&mut self, id: Id, kind: ElementKind, pseudo_classes: &[PseudoClass], f: F) -> R
+        where F: FnOnce(&mut Gui) -> R
+    {
+        self.build_element(id, kind, pseudo_classes, false, f)
+    }
+
+    /// Build the element `id` as a part of a widget whose element has the same `kind`, styled with the `style_variant`
+    /// (`Kind.variant` in CSS) and in the state described by `pseudo_classes` like in `element_in_state`.
+    ///
+    /// Only the styles of the part itself apply to it, rules for the whole widget (like `Kind` or `Kind:checked`) don't,
+    /// whatever variant the widget is styled with. Elements inside of the part are styled with the `style_variant`.
+    pub fn part<F, R>(&mut self, id: Id, kind: ElementKind, style_variant: StyleVariant, pseudo_classes: &[PseudoClass], f: F) -> R
+        where F: FnOnce(&mut Gui) -> R
+    {
+        let old_style = self.scene.swap_style_variant(style_variant);
+        let result = self.build_element(id, kind, pseudo_classes, true, f);
+        self.scene.swap_style_variant(old_style); // FIXME: execute even in case of panic
+
+        result
+    }
+
+    fn build_element<F, R>(&mut self, id: Id, kind: ElementKind, pseudo_classes: &[PseudoClass], part: bool, f: F) -> R
+        where F: FnOnce(&mut Gui) -> R
+    {
+        let style_in_state = |scene: &Scene, pseudo_classes: &[PseudoClass]| if part {
+            scene.part_style_in_state(kind, pseudo_classes)
+        } else {
+            scene.element_style_in_state(kind, pseudo_classes)
+        };
+
+        let style = if self.hover.is_hovered(id) || self.focus.is_focused(id) {
+            let mut pseudo_classes = pseudo_classes.to_vec();
+            if self.hover.is_hovered(id) {
+                pseudo_classes.push(pseudo_class!("hover"));
+            }
+            if self.focus.is_focused(id) {
+                pseudo_classes.push(pseudo_class!("focus"));
+            }
+            style_in_state(&self.scene, &pseudo_classes)
+        } else {
+            style_in_state(&self.scene, pseudo_classes)
+        };
         let place = self.layout.prev_value_rect(Rect::from(id));
 
         self.scene.start_element();
@@ -41,6 +117,35 @@ impl Gui {
         result
     }
     
+    /// Build the element `id` as a popup painted above all other elements.
+    ///
+    /// Popups get the mouse before the other widgets while the pointer is over them, see `Input::get_mouse`. They also
+    /// form a focus scope so directional navigation stays inside while one is focused.
+    pub fn popup<F, R>(&mut self, id: Id, kind: ElementKind, f: F) -> R
+        where F: FnOnce(&mut Gui) -> R
+    {
+        self.scene.start_layer();
+        self.input.start_popup(id);
+
+        let result = self.focus_scope(id, |gui| gui.element(id, kind, f));
+
+        self.input.end_popup();
+        self.scene.close_layer(); // FIXME: execute even in case of panic
+
+        result
+    }
+
+    /// Build the element `id` as a modal popup which blocks the widgets underneath.
+    ///
+    /// The element should cover the whole window so that the mouse doesn't reach anything outside of it. Focus is trapped
+    /// inside during this frame, see `Focus::trap`.
+    pub fn modal<F, R>(&mut self, id: Id, kind: ElementKind, f: F) -> R
+        where F: FnOnce(&mut Gui) -> R
+    {
+        self.focus.trap(id);
+        self.popup(id, kind, f)
+    }
+
     pub fn themed<F: FnOnce(&mut Gui)>(&mut self, theme: Theme, f: F) -> Theme {
         let old_theme = self.scene.swap_theme(theme);
         f(self);
@@ -53,9 +158,128 @@ impl Gui {
         self.scene.swap_style_variant(old_style) // FIXME: execute even in case of panic
     }
 
+    /// Element is under the pointer. This is based on the element places from the previous frame.
+    pub fn is_hovered(&self, id: Id) -> bool {
+        self.hover.is_hovered(id)
+    }
+
+    /// Pointer has entered the element during the previous frame.
+    pub fn hover_entered(&self, id: Id) -> bool {
+        self.hover.entered(id)
+    }
+
+    /// Pointer has left the element during the previous frame.
+    pub fn hover_left(&self, id: Id) -> bool {
+        self.hover.left(id)
+    }
+
+    /// Allow the element to receive focus through directional navigation during this frame.
+    ///
+    /// Element place from the previous frame is used to find the element in a direction.
+    pub fn focusable(&mut self, id: Id) {
+        let place = self.layout.prev_value_rect(Rect::from(id));
+        self.focus.focusable(id, place);
+    }
+
+    /// Directional navigation prefers elements made focusable inside of the same scope as the focused element.
+    pub fn focus_scope<F, R>(&mut self, id: Id, f: F) -> R
+        where F: FnOnce(&mut Gui) -> R
+    {
+        self.focus.push_scope(id);
+        let result = f(self);
+        self.focus.pop_scope(); // FIXME: execute even in case of panic
+        result
+    }
+
+    pub fn is_focused(&self, id: Id) -> bool {
+        self.focus.is_focused(id)
+    }
+
+    pub fn focus(&mut self, id: Id) {
+        self.focus.focus(id);
+    }
+
+    /// Focused element is inside the focus scope `id`. Only the elements made focusable so far in this frame are known.
+    pub fn is_focused_within(&self, id: Id) -> bool {
+        self.focus.is_focused_within(id)
+    }
+
+    /// Choose where the focus goes from the element `from` in the `direction` instead of finding it by element places.
+    ///
+    /// `None` keeps the focus on `from`. Containers should call this each frame while they want to override navigation.
+    pub fn override_navigation(&mut self, from: Id, direction: Direction, to: Option<Id>) {
+        self.focus.override_navigation(from, direction, to);
+    }
+
     pub fn advance(&mut self) {
+        let animating = self.input.animation_frame_requested();
+        self.hover.update(self.scene.commands(), self.input.mouse_position());
+
+        for &direction in self.input.navigation() {
+            self.focus.navigate(direction);
+        }
+        self.focus.advance();
+
         self.input.advance();
         self.scene.advance();
         self.layout.advance();
+
+        let layout = &self.layout;
+        self.input.advance_popups(|id| layout.prev_value_rect(Rect::from(id)));
+
+        self.next_frame = animating || self.layout.changed() || self.input.keyboard.repeat_pending();
+    }
+
+    /// Next frame should be shown without waiting for events. This is valid after `advance`.
+    ///
+    /// It is the case when some widget requested an animation frame with `Input::request_animation_frame`, while the
+    /// layout is settling because elements are placed according to the layout solved at the end of the previous frame
+    /// and while a held key is repeated by `KeyRepeat::Custom`.
+    pub fn next_frame_requested(&self) -> bool {
+        self.next_frame
     }
 }
+
+#[test]
+fn test_next_frame_requested() {
+    use std::time::Duration;
+    use self::input::{Event, KeyEvent, Key, ScanCode, KeyRepeat};
+
+    let mut gui = Gui::new(Theme::empty());
+    let id = Id::unique();
+
+    let frame = |gui: &mut Gui, width: f64| {
+        gui.element(id, element_kind!("Test"), |_gui| {});
+        let place = Rect::from(id);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == width,
+            (place.bottom) == 10.0,
+        ]);
+
+        gui.advance();
+        gui.next_frame_requested()
+    };
+
+    // The first frame is shown before the layout is solved, the second one shows the solved layout.
+    assert!(frame(&mut gui, 100.0));
+    assert!(!frame(&mut gui, 100.0));
+    assert_eq!(gui.layout.prev_value_rect(Rect::from(id)).right, 100.0);
+
+    // Changed layout is shown during the next frame without waiting for events, after that the platform can wait.
+    assert!(frame(&mut gui, 200.0));
+    assert_eq!(gui.layout.prev_value_rect(Rect::from(id)).right, 200.0);
+    assert!(!frame(&mut gui, 200.0));
+
+    gui.input.request_animation_frame();
+    assert!(frame(&mut gui, 200.0));
+    assert!(!frame(&mut gui, 200.0));
+
+    // Held key is repeated without any events when the repeats are generated from the time.
+    gui.input.keyboard.set_repeat(KeyRepeat::Custom { delay: Duration::from_millis(500), interval: Duration::from_millis(50) });
+    gui.input.event(&Event::Key(KeyEvent { key: Some(Key::A), scan_code: ScanCode(0), pressed: true }));
+    assert!(frame(&mut gui, 200.0));
+    gui.input.event(&Event::Key(KeyEvent { key: Some(Key::A), scan_code: ScanCode(0), pressed: false }));
+    assert!(!frame(&mut gui, 200.0));
+}
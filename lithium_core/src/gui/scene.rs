@@ -2,15 +2,33 @@ use std::any::Any;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::cell::RefCell;
-use std::mem::swap;
+use std::mem::{swap, replace};
 use {Color, Vec2, Rect, Theme, Id};
-use theme::{ColorId, ElementKind, ElementStyle, StyleVariant};
+use image;
+use theme::{ColorId, ElementKind, ElementStyle, StyleVariant, PseudoClass};
 
 #[derive(PartialEq)]
 pub struct Scene {
     theme: Theme,
     style_variant: StyleVariant,
     commands: Vec<Command>,
+
+    /// Number of started elements which are not closed yet in the layer being built.
+    depth: usize,
+
+    /// Layers finished inside of the open elements, they are added after these elements close.
+    pending: Vec<Command>,
+
+    /// Layers containing the one being built.
+    outer_layers: Vec<Layer>,
+}
+
+/// Commands of a layer with the state of its building.
+#[derive(PartialEq)]
+struct Layer {
+    commands: Vec<Command>,
+    depth: usize,
+    pending: Vec<Command>,
 }
 
 impl Scene {
@@ -19,6 +37,10 @@ impl Scene {
             theme: theme,
             style_variant: StyleVariant::default(),
             commands: Vec::new(),
+
+            depth: 0,
+            pending: Vec::new(),
+            outer_layers: Vec::new(),
         }
     }
 
@@ -30,6 +52,14 @@ impl Scene {
         self.theme.element_style(self.style_variant, kind).unwrap_or(Arc::new(ElementStyle::default()))
     }
 
+    pub fn element_style_in_state(&self, kind: ElementKind, pseudo_classes: &[PseudoClass]) -> Arc<ElementStyle> {
+        self.theme.element_style_in_state(self.style_variant, kind, pseudo_classes).unwrap_or(Arc::new(ElementStyle::default()))
+    }
+
+    pub fn part_style_in_state(&self, kind: ElementKind, pseudo_classes: &[PseudoClass]) -> Arc<ElementStyle> {
+        self.theme.part_style_in_state(self.style_variant, kind, pseudo_classes).unwrap_or(Arc::new(ElementStyle::default()))
+    }
+
     pub fn text(&mut self, text: Text) {
         self.commands.push(Command::Text(text));
     }
@@ -38,12 +68,51 @@ impl Scene {
         self.commands.push(Command::Mesh(mesh));
     }
 
+    pub fn image(&mut self, image: Image) {
+        self.commands.push(Command::Image(image));
+    }
+
     pub fn start_element(&mut self) {
         self.commands.push(Command::StartElement);
+        self.depth += 1;
     }
 
     pub fn close_element(&mut self, element: Element) {
         self.commands.push(Command::CloseElement(element));
+        self.depth -= 1;
+
+        if self.depth == 0 {
+            self.commands.append(&mut self.pending);
+        }
+    }
+
+    /// Commands from now until `close_layer` form a layer which is painted above everything built before it.
+    ///
+    /// The layer is added after the top-level element containing it, so it is not clipped by the elements it was built
+    /// in and it is above them for hovering as well.
+    pub fn start_layer(&mut self) {
+        self.outer_layers.push(Layer {
+            commands: replace(&mut self.commands, Vec::new()),
+            depth: self.depth,
+            pending: replace(&mut self.pending, Vec::new()),
+        });
+
+        self.depth = 0;
+    }
+
+    pub fn close_layer(&mut self) {
+        let outer = self.outer_layers.pop().expect("no layer to close");
+
+        let mut layer = replace(&mut self.commands, outer.commands);
+        layer.append(&mut self.pending);
+        self.depth = outer.depth;
+        self.pending = outer.pending;
+
+        if self.depth == 0 {
+            self.commands.append(&mut layer);
+        } else {
+            self.pending.append(&mut layer);
+        }
     }
 
     pub fn swap_theme(&mut self, mut theme: Theme) -> Theme {
@@ -62,6 +131,9 @@ impl Scene {
 
     pub fn advance(&mut self) {
         self.commands.clear();
+        self.pending.clear();
+        self.outer_layers.clear();
+        self.depth = 0;
     }
 }
 
@@ -71,6 +143,7 @@ pub enum Command {
     CloseElement(Element),
     Text(Text),
     Mesh(Mesh),
+    Image(Image),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -112,9 +185,80 @@ pub struct Vertex {
     pub color: Color,
 }
 
+/// Image drawn into `place` and clipped to `clip`, both are in window coordinates.
+///
+/// Renderers should upload the pixels once and then only the changed regions, see `image::ImagesWatcher`.
+#[derive(Clone, Debug)]
+pub struct Image {
+    pub id: Id,
+    pub image: Arc<image::Image>,
+    pub place: Rect<f64>,
+    pub clip: Rect<f64>,
+    pub sampling: Sampling,
+}
+
+impl PartialEq for Image {
+    fn eq(&self, other: &Image) -> bool {
+        self.id == other.id && Arc::ptr_eq(&self.image, &other.image) && self.place == other.place &&
+            self.clip == other.clip && self.sampling == other.sampling
+    }
+}
+
+/// How image pixels are interpolated when the image is scaled.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Sampling {
+    /// Interpolate between pixels, suitable for photos.
+    Smooth,
+    /// Keep the pixels sharp, suitable for pixel art and magnified views.
+    Pixelated,
+}
+
+impl Default for Sampling {
+    fn default() -> Self {
+        Sampling::Smooth
+    }
+}
+
 /// Anything that can be rendered.
 ///
 /// There are no methods because renderer should downcast it to the concrete type.
 ///
 /// This trait is mostly to prevent accidentally trying to draw things which cannot be rendered.
 pub trait Render: 'static + Any {}
+
+#[test]
+fn test_layers() {
+    use theme::element_kind;
+
+    let mut scene = Scene::new(Theme::empty());
+    let (root, child, popup, nested) = (Id::unique(), Id::unique(), Id::unique(), Id::unique());
+
+    let close = |scene: &mut Scene, id| scene.close_element(Element {
+        id,
+        place: Rect { left: 0.0, top: 0.0, right: 10.0, bottom: 10.0 },
+        kind: element_kind("Test"),
+        style: Arc::new(ElementStyle::default()),
+    });
+
+    scene.start_element();
+        scene.start_element();
+            scene.start_layer();
+            scene.start_element();
+                scene.start_layer();
+                scene.start_element();
+                close(&mut scene, nested);
+                scene.close_layer();
+            close(&mut scene, popup);
+            scene.close_layer();
+        close(&mut scene, child);
+    close(&mut scene, root);
+
+    let closed: Vec<Id> = scene.commands().iter().filter_map(|command| match *command {
+        Command::CloseElement(ref element) => Some(element.id),
+        _ => None,
+    }).collect();
+
+    // Layers follow the top-level element they were built in and nested layers follow their parents.
+    assert_eq!(closed, vec![child, root, popup, nested]);
+    assert!(scene.commands()[4] == Command::StartElement);
+}
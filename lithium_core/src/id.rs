@@ -9,6 +9,12 @@ impl Id {
     pub fn unique() -> Self {
         Id(thread_rng().next_u64(), thread_rng().next_u64())
     }
+
+    /// Id derived from this one and the `key`. The same key always gives the same id, so this can be used to keep ids of
+    /// dynamically created children stable.
+    pub fn child(self, key: u64) -> Self {
+        Id(hash_combine(self.0, key), hash_combine(self.1, key ^ 0x5be0cd19137e2179))
+    }
 }
 
 impl From<Id> for Rect<Var> {
@@ -25,4 +31,13 @@ impl From<Id> for Rect<Var> {
 fn hash_combine(a: u64, b: u64) -> u64 {
     // is this good?
     a ^ (b.wrapping_add(a.rotate_left(17)).wrapping_add(a >> 2))
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_child_ids() {
+    let parent = Id::unique();
+
+    assert_eq!(parent.child(5), parent.child(5));
+    assert!(parent.child(5) != parent.child(6));
+    assert!(parent.child(5) != Id::unique().child(5));
+}
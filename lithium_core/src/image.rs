@@ -62,7 +62,7 @@ impl Image {
 		for y in region.top..region.bottom {			
 			for x in region.left..region.right {
 				let index = self.index(Vec2::new(x, y));
-				self.pixels[index] = data[((y - region.top)*region.width() + x - region.bottom) as usize];
+				self.pixels[index] = data[((y - region.top)*region.width() + x - region.left) as usize];
 			}
 		}
 	}
@@ -75,7 +75,7 @@ impl Image {
 			for x in region.left..region.right {
 				let index = self.index(Vec2::new(x, y));
 
-				let new_pixel = data[((y - region.top)*region.width() + x - region.bottom) as usize];
+				let new_pixel = data[((y - region.top)*region.width() + x - region.left) as usize];
 				let new_pixel = Color::from_rgb24(new_pixel[0], new_pixel[1], new_pixel[2]);
 
 				self.pixels[index] = new_pixel;
@@ -91,7 +91,7 @@ impl Image {
 			for x in region.left..region.right {
 				let index = self.index(Vec2::new(x, y));
 
-				let new_pixel = data[((y - region.top)*region.width() + x - region.bottom) as usize];
+				let new_pixel = data[((y - region.top)*region.width() + x - region.left) as usize];
 				let new_pixel = Color::from_rgba32(new_pixel[0], new_pixel[1], new_pixel[2], new_pixel[3]);
 
 				self.pixels[index] = new_pixel;
@@ -135,7 +135,7 @@ impl Image {
 
 	fn invalidate(&mut self, region: Rect<i32>) {
 		let cells_row_len = (self.size.x - 1)/CELL_SIZE + 1;
-		let cells_column_len = (self.size.x - 1)/CELL_SIZE + 1;
+		let cells_column_len = (self.size.y - 1)/CELL_SIZE + 1;
 
 		if self.cells.is_empty() {
 			self.cells = iter::repeat(0).take((cells_row_len*cells_column_len) as usize).collect();
@@ -211,31 +211,62 @@ impl<T> ImagesWatcher<T> {
 impl<T> ImageWatcher<T> {
 	fn get_and_reset_invalid_region(&mut self, image: &Image) -> Option<Rect<i32>> {
 		if image.cells == self.cells {
-			None
-		} else {
-			let mut region: Option<Rect<i32>> = None;
-
-			let cells_row_len = (image.size.x - 1)/CELL_SIZE + 1;
-
-			for y in 0..(((image.size.y - 1)/CELL_SIZE) + 1) {
-				for x in 0..(((image.size.x - 1)/CELL_SIZE) + 1) {
-					let index = (y*cells_row_len + x) as usize;
-					if self.cells[index] != image.cells[index] {
-						if let Some(ref mut region) = region {
-							*region |= Rect::from_top_left_and_size(Vec2::new(x, y), Vec2::new(1, 1));
-						}
-					}
-				}
-			}
+			return None;
+		}
 
-			region.map(|region| {
-				Rect {
-					left: region.left*CELL_SIZE,
-					top: region.top*CELL_SIZE,
-					right: min(region.right*CELL_SIZE, image.size.x),
-					bottom: min(region.bottom*CELL_SIZE, image.size.x),
-				}
-			})
+		let mut region: Option<Rect<i32>> = None;
+
+		// Cells are allocated on the first invalidation, so the watcher may have seen the image without them.
+		let cells_row_len = (image.size.x - 1)/CELL_SIZE + 1;
+
+		for (index, &cell) in image.cells.iter().enumerate() {
+			if self.cells.get(index).cloned().unwrap_or(0) != cell {
+				let index = index as i32;
+				let cell = Rect::from_top_left_and_size(Vec2::new(index % cells_row_len, index/cells_row_len), Vec2::new(1, 1));
+
+				region = Some(match region {
+					Some(region) => region | cell,
+					None => cell,
+				});
+			}
 		}
+
+		self.cells = image.cells.clone();
+
+		region.map(|region| {
+			Rect {
+				left: region.left*CELL_SIZE,
+				top: region.top*CELL_SIZE,
+				right: min(region.right*CELL_SIZE, image.size.x),
+				bottom: min(region.bottom*CELL_SIZE, image.size.y),
+			}
+		})
 	}
 }
+
+#[test]
+fn test_images_watcher() {
+	let mut image = Image::new(Vec2::new(100, 150), vec![Color::black(); 100*150]);
+	let mut watcher = ImagesWatcher::new();
+
+	let state = |watcher: &mut ImagesWatcher<u32>, image: &Image| match watcher.get_image_state(image, || 7) {
+		ImageState::New(data) => (data, "new", None),
+		ImageState::Same(data) => (data, "same", None),
+		ImageState::Updated(data, region) => (data, "updated", Some(region)),
+	};
+
+	assert_eq!(state(&mut watcher, &image), (7, "new", None));
+	assert_eq!(state(&mut watcher, &image), (7, "same", None));
+
+	// Cells touched by the update are reported once, clamped to the image size.
+	image.update(Some(Rect { left: 70, top: 60, right: 72, bottom: 140 }), &[Color::white(); 2*80]);
+	assert_eq!(image.pixel(Vec2::new(71, 139)), Color::white());
+	assert_eq!(state(&mut watcher, &image), (7, "updated", Some(Rect { left: 64, top: 0, right: 100, bottom: 150 })));
+	assert_eq!(state(&mut watcher, &image), (7, "same", None));
+
+	*image.pixel_mut(Vec2::new(0, 0)) = Color::white();
+	assert_eq!(state(&mut watcher, &image), (7, "updated", Some(Rect { left: 0, top: 0, right: 64, bottom: 64 })));
+
+	drop(image);
+	assert_eq!(watcher.deleted().collect::<Vec<_>>(), vec![7]);
+}
@@ -12,6 +12,7 @@ pub mod geometry;
 mod color;
 mod id;
 pub mod image;
+pub mod path;
 pub mod solver;
 pub mod font;
 pub mod gui;
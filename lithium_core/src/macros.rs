@@ -20,6 +20,13 @@ macro_rules! style_variant {
     }
 }
 
+#[macro_export]
+macro_rules! pseudo_class {
+    ($e:expr) => {
+        $crate::theme::pseudo_class($e)
+    }
+}
+
 #[macro_export]
 macro_rules! add_constraints {
     ($layout:expr, [$(($left:expr) $cmp:tt $right:expr,)*]) => {
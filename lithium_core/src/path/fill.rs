@@ -0,0 +1,258 @@
+use {Color, Vec2};
+use gui::scene::MeshVertices;
+use super::{Path, FRINGE, perp, miter, quad};
+
+/// Distance between the boundaries of two bands below which they are merged.
+const EPSILON: f64 = 1e-9;
+
+/// Which parts of a self-intersecting path or of a path with several subpaths are inside of it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum FillRule {
+    /// Points around which the path winds at least once in either direction.
+    NonZero,
+    /// Points which are surrounded by an odd number of subpaths, like the `evenodd` rule of SVG.
+    EvenOdd,
+}
+
+impl Default for FillRule {
+    fn default() -> Self {
+        FillRule::NonZero
+    }
+}
+
+impl FillRule {
+    fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct FillStyle {
+    pub rule: FillRule,
+
+    /// Add a one pixel wide band fading out along the outside of the edges.
+    pub anti_alias: bool,
+}
+
+impl Default for FillStyle {
+    fn default() -> Self {
+        FillStyle {
+            rule: FillRule::default(),
+            anti_alias: true,
+        }
+    }
+}
+
+/// Segment of the outline directed downwards.
+struct Edge {
+    top: Vec2<f64>,
+    bottom: Vec2<f64>,
+
+    /// `1` if the outline goes down along the edge and `-1` if it goes up.
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f64) -> f64 {
+        self.top.x + (self.bottom.x - self.top.x)*(y - self.top.y)/(self.bottom.y - self.top.y)
+    }
+}
+
+impl Path {
+    /// Append triangles covering the inside of the path to `vertices`, every subpath is treated as closed.
+    ///
+    /// The path is cut by horizontal lines going through its vertices and self-intersections into bands in which the
+    /// edges do not cross, so the inside of a band is a row of trapezoids.
+    pub fn fill(&self, style: &FillStyle, color: Color, vertices: &mut MeshVertices) {
+        let edges = self.edges();
+
+        let mut boundaries: Vec<f64> = edges.iter().flat_map(|edge| vec![edge.top.y, edge.bottom.y]).collect();
+        for (i, a) in edges.iter().enumerate() {
+            boundaries.extend(edges[i + 1..].iter().filter_map(|b| crossing(a, b)));
+        }
+
+        boundaries.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        boundaries.dedup_by(|a, b| *a - *b < EPSILON);
+
+        let mut active = Vec::new();
+        for band in boundaries.windows(2) {
+            let (top, bottom) = (band[0], band[1]);
+            let middle = (top + bottom)/2.0;
+
+            active.clear();
+            active.extend(edges.iter().filter(|edge| edge.top.y < middle && edge.bottom.y > middle));
+            active.sort_by(|a, b| a.x_at(middle).partial_cmp(&b.x_at(middle)).unwrap());
+
+            let mut winding = 0;
+            let mut left = None;
+            for edge in &active {
+                let was_inside = style.rule.is_inside(winding);
+                winding += edge.winding;
+
+                match (was_inside, style.rule.is_inside(winding)) {
+                    (false, true) => left = Some(edge),
+                    (true, false) => {
+                        let left = left.unwrap();
+                        quad(vertices, [
+                            (Vec2::new(left.x_at(top), top), color),
+                            (Vec2::new(edge.x_at(top), top), color),
+                            (Vec2::new(edge.x_at(bottom), bottom), color),
+                            (Vec2::new(left.x_at(bottom), bottom), color),
+                        ]);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if style.anti_alias {
+            self.fill_fringe(style.rule, &edges, color, vertices);
+        }
+    }
+
+    /// Fringe along the edges which separate the inside from the outside, its corners are mitered.
+    fn fill_fringe(&self, rule: FillRule, edges: &[Edge], color: Color, vertices: &mut MeshVertices) {
+        let is_inside = |point: Vec2<f64>| {
+            let winding = edges.iter()
+                .filter(|edge| edge.top.y <= point.y && edge.bottom.y > point.y && edge.x_at(point.y) < point.x)
+                .map(|edge| edge.winding)
+                .sum();
+
+            rule.is_inside(winding)
+        };
+
+        for polyline in &self.polylines {
+            let points = &polyline.points;
+            let len = points.len();
+            if len < 3 {
+                continue;
+            }
+
+            // Outward normal of the segment starting at each point, if it is on the boundary of the inside.
+            let normals: Vec<Option<Vec2<f64>>> = (0..len).map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % len]);
+                let normal = perp(b - a).normalize();
+                let middle = (a + b)*0.5;
+
+                match (is_inside(middle + normal*0.01), is_inside(middle - normal*0.01)) {
+                    (false, true) => Some(normal),
+                    (true, false) => Some(normal*-1.0),
+                    _ => None,
+                }
+            }).collect();
+
+            // Offset of the fringe at a point between the segments ending and starting there.
+            let offset = |prev: Option<Vec2<f64>>, next: Vec2<f64>| match prev {
+                Some(prev) => miter(prev, next, 4.0)*FRINGE,
+                None => next*FRINGE,
+            };
+
+            let transparent = Color::transparent();
+            for i in 0..len {
+                if let Some(normal) = normals[i] {
+                    let j = (i + 1) % len;
+                    let start = offset(normals[(i + len - 1) % len], normal);
+                    let end = offset(normals[j], normal);
+
+                    quad(vertices, [
+                        (points[i], color),
+                        (points[j], color),
+                        (points[j] + end, transparent),
+                        (points[i] + start, transparent),
+                    ]);
+                }
+            }
+        }
+    }
+
+    /// Non-horizontal segments of all subpaths closed by a line to their start.
+    fn edges(&self) -> Vec<Edge> {
+        let mut edges = Vec::new();
+
+        for polyline in &self.polylines {
+            let points = &polyline.points;
+
+            for i in 0..points.len() {
+                let (a, b) = (points[i], points[(i + 1) % points.len()]);
+
+                if a.y < b.y {
+                    edges.push(Edge { top: a, bottom: b, winding: 1 });
+                } else if a.y > b.y {
+                    edges.push(Edge { top: b, bottom: a, winding: -1 });
+                }
+            }
+        }
+
+        edges
+    }
+}
+
+/// Vertical coordinate where the edges cross each other away from their ends.
+fn crossing(a: &Edge, b: &Edge) -> Option<f64> {
+    if a.bottom.y <= b.top.y || b.bottom.y <= a.top.y {
+        return None;
+    }
+
+    let (r, s) = (a.bottom - a.top, b.bottom - b.top);
+    let denominator = Vec2::cross(r, s);
+    if denominator == 0.0 {
+        return None;
+    }
+
+    let d = b.top - a.top;
+    let t = Vec2::cross(d, s)/denominator;
+    let u = Vec2::cross(d, r)/denominator;
+
+    if t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0 {
+        Some(a.top.y + r.y*t)
+    } else {
+        None
+    }
+}
+
+#[test]
+fn test_fill() {
+    use Rect;
+
+    // Area covered by the triangles, each weighted by the average opacity of its vertices.
+    let area = |path: &Path, style: FillStyle| {
+        let mut vertices = MeshVertices { vertices: Vec::new(), indices: Vec::new() };
+        path.fill(&style, Color::white(), &mut vertices);
+
+        let area: f64 = vertices.indices.chunks(3).map(|triangle| {
+            let corner = |i: usize| &vertices.vertices[triangle[i] as usize];
+            let alpha = (corner(0).color.a + corner(1).color.a + corner(2).color.a) as f64/3.0;
+            Vec2::cross(corner(1).position - corner(0).position, corner(2).position - corner(0).position).abs()/2.0*alpha
+        }).sum();
+
+        (area*1e6).round()/1e6
+    };
+
+    let solid = |rule| FillStyle { rule, anti_alias: false };
+
+    let mut square = Path::new();
+    square.rect(Rect { left: 0.0, top: 0.0, right: 10.0, bottom: 10.0 });
+    assert_eq!(area(&square, solid(FillRule::NonZero)), 100.0);
+
+    // Hole in the same direction is filled only with the even-odd rule, in the opposite direction with both.
+    let mut holed = square.clone();
+    holed.rect(Rect { left: 2.0, top: 2.0, right: 6.0, bottom: 6.0 });
+    assert_eq!(area(&holed, solid(FillRule::NonZero)), 100.0);
+    assert_eq!(area(&holed, solid(FillRule::EvenOdd)), 84.0);
+
+    let mut reversed = square.clone();
+    reversed.move_to(Vec2::new(2.0, 2.0)).line_to(Vec2::new(2.0, 6.0)).line_to(Vec2::new(6.0, 6.0)).line_to(Vec2::new(6.0, 2.0));
+    assert_eq!(area(&reversed, solid(FillRule::NonZero)), 84.0);
+
+    // Self-intersecting bow tie is split where the edges cross.
+    let mut bow_tie = Path::new();
+    bow_tie.move_to(Vec2::new(0.0, 0.0)).line_to(Vec2::new(10.0, 10.0)).line_to(Vec2::new(10.0, 0.0)).line_to(Vec2::new(0.0, 10.0));
+    assert_eq!(area(&bow_tie, solid(FillRule::EvenOdd)), 50.0);
+
+    // Fringe fades out of the square and into the hole.
+    let fringed = area(&holed, FillStyle { rule: FillRule::EvenOdd, anti_alias: true }) - 84.0;
+    assert!(fringed > (40.0 - 16.0)*0.5 && fringed < (44.0 + 16.0)*0.5);
+}
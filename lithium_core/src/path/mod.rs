@@ -0,0 +1,244 @@
+use std::f64::consts::PI;
+use {Color, Vec2, Rect};
+use gui::scene::{MeshVertices, Vertex};
+
+mod fill;
+mod stroke;
+
+pub use self::fill::{FillStyle, FillRule};
+pub use self::stroke::{StrokeStyle, LineJoin, LineCap};
+
+/// Maximum distance in pixels between a curve and the line segments approximating it.
+const TOLERANCE: f64 = 0.1;
+
+/// Width of the band in which anti-aliased edges fade out.
+const FRINGE: f64 = 1.0;
+
+/// Shape made of straight and curved segments which can be filled or stroked into a `MeshVertices`.
+///
+/// Coordinates are in pixels, curves are approximated by line segments when they are added. A path consists of
+/// subpaths, each started by `move_to` and optionally closed by `close`. Drawing without a current point starts a new
+/// subpath, drawing after `close` starts one at the beginning of the closed subpath.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Path {
+    polylines: Vec<Polyline>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+struct Polyline {
+    points: Vec<Vec2<f64>>,
+    closed: bool,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path {
+            polylines: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.polylines.is_empty()
+    }
+
+    /// Start a new subpath at the point.
+    pub fn move_to(&mut self, point: Vec2<f64>) -> &mut Self {
+        match self.polylines.last_mut() {
+            // Subpath containing only the starting point is moved.
+            Some(ref mut polyline) if !polyline.closed && polyline.points.len() == 1 => polyline.points[0] = point,
+            _ => self.polylines.push(Polyline { points: vec![point], closed: false }),
+        }
+
+        self
+    }
+
+    pub fn line_to(&mut self, point: Vec2<f64>) -> &mut Self {
+        self.start(point);
+        self.push(point);
+        self
+    }
+
+    /// Quadratic Bézier curve from the current point.
+    pub fn quad_to(&mut self, control: Vec2<f64>, point: Vec2<f64>) -> &mut Self {
+        let from = self.start(control);
+        let segments = segments((from - control*2.0 + point).norm()/8.0);
+
+        for i in 1..(segments + 1) {
+            let t = i as f64/segments as f64;
+            let s = 1.0 - t;
+            self.push(from*(s*s) + control*(2.0*s*t) + point*(t*t));
+        }
+
+        self
+    }
+
+    /// Cubic Bézier curve from the current point.
+    pub fn cubic_to(&mut self, control1: Vec2<f64>, control2: Vec2<f64>, point: Vec2<f64>) -> &mut Self {
+        let from = self.start(control1);
+        let curvature = (from - control1*2.0 + control2).norm().max((control1 - control2*2.0 + point).norm());
+        let segments = segments(curvature*0.75);
+
+        for i in 1..(segments + 1) {
+            let t = i as f64/segments as f64;
+            let s = 1.0 - t;
+            self.push(from*(s*s*s) + control1*(3.0*s*s*t) + control2*(3.0*s*t*t) + point*(t*t*t));
+        }
+
+        self
+    }
+
+    /// Circular arc between the angles in radians, clockwise if `end_angle` is greater than `start_angle`.
+    ///
+    /// Angles are measured from the positive x axis, as in `Vec2::from_angle`. If there is a current point it is
+    /// connected to the start of the arc by a line.
+    pub fn arc(&mut self, center: Vec2<f64>, radius: f64, start_angle: f64, end_angle: f64) -> &mut Self {
+        let sweep = end_angle - start_angle;
+        let start = center + Vec2::from_angle(start_angle)*radius;
+        self.line_to(start);
+
+        let segments = arc_segments(radius, sweep.abs());
+        for i in 1..(segments + 1) {
+            let angle = start_angle + sweep*(i as f64/segments as f64);
+            self.push(center + Vec2::from_angle(angle)*radius);
+        }
+
+        self
+    }
+
+    /// Connect the current subpath to its start.
+    pub fn close(&mut self) -> &mut Self {
+        if let Some(polyline) = self.polylines.last_mut() {
+            if polyline.points.len() > 2 && is_same(polyline.points[0], *polyline.points.last().unwrap()) {
+                polyline.points.pop();
+            }
+
+            polyline.closed = true;
+        }
+
+        self
+    }
+
+    /// Closed subpath along the edges of the rectangle.
+    pub fn rect(&mut self, rect: Rect<f64>) -> &mut Self {
+        self.move_to(rect.top_left())
+            .line_to(rect.top_right())
+            .line_to(rect.bottom_right())
+            .line_to(rect.bottom_left())
+            .close()
+    }
+
+    /// Closed subpath along the circle.
+    pub fn circle(&mut self, center: Vec2<f64>, radius: f64) -> &mut Self {
+        self.move_to(center + Vec2::new(radius, 0.0))
+            .arc(center, radius, 0.0, 2.0*PI)
+            .close()
+    }
+
+    /// Current point, a new subpath is started at `point` if there is none.
+    fn start(&mut self, point: Vec2<f64>) -> Vec2<f64> {
+        let start = match self.polylines.last() {
+            Some(polyline) if polyline.closed => polyline.points[0],
+            Some(polyline) => return *polyline.points.last().unwrap(),
+            None => point,
+        };
+
+        self.polylines.push(Polyline { points: vec![start], closed: false });
+        start
+    }
+
+    /// Add the point to the open subpath unless it is the same as the current one.
+    fn push(&mut self, point: Vec2<f64>) {
+        let points = &mut self.polylines.last_mut().unwrap().points;
+        if !is_same(*points.last().unwrap(), point) {
+            points.push(point);
+        }
+    }
+}
+
+fn is_same(a: Vec2<f64>, b: Vec2<f64>) -> bool {
+    (a - b).norm_squared() < 1e-12
+}
+
+/// Number of line segments approximating a curve which deviates by `deviation` from a single segment, the deviation
+/// decreases with the square of the number of segments.
+fn segments(deviation: f64) -> u32 {
+    ((deviation/TOLERANCE).sqrt().ceil() as u32).max(1)
+}
+
+/// Number of line segments approximating an arc.
+fn arc_segments(radius: f64, angle: f64) -> u32 {
+    let step = 2.0*(1.0 - TOLERANCE/radius).max(-1.0).acos();
+    ((angle/step).ceil() as u32).max(1)
+}
+
+/// Vector perpendicular to `v`, rotated clockwise on the screen.
+fn perp(v: Vec2<f64>) -> Vec2<f64> {
+    Vec2::new(-v.y, v.x)
+}
+
+/// Offset of the corner where lines offset by the unit normals `n0` and `n1` meet, it is at most `limit` long.
+fn miter(n0: Vec2<f64>, n1: Vec2<f64>, limit: f64) -> Vec2<f64> {
+    let middle = (n0 + n1)*0.5;
+    let norm_squared = middle.norm_squared();
+
+    if norm_squared < 1e-12 {
+        return n1*limit;
+    }
+
+    middle*(1.0/norm_squared).min(limit/norm_squared.sqrt())
+}
+
+/// Premultiplied color with the opacity multiplied by `alpha`.
+fn fade(color: Color, alpha: f32) -> Color {
+    Color { r: color.r*alpha, g: color.g*alpha, b: color.b*alpha, a: color.a*alpha }
+}
+
+fn quad(vertices: &mut MeshVertices, corners: [(Vec2<f64>, Color); 4]) {
+    let first = vertices.vertices.len() as u32;
+    vertices.vertices.extend(corners.iter().map(|&(position, color)| Vertex { position, color }));
+    vertices.indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+}
+
+#[test]
+fn test_path() {
+    let mut path = Path::new();
+    path.move_to(Vec2::new(0.0, 0.0)).move_to(Vec2::new(1.0, 0.0)).line_to(Vec2::new(2.0, 0.0)).line_to(Vec2::new(2.0, 0.0));
+    path.quad_to(Vec2::new(3.0, 0.0), Vec2::new(3.0, 1.0)).close();
+    path.line_to(Vec2::new(0.0, 5.0));
+    path.cubic_to(Vec2::new(0.0, 50.0), Vec2::new(50.0, 50.0), Vec2::new(50.0, 5.0));
+    path.circle(Vec2::new(0.0, 0.0), 10.0);
+
+    assert_eq!(path.polylines.len(), 3);
+    assert_eq!(&path.polylines[0].points[..2], &[Vec2::new(1.0, 0.0), Vec2::new(2.0, 0.0)]);
+    assert_eq!(path.polylines[0].points.last(), Some(&Vec2::new(3.0, 1.0)));
+    assert!(path.polylines[0].closed);
+
+    // Subpath after `close` continues from the start of the closed one.
+    assert_eq!(&path.polylines[1].points[..2], &[Vec2::new(1.0, 0.0), Vec2::new(0.0, 5.0)]);
+    assert_eq!(path.polylines[1].points.last(), Some(&Vec2::new(50.0, 5.0)));
+    assert!(!path.polylines[1].closed);
+
+    // Curves are within the tolerance of their flattening.
+    let max_distance = |from: Vec2<f64>, points: &[Vec2<f64>], f: &Fn(f64) -> Vec2<f64>| {
+        (0..101).map(|i| {
+            let point = f(i as f64/100.0);
+            points.windows(2).map(|segment| {
+                let (a, b) = (segment[0], segment[1]);
+                let t = (Vec2::dot(point - a, b - a)/(b - a).norm_squared()).max(0.0).min(1.0);
+                (a + (b - a)*t - point).norm()
+            }).fold((point - from).norm(), f64::min)
+        }).fold(0.0, f64::max)
+    };
+
+    let cubic = &path.polylines[1].points[1..];
+    let distance = max_distance(Vec2::new(0.0, 5.0), cubic, &|t| {
+        let s = 1.0 - t;
+        Vec2::new(0.0, 5.0)*(s*s*s) + Vec2::new(0.0, 50.0)*(3.0*s*s*t) + Vec2::new(50.0, 50.0)*(3.0*s*t*t) + Vec2::new(50.0, 5.0)*(t*t*t)
+    });
+    assert!(distance <= TOLERANCE && cubic.len() > 4);
+
+    let circle = &path.polylines[2];
+    assert!(circle.closed);
+    assert!(circle.points.iter().all(|point| (point.norm() - 10.0).abs() < 1e-9));
+    assert!(max_distance(circle.points[0], &circle.points, &|t| Vec2::from_angle(t*PI)*10.0) <= TOLERANCE);
+}
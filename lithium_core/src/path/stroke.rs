@@ -0,0 +1,389 @@
+use std::f64::consts::PI;
+use std::mem::replace;
+use {Color, Vec2};
+use gui::scene::{MeshVertices, Vertex};
+use super::{Path, FRINGE, TOLERANCE, is_same, arc_segments, perp, miter, fade};
+
+/// Shape of the outer corner where two segments of a stroke meet.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum LineJoin {
+    /// Sharp corner, it is beveled if it would be longer than `miter_limit` times the half of the width.
+    Miter,
+    Round,
+    Bevel,
+}
+
+impl Default for LineJoin {
+    fn default() -> Self {
+        LineJoin::Miter
+    }
+}
+
+/// Shape of the ends of open subpaths and dashes.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum LineCap {
+    /// Stroke ends at the end point.
+    Butt,
+    /// Half circle around the end point.
+    Round,
+    /// Stroke extends past the end point by the half of its width.
+    Square,
+}
+
+impl Default for LineCap {
+    fn default() -> Self {
+        LineCap::Butt
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct StrokeStyle {
+    pub width: f64,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub miter_limit: f64,
+
+    /// Alternating lengths of dashes and gaps, the pattern is repeated twice if it has odd length. Empty for a solid
+    /// stroke.
+    pub dashes: Vec<f64>,
+
+    /// Distance into the dash pattern at which the stroke starts.
+    pub dash_offset: f64,
+
+    /// Add a one pixel wide band fading out along the sides of the stroke. Thinner strokes are drawn one pixel wide
+    /// with reduced opacity.
+    pub anti_alias: bool,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        StrokeStyle {
+            width: 1.0,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: 4.0,
+            dashes: Vec::new(),
+            dash_offset: 0.0,
+            anti_alias: true,
+        }
+    }
+}
+
+/// Cross-section of a stroke, its sides are offset from the `point` by the `left` and `right` vectors multiplied by the
+/// distance from the center.
+struct Section {
+    point: Vec2<f64>,
+    left: Vec2<f64>,
+    right: Vec2<f64>,
+
+    /// Section at the tip of a butt or square cap where the anti-aliasing fades out along the stroke.
+    faded: bool,
+}
+
+struct Stroker<'a> {
+    style: &'a StrokeStyle,
+    half_width: f64,
+    color: Color,
+    vertices: &'a mut MeshVertices,
+    sections: Vec<Section>,
+}
+
+impl Path {
+    /// Append triangles covering the outline of the path to `vertices`.
+    ///
+    /// Every subpath is turned into a strip of cross-sections going along it, joins and caps are made of additional
+    /// cross-sections so the triangles do not overlap except where the path crosses itself.
+    pub fn stroke(&self, style: &StrokeStyle, color: Color, vertices: &mut MeshVertices) {
+        let (width, color) = if style.anti_alias && style.width < FRINGE {
+            (FRINGE, fade(color, (style.width.max(0.0)/FRINGE) as f32))
+        } else {
+            (style.width, color)
+        };
+
+        if width <= 0.0 {
+            return;
+        }
+
+        let mut stroker = Stroker { style, half_width: width/2.0, color, vertices, sections: Vec::new() };
+
+        for polyline in &self.polylines {
+            if style.dashes.is_empty() {
+                stroker.polyline(&polyline.points, polyline.closed);
+            } else {
+                for dash in dashes(&polyline.points, polyline.closed, &style.dashes, style.dash_offset) {
+                    stroker.polyline(&dash, false);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Stroker<'a> {
+    fn polyline(&mut self, points: &[Vec2<f64>], closed: bool) {
+        let mut points = points.to_vec();
+        points.dedup_by(|a, b| is_same(*a, *b));
+        if closed && points.len() > 2 && is_same(points[0], *points.last().unwrap()) {
+            points.pop();
+        }
+
+        let len = points.len();
+        if len < 2 {
+            return;
+        }
+
+        let direction = |i: usize| (points[(i + 1) % len] - points[i]).normalize();
+        self.sections.clear();
+
+        if closed {
+            for i in 0..len {
+                self.join(points[i], direction((i + len - 1) % len), direction(i));
+            }
+
+            let first = &self.sections[0];
+            let last = Section { point: first.point, left: first.left, right: first.right, faded: false };
+            self.sections.push(last);
+        } else {
+            self.cap(points[0], direction(0)*-1.0, true);
+            for i in 1..(len - 1) {
+                self.join(points[i], direction(i - 1), direction(i));
+            }
+            self.cap(points[len - 1], direction(len - 2), false);
+        }
+
+        self.strip();
+    }
+
+    /// Sections at a point where the direction changes from `incoming` to `outgoing`.
+    fn join(&mut self, point: Vec2<f64>, incoming: Vec2<f64>, outgoing: Vec2<f64>) {
+        let (n0, n1) = (perp(incoming), perp(outgoing));
+        let limit = self.style.miter_limit.max(1.0);
+        let corner = miter(n0, n1, limit);
+
+        let straight = Vec2::dot(n0, n1) > 1.0 - 1e-9;
+        let mitered = self.style.join == LineJoin::Miter && 1.0/corner.norm_squared().sqrt() <= limit*(1.0 + 1e-9);
+        if straight || mitered {
+            self.sections.push(Section { point, left: corner, right: corner*-1.0, faded: false });
+            return;
+        }
+
+        // The inner side meets at the miter corner while the outer one goes around the point. The path turns towards
+        // the left side, which is the one of `perp`, if the cross product is positive.
+        let inner_left = Vec2::cross(incoming, outgoing) > 0.0;
+        let (inner, from, to) = if inner_left { (corner, n0*-1.0, n1*-1.0) } else { (corner*-1.0, n0, n1) };
+
+        let steps = match self.style.join {
+            LineJoin::Round => arc_segments(self.half_width, Vec2::dot(from, to).max(-1.0).min(1.0).acos()),
+            _ => 1,
+        };
+
+        let angle = Vec2::cross(from, to).atan2(Vec2::dot(from, to));
+        for i in 0..(steps + 1) {
+            let outer = rotate(from, angle*(i as f64/steps as f64));
+            let (left, right) = if inner_left { (inner, outer) } else { (outer, inner) };
+            self.sections.push(Section { point, left, right, faded: false });
+        }
+    }
+
+    /// Sections of the cap at the end of the stroke going in `forward` direction from the `point`.
+    fn cap(&mut self, point: Vec2<f64>, forward: Vec2<f64>, start: bool) {
+        // Normal of the stroke going away from the start.
+        let normal = if start { perp(forward*-1.0) } else { perp(forward) };
+        let fringe = if self.style.anti_alias { FRINGE/2.0 } else { 0.0 };
+        let mut sections = Vec::new();
+
+        match self.style.cap {
+            LineCap::Butt | LineCap::Square => {
+                let tip = if self.style.cap == LineCap::Square { point + forward*self.half_width } else { point };
+                sections.push(Section { point: tip, left: normal, right: normal*-1.0, faded: false });
+
+                if fringe > 0.0 {
+                    sections.push(Section { point: tip + forward*fringe, left: normal, right: normal*-1.0, faded: true });
+                }
+            }
+            LineCap::Round => {
+                let steps = arc_segments(self.half_width, PI/2.0);
+
+                for i in 0..(steps + 1) {
+                    let angle = PI/2.0*(i as f64/steps as f64);
+                    let (cos, sin) = (angle.cos(), angle.sin());
+                    sections.push(Section {
+                        point,
+                        left: normal*cos + forward*sin,
+                        right: normal*-cos + forward*sin,
+                        faded: false,
+                    });
+                }
+            }
+        }
+
+        if start {
+            sections.reverse();
+        }
+
+        self.sections.extend(sections);
+    }
+
+    /// Triangles between the consecutive sections.
+    fn strip(&mut self) {
+        let transparent = Color::transparent();
+
+        // Distances of the vertices of a section from its center and whether they are on the outside of the fringe.
+        let fringe = if self.style.anti_alias { FRINGE/2.0 } else { 0.0 };
+        let mut offsets = vec![((self.half_width - fringe).max(0.0), false)];
+        if fringe > 0.0 {
+            offsets.insert(0, (self.half_width + fringe, true));
+        }
+
+        let count = 2*offsets.len() as u32;
+        let first = self.vertices.vertices.len() as u32;
+
+        for section in &self.sections {
+            let (inside, vertices) = (if section.faded { transparent } else { self.color }, &mut self.vertices.vertices);
+            let left = offsets.iter().map(|&(distance, outside)| (section.left*distance, outside));
+            let right = offsets.iter().rev().map(|&(distance, outside)| (section.right*distance, outside));
+
+            for (offset, outside) in left.chain(right) {
+                vertices.push(Vertex { position: section.point + offset, color: if outside { transparent } else { inside } });
+            }
+        }
+
+        for i in 1..self.sections.len() as u32 {
+            let (a, b) = (first + (i - 1)*count, first + i*count);
+
+            for k in 0..(count - 1) {
+                self.vertices.indices.extend_from_slice(&[a + k, a + k + 1, b + k + 1, a + k, b + k + 1, b + k]);
+            }
+        }
+    }
+}
+
+fn rotate(v: Vec2<f64>, angle: f64) -> Vec2<f64> {
+    let (cos, sin) = (angle.cos(), angle.sin());
+    Vec2::new(v.x*cos - v.y*sin, v.x*sin + v.y*cos)
+}
+
+/// Parts of the polyline covered by the dashes of the pattern.
+fn dashes(points: &[Vec2<f64>], closed: bool, pattern: &[f64], offset: f64) -> Vec<Vec<Vec2<f64>>> {
+    let mut pattern = pattern.to_vec();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_slice(&pattern.clone());
+    }
+
+    let total: f64 = pattern.iter().sum();
+    if total <= TOLERANCE || pattern.iter().any(|&length| length < 0.0) || points.is_empty() {
+        return vec![points.to_vec()];
+    }
+
+    // Find the dash in which the stroke starts.
+    let mut index = 0;
+    let mut left = ((offset % total) + total) % total;
+    while left >= pattern[index] {
+        left -= pattern[index];
+        index = (index + 1) % pattern.len();
+    }
+    left = pattern[index] - left;
+
+    let mut segments: Vec<(Vec2<f64>, Vec2<f64>)> = points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+    if closed {
+        segments.push((*points.last().unwrap(), points[0]));
+    }
+
+    let mut dashes = Vec::new();
+    let mut dash = Vec::new();
+    if index % 2 == 0 {
+        dash.push(points[0]);
+    }
+
+    for (a, b) in segments {
+        let length = (b - a).norm();
+        let mut done = 0.0;
+
+        while length - done > left {
+            done += left;
+            let point = a + (b - a)*(done/length);
+
+            dash.push(point);
+            if index % 2 == 0 {
+                dashes.push(replace(&mut dash, Vec::new()));
+            }
+
+            index = (index + 1) % pattern.len();
+            left = pattern[index];
+        }
+
+        left -= length - done;
+        if index % 2 == 0 {
+            dash.push(b);
+        }
+    }
+
+    if dash.len() > 1 {
+        dashes.push(dash);
+    }
+
+    dashes
+}
+
+#[test]
+fn test_stroke() {
+    // Area covered by the triangles of the stroke.
+    let area = |path: &Path, style: &StrokeStyle| {
+        let mut vertices = MeshVertices { vertices: Vec::new(), indices: Vec::new() };
+        path.stroke(style, Color::white(), &mut vertices);
+
+        let area: f64 = vertices.indices.chunks(3).map(|triangle| {
+            let corner = |i: usize| vertices.vertices[triangle[i] as usize].position;
+            Vec2::cross(corner(1) - corner(0), corner(2) - corner(0)).abs()/2.0
+        }).sum();
+
+        (area*1e6).round()/1e6
+    };
+
+    let solid = |join, cap| StrokeStyle { width: 2.0, join, cap, anti_alias: false, ..StrokeStyle::default() };
+
+    let mut line = Path::new();
+    line.move_to(Vec2::new(0.0, 0.0)).line_to(Vec2::new(10.0, 0.0));
+    assert_eq!(area(&line, &solid(LineJoin::Miter, LineCap::Butt)), 20.0);
+    assert_eq!(area(&line, &solid(LineJoin::Miter, LineCap::Square)), 24.0);
+
+    let round = area(&line, &solid(LineJoin::Miter, LineCap::Round));
+    assert!(round <= 20.0 + PI && round > 20.0 + PI - 0.4);
+
+    // Right angle corner.
+    let mut corner = Path::new();
+    corner.move_to(Vec2::new(0.0, 0.0)).line_to(Vec2::new(10.0, 0.0)).line_to(Vec2::new(10.0, 10.0));
+    assert_eq!(area(&corner, &solid(LineJoin::Miter, LineCap::Butt)), 40.0);
+    assert_eq!(area(&corner, &solid(LineJoin::Bevel, LineCap::Butt)), 39.5);
+
+    let round = area(&corner, &solid(LineJoin::Round, LineCap::Butt));
+    assert!(round <= 39.0 + PI/4.0 && round > 39.0 + PI/4.0 - 0.1);
+
+    // Miter of a sharp corner is longer than the limit.
+    let mut sharp = Path::new();
+    sharp.move_to(Vec2::new(0.0, 0.0)).line_to(Vec2::new(10.0, 0.0)).line_to(Vec2::new(0.0, 1.0));
+    let mut style = solid(LineJoin::Miter, LineCap::Butt);
+    let beveled = area(&sharp, &style);
+    style.miter_limit = 100.0;
+    assert!(area(&sharp, &style) > beveled + 5.0);
+
+    // Closed square has no caps.
+    let mut square = Path::new();
+    square.rect(::Rect { left: 0.0, top: 0.0, right: 10.0, bottom: 10.0 });
+    assert_eq!(area(&square, &solid(LineJoin::Miter, LineCap::Square)), 12.0*12.0 - 8.0*8.0);
+
+    // Dashes of length 2 with gaps of 3, the last one starts at the end and is empty.
+    let mut style = solid(LineJoin::Miter, LineCap::Butt);
+    style.dashes = vec![2.0, 3.0];
+    assert_eq!(area(&line, &style), 8.0);
+    style.dash_offset = 1.0;
+    assert_eq!(area(&line, &style), 8.0);
+    style.dashes = vec![2.0];
+    style.dash_offset = 0.0;
+    assert_eq!(area(&line, &style), 12.0);
+
+    // Anti-aliasing keeps the coverage of a thin line.
+    let mut vertices = MeshVertices { vertices: Vec::new(), indices: Vec::new() };
+    line.stroke(&StrokeStyle { width: 0.5, ..StrokeStyle::default() }, Color::white(), &mut vertices);
+    assert!(vertices.vertices.iter().all(|vertex| vertex.color.a <= 0.5));
+    assert!(vertices.vertices.iter().all(|vertex| vertex.position.y.abs() <= 1.0));
+}
@@ -41,6 +41,7 @@ pub struct Constraint<E: Expression> {
 pub struct ConstraintTag {
     marker: Symbol,
     other: Symbol,
+    strength: f64,
 }
 
 /// A constraint solver which uses the cassowary algorithm.
@@ -128,6 +129,7 @@ impl Solver {
             ConstraintTag {
                 marker: slack,
                 other: error,
+                strength,
             }
         } else {
             let error_plus = Symbol::new(SymbolKind::Error);
@@ -139,6 +141,7 @@ impl Solver {
             ConstraintTag {
                 marker: error_plus,
                 other: error_minus,
+                strength,
             }
         };
 
@@ -286,11 +289,22 @@ impl Solver {
     /// Remove the effects of a constraint on the objective function.
     fn remove_constraint_effects(&mut self, tag: ConstraintTag) {
         if tag.marker.kind == SymbolKind::Error {
-            self.objective.cells.remove(&tag.marker);
+            self.remove_marker_effects(tag.marker, tag.strength);
         }
 
-        if tag.other.kind == SymbolKind::Error { // FIXME: always true?
-            self.objective.cells.remove(&tag.other);
+        if tag.other.kind == SymbolKind::Error {
+            self.remove_marker_effects(tag.other, tag.strength);
+        }
+    }
+
+    /// Remove the effects of an error marker on the objective function.
+    ///
+    /// A basic marker was substituted into the objective function by its row, so the row has to be subtracted as well.
+    fn remove_marker_effects(&mut self, marker: Symbol, strength: f64) {
+        if let Some(row) = self.rows.get(&marker) {
+            self.objective.add_row(row, -strength);
+        } else {
+            self.objective.add_symbol(marker, -strength);
         }
     }
 }
@@ -299,3 +313,22 @@ fn near_zero(value: f64) -> bool {
     const EPS: f64 = 1e-8;
     value.abs() < EPS
 }
+
+#[test]
+fn test_remove_constraint() {
+    let x = Var::from(Id::unique());
+    let term = |coefficient| Term { variable: x, coefficient };
+
+    let mut solver = Solver::default();
+    let preferred = solver.add_constraint(false, -10.0, &[term(1.0)], Strength::Weak);
+    let maximum = solver.add_constraint(true, 5.0, &[term(-1.0)], Strength::Medium);
+    assert_eq!(solver.get_value(x), Some(5.0));
+
+    // Error of the preferred value is basic here, its row has to be removed from the objective function as well.
+    solver.remove_constraint(preferred);
+    solver.remove_constraint(maximum);
+    assert!(solver.objective.cells.is_empty());
+
+    solver.add_constraint(false, -2.0, &[term(1.0)], Strength::Weak);
+    assert_eq!(solver.get_value(x), Some(2.0));
+}
@@ -1,9 +1,10 @@
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use {Id, Var};
 use util::IdIdentityHasherBuilder;
 use super::near_zero;
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub struct Symbol {
     id: Id,
     pub kind: SymbolKind,
@@ -22,6 +23,13 @@ pub struct Row {
     pub constant: f64
 }
 
+// Identifiers of symbols are unique so the kind is not hashed, which also keeps `IdIdentityHasher` usable for rows.
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
 impl Symbol {
     pub fn new(kind: SymbolKind) -> Self {
         Symbol {
@@ -28,7 +28,8 @@ pub struct ElementStyle {
     pub border_radius: [Vec2<LengthOrPercentage>; 4],
     pub visible: bool,
     pub outline: Outline,
-    pub overflow: Overflow,
+    /// Overflow along the horizontal and the vertical axis.
+    pub overflow: Vec2<Overflow>,
     pub opacity: f32,
     pub isolate: bool,
 }
@@ -247,8 +248,15 @@ pub enum Filter {
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Overflow {
     Visible,
+
+    /// Content is clipped and can only be scrolled programmatically.
     Hidden,
-    // Scroll,
+
+    /// Content is clipped and scroll bars are always shown.
+    Scroll,
+
+    /// Content is clipped and scroll bars are shown when the content does not fit.
+    Auto,
 }
 
 impl Default for ElementStyle {
@@ -264,13 +272,22 @@ impl Default for ElementStyle {
             border_radius: [Vec2::new(LengthOrPercentage::Length(0.0), LengthOrPercentage::Length(0.0)); 4],
             visible: true,
             outline: Outline::default(),
-            overflow: Overflow::default(),
+            overflow: Vec2::new(Overflow::default(), Overflow::default()),
             opacity: 1.0,
             isolate: false,
         }
     }
 }
 
+impl ElementStyle {
+    /// Children are clipped to the element place along both axes.
+    ///
+    /// Like in CSS, overflow which is visible along only one of the axes is treated as clipped.
+    pub fn clips(&self) -> bool {
+        self.overflow.x != Overflow::Visible || self.overflow.y != Overflow::Visible
+    }
+}
+
 impl Default for Border {
     fn default() -> Self {
         Border {
@@ -1,5 +1,5 @@
 use blake2_rfc::blake2b::blake2b;
-use super::{ColorId, ElementKind, StyleVariant};
+use super::{ColorId, ElementKind, StyleVariant, PseudoClass};
 
 /// Generate color identifier from the string.
 /*FIXME: const*/ pub fn color_id(text: &str) -> ColorId {
@@ -16,6 +16,11 @@ use super::{ColorId, ElementKind, StyleVariant};
     StyleVariant(hash("style_variant", text))
 }
 
+/// Generate pseudo-class identifier from the string.
+/*FIXME: const*/ pub fn pseudo_class(text: &str) -> PseudoClass {
+    PseudoClass(hash("pseudo_class", text))
+}
+
 /*FIXME: const*/ fn hash(key: &str, text: &str) -> u64 {
     let result = blake2b(8, key.as_bytes(), text.as_bytes());
     let bytes = result.as_bytes();
@@ -36,4 +41,5 @@ fn ids_stay_the_same() {
     assert_eq!(color_id("test"), ColorId(15669914510866457799));
     assert_eq!(element_kind("test"), ElementKind(14929189791165124317));
     assert_eq!(style_variant("test"), StyleVariant(15111611029265304875));
+    assert_eq!(pseudo_class("test"), PseudoClass(10962339467276327747));
 }
\ No newline at end of file
@@ -5,11 +5,16 @@ use css;
 use Color;
 
 pub use self::element_style::ElementStyle;
-pub use self::identifiers::{color_id, element_kind, style_variant};
+pub use self::identifiers::{color_id, element_kind, style_variant, pseudo_class};
 
 mod identifiers;
 pub mod element_style;
 
+/// Number of pseudo-classes which are taken into account when choosing style of an element.
+///
+/// Every combination of them may have its own style so the lookup is exponential in their number.
+pub const MAX_PSEUDO_CLASSES: usize = 8;
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct ColorId(pub u64);
 
@@ -19,6 +24,12 @@ pub struct ElementKind(pub u64);
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct StyleVariant(pub u64);
 
+/// State of the element such as `hover` which can be styled separately, like pseudo-classes in CSS.
+///
+/// Unlike style variants which apply to the whole subtree pseudo-classes apply only to a single element.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd, Debug)]
+pub struct PseudoClass(pub u64);
+
 #[derive(Clone, Default, PartialEq)]
 pub struct Theme {
     pub colors: HashMap<(StyleVariant, ColorId), Color, IdIdentityHasherBuilder>,
@@ -47,6 +58,23 @@ impl Theme {
         }
     }
 
+    /// Get style of the element which is in the state described by the `pseudo_classes`.
+    ///
+    /// If there is no style for the exact combination of pseudo-classes then combinations with fewer pseudo-classes are tried
+    /// falling back to the style of the element without any. Repeated pseudo-classes are ignored and so are the ones after
+    /// the first `MAX_PSEUDO_CLASSES`.
+    pub fn element_style_in_state(&self, style_variant: StyleVariant, kind: ElementKind, pseudo_classes: &[PseudoClass]) -> Option<Arc<ElementStyle>> {
+        style_in_state(kind, pseudo_classes, |kind| self.element_style(style_variant, kind))
+    }
+
+    /// Get style of a part of a widget in the state described by the `pseudo_classes`.
+    ///
+    /// Same as `element_style_in_state` except that only the styles of the `style_variant` itself are used, so the style of
+    /// the whole widget which has the same kind in the default variant doesn't apply to the part.
+    pub fn part_style_in_state(&self, style_variant: StyleVariant, kind: ElementKind, pseudo_classes: &[PseudoClass]) -> Option<Arc<ElementStyle>> {
+        style_in_state(kind, pseudo_classes, |kind| self.element_styles.get(&(style_variant, kind)).cloned())
+    }
+
     pub fn element_style(&self, style_variant: StyleVariant, kind: ElementKind) -> Option<Arc<ElementStyle>> {
         if let Some(&ref element_style) = self.element_styles.get(&(style_variant, kind)) {
             Some(element_style.clone())
@@ -58,8 +86,91 @@ impl Theme {
     }
 }
 
+/// Look up the style of the `kind` combined with the `pseudo_classes`, see `Theme::element_style_in_state`.
+fn style_in_state<F>(kind: ElementKind, pseudo_classes: &[PseudoClass], lookup: F) -> Option<Arc<ElementStyle>>
+    where F: Fn(ElementKind) -> Option<Arc<ElementStyle>>
+{
+    let mut unique_pseudo_classes = Vec::with_capacity(pseudo_classes.len());
+    for &pseudo_class in pseudo_classes {
+        if !unique_pseudo_classes.contains(&pseudo_class) && unique_pseudo_classes.len() < MAX_PSEUDO_CLASSES {
+            unique_pseudo_classes.push(pseudo_class);
+        }
+    }
+
+    let pseudo_classes = &unique_pseudo_classes[..];
+    let n = pseudo_classes.len();
+
+    // Each bit of the mask tells if corresponding pseudo-class is included, masks are tried from the ones with the most bits set.
+    for count in (0..n + 1).rev() {
+        for mask in 0..(1u32 << n) {
+            if mask.count_ones() as usize != count {
+                continue;
+            }
+
+            let mut kind_in_state = kind;
+            for (i, &pseudo_class) in pseudo_classes.iter().enumerate() {
+                if mask & (1 << i) != 0 {
+                    kind_in_state = kind_in_state.with_pseudo_class(pseudo_class);
+                }
+            }
+
+            if let Some(element_style) = lookup(kind_in_state) {
+                return Some(element_style);
+            }
+        }
+    }
+
+    None
+}
+
+impl ElementKind {
+    /// Kind under which the theme stores style of this element in the specified state.
+    ///
+    /// The result doesn't depend on the order in which pseudo-classes are added. Each pseudo-class should be added only
+    /// once since adding it again gives yet another kind.
+    pub fn with_pseudo_class(self, pseudo_class: PseudoClass) -> ElementKind {
+        // Unlike xor the addition doesn't cancel out a repeated pseudo-class and the multiplication by an odd constant
+        // spreads the bits so that different combinations are unlikely to add up to the same kind.
+        ElementKind(self.0.wrapping_add(pseudo_class.0.wrapping_mul(0x9e37_79b9_7f4a_7c15)))
+    }
+}
+
 impl Default for StyleVariant {
     fn default() -> Self {
         style_variant!("default")
     }
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_element_style_in_state() {
+    let button = element_kind("Button");
+    let (hover, focus) = (pseudo_class("hover"), pseudo_class("focus"));
+
+    let style = |opacity| Arc::new(ElementStyle { opacity, ..ElementStyle::default() });
+
+    let mut theme = Theme::empty();
+    theme.element_styles.insert((StyleVariant::default(), button), style(1.0));
+    theme.element_styles.insert((StyleVariant::default(), button.with_pseudo_class(hover)), style(0.5));
+    theme.element_styles.insert((StyleVariant::default(), button.with_pseudo_class(hover).with_pseudo_class(focus)), style(0.25));
+
+    let opacity = |pseudo_classes: &[PseudoClass]| {
+        theme.element_style_in_state(style_variant("error"), button, pseudo_classes).unwrap().opacity
+    };
+
+    assert_eq!(opacity(&[]), 1.0);
+    assert_eq!(opacity(&[focus]), 1.0);
+    assert_eq!(opacity(&[hover]), 0.5);
+    assert_eq!(opacity(&[focus, hover]), 0.25);
+    assert_eq!(opacity(&[pseudo_class("checked"), hover]), 0.5);
+    assert!(theme.element_style_in_state(StyleVariant::default(), element_kind("Other"), &[hover]).is_none());
+
+    // Repeated pseudo-classes don't cancel out.
+    assert_ne!(button.with_pseudo_class(hover).with_pseudo_class(hover), button);
+    assert_eq!(opacity(&[hover, hover]), 0.5);
+    assert_eq!(opacity(&[hover, focus, hover, focus]), 0.25);
+
+    // Pseudo-classes past the limit are ignored.
+    let many: Vec<_> = (0..20).map(|i| pseudo_class(&format!("state{}", i))).chain(Some(hover)).collect();
+    assert_eq!(opacity(&many), 1.0);
+    assert_eq!(opacity(&many[many.len() - MAX_PSEUDO_CLASSES..]), 0.5);
+}
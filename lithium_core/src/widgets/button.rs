@@ -1,11 +1,13 @@
 use {Id, Gui, Rect, Var};
 use layout;
 use super::Widget;
-use super::ClickArea;
+use super::{ClickArea, activated_by_keyboard};
 
+/// Clickable widget showing a label, it can be focused and pressed with Space as well.
 pub struct Button<T: Widget> {
     id: Id,
     click_area: ClickArea,
+    clicked: bool,
 
     pub label: T,
 }
@@ -14,6 +16,8 @@ impl<T: Widget> Widget for Button<T> {
     fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
         let place = Rect::from(self.id);
 
+        gui.focusable(self.id);
+
     	gui.element(self.id, element_kind!("Button"), |gui| {
             let click_area_place = self.click_area.appear(gui);
             let label_place = self.label.appear(gui);
@@ -22,6 +26,14 @@ impl<T: Widget> Widget for Button<T> {
             layout::center(gui, place, label_place);
         });
 
+        if self.click_area.clicked() {
+            gui.focus(self.id);
+            self.clicked = true;
+        }
+        if activated_by_keyboard(gui, self.id) {
+            self.clicked = true;
+        }
+
         place
     }
 }
@@ -31,11 +43,19 @@ impl<T: Widget> Button<T> {
         Button {
             id: Id::unique(),
             click_area: ClickArea::new(),
+            clicked: false,
             label
         }
     }
 
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Button was clicked or pressed with Space since the last call.
     pub fn clicked(&mut self) -> bool {
-        self.click_area.clicked()
+        let prev = self.clicked;
+        self.clicked = false;
+        prev
     }
 }
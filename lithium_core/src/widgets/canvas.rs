@@ -0,0 +1,177 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use {Id, Gui, Rect, Var, Vec2, Color};
+use gui::scene::{Mesh, MeshVertices};
+use path::{Path, FillStyle, StrokeStyle};
+use solver::Strength;
+use super::Widget;
+
+/// Area where the application draws its own graphics made of paths.
+///
+/// The `draw` function is called when the place of the `Canvas` element changes and after `invalidate`, the mesh it
+/// produces is reused in the other frames. The theme can give the element `overflow: hidden` to clip the graphics. The
+/// preferred size of the canvas is `size`.
+pub struct Canvas {
+    id: Id,
+    vertices: Rc<RefCell<MeshVertices>>,
+    draw: Box<FnMut(&mut Painter)>,
+
+    /// Place for which the mesh was drawn.
+    drawn: Option<Rect<f64>>,
+
+    pub size: Vec2<f64>,
+}
+
+/// Draws paths into a canvas, their coordinates are relative to the top left corner of the canvas.
+pub struct Painter<'a> {
+    vertices: &'a mut MeshVertices,
+    place: Rect<f64>,
+}
+
+impl Widget for Canvas {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let frame = gui.layout.prev_value_rect(place);
+
+        if self.drawn != Some(frame) {
+            let mut vertices = self.vertices.borrow_mut();
+            vertices.vertices.clear();
+            vertices.indices.clear();
+
+            (self.draw)(&mut Painter { vertices: &mut vertices, place: frame });
+            self.drawn = Some(frame);
+        }
+
+        let vertices = self.vertices.clone();
+        gui.element(self.id, element_kind!("Canvas"), |gui| {
+            gui.scene.mesh(Mesh { data: vertices });
+        });
+
+        gui.layout.with_strength(Strength::Weak, |layout| {
+            add_constraints!(layout, [
+                (place.right - place.left) == self.size.x,
+                (place.bottom - place.top) == self.size.y,
+            ]);
+        });
+
+        place
+    }
+}
+
+impl Canvas {
+    pub fn new<F: FnMut(&mut Painter) + 'static>(draw: F) -> Self {
+        Canvas {
+            id: Id::unique(),
+            vertices: Rc::new(RefCell::new(MeshVertices { vertices: Vec::new(), indices: Vec::new() })),
+            draw: Box::new(draw),
+            drawn: None,
+
+            size: Vec2::new(100.0, 100.0),
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Draw the canvas again in the next frame.
+    pub fn invalidate(&mut self) {
+        self.drawn = None;
+    }
+
+    /// Replace the function drawing the canvas.
+    pub fn set_draw<F: FnMut(&mut Painter) + 'static>(&mut self, draw: F) {
+        self.draw = Box::new(draw);
+        self.drawn = None;
+    }
+}
+
+impl<'a> Painter<'a> {
+    /// Size of the canvas.
+    pub fn size(&self) -> Vec2<f64> {
+        self.place.size()
+    }
+
+    pub fn fill(&mut self, path: &Path, style: &FillStyle, color: Color) {
+        let first = self.vertices.vertices.len();
+        path.fill(style, color, self.vertices);
+        self.translate(first);
+    }
+
+    pub fn stroke(&mut self, path: &Path, style: &StrokeStyle, color: Color) {
+        let first = self.vertices.vertices.len();
+        path.stroke(style, color, self.vertices);
+        self.translate(first);
+    }
+
+    /// Move the vertices starting at `first` from the canvas to the window coordinates.
+    fn translate(&mut self, first: usize) {
+        let offset = self.place.top_left();
+        for vertex in &mut self.vertices.vertices[first..] {
+            vertex.position += offset;
+        }
+    }
+}
+
+#[test]
+fn test_canvas() {
+    use std::cell::Cell;
+    use Theme;
+    use gui::scene::Command;
+
+    let draws = Rc::new(Cell::new(0));
+    let mut canvas = Canvas::new({
+        let draws = draws.clone();
+        move |painter| {
+            draws.set(draws.get() + 1);
+
+            let mut path = Path::new();
+            path.rect(Rect::from_top_left_and_size(Vec2::zero(), painter.size()));
+            painter.fill(&path, &FillStyle { anti_alias: false, ..FillStyle::default() }, Color::black());
+        }
+    });
+
+    let mut gui = Gui::new(Theme::empty());
+
+    // Returns the bounds of the drawn mesh.
+    let frame = |gui: &mut Gui, canvas: &mut Canvas, width: f64| {
+        let place = canvas.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 10.0,
+            (place.top) == 20.0,
+            (place.right) == 10.0 + width,
+        ]);
+
+        let bounds = gui.scene.commands().iter().filter_map(|command| match *command {
+            Command::Mesh(ref mesh) => mesh.data.borrow().vertices.iter().map(|vertex| vertex.position).fold(None, |bounds, point| {
+                let bounds = bounds.unwrap_or(Rect::from_corners(point, point));
+                Some(Rect {
+                    left: bounds.left.min(point.x),
+                    top: bounds.top.min(point.y),
+                    right: bounds.right.max(point.x),
+                    bottom: bounds.bottom.max(point.y),
+                })
+            }),
+            _ => None,
+        }).next();
+
+        gui.advance();
+        bounds
+    };
+
+    frame(&mut gui, &mut canvas, 50.0);
+    let bounds = frame(&mut gui, &mut canvas, 50.0);
+    assert_eq!(bounds, Some(Rect { left: 10.0, top: 20.0, right: 60.0, bottom: 120.0 }));
+    assert_eq!(draws.get(), 2);
+
+    // Mesh is reused until the place changes or the canvas is invalidated.
+    frame(&mut gui, &mut canvas, 50.0);
+    assert_eq!(draws.get(), 2);
+    canvas.invalidate();
+    frame(&mut gui, &mut canvas, 50.0);
+    assert_eq!(draws.get(), 3);
+    frame(&mut gui, &mut canvas, 80.0);
+    let bounds = frame(&mut gui, &mut canvas, 80.0);
+    assert_eq!(bounds.map(|bounds| bounds.right), Some(90.0));
+    assert_eq!(draws.get(), 4);
+}
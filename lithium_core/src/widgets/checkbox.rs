@@ -0,0 +1,186 @@
+use {Id, Gui, Rect, Var};
+use layout;
+use super::{Widget, ClickArea, activated_by_keyboard, indicator_with_label};
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum CheckState {
+    Unchecked,
+    Checked,
+
+    /// Neither checked nor unchecked, for example when only some of the nested options are checked.
+    Indeterminate,
+}
+
+/// Box which can be checked followed by a label.
+///
+/// The box is a `Checkbox` element styled with the `indicator` style variant (`Checkbox.indicator` in CSS). Both it and
+/// the whole `Checkbox` get the `checked` or `indeterminate` pseudo-class according to the state, but rules for the
+/// whole `Checkbox` don't apply to the box, see `Gui::part`. Clicking the checkbox or pressing Space while it is
+/// focused changes the state.
+pub struct Checkbox<T: Widget> {
+    id: Id,
+    indicator_id: Id,
+    click_area: ClickArea,
+    state: CheckState,
+    changed: bool,
+
+    pub label: T,
+
+    /// Cycle through the indeterminate state when clicked instead of switching between checked and unchecked.
+    pub tri_state: bool,
+}
+
+impl<T: Widget> Widget for Checkbox<T> {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let indicator_place = Rect::from(self.indicator_id);
+
+        gui.focusable(self.id);
+
+        let pseudo_classes = match self.state {
+            CheckState::Unchecked => vec![],
+            CheckState::Checked => vec![pseudo_class!("checked")],
+            CheckState::Indeterminate => vec![pseudo_class!("indeterminate")],
+        };
+
+        let indicator_id = self.indicator_id;
+        let click_area = &mut self.click_area;
+        let label = &mut self.label;
+
+        gui.element_in_state(self.id, element_kind!("Checkbox"), &pseudo_classes, |gui| {
+            let click_area_place = click_area.appear(gui);
+            gui.part(indicator_id, element_kind!("Checkbox"), style_variant!("indicator"), &pseudo_classes, |_gui| {});
+            let label_place = label.appear(gui);
+
+            layout::equal(gui, place, click_area_place);
+            indicator_with_label(gui, place, indicator_place, label_place);
+        });
+
+        let clicked = self.click_area.clicked();
+        if clicked {
+            gui.focus(self.id);
+        }
+
+        if clicked || activated_by_keyboard(gui, self.id) {
+            self.state = match (self.state, self.tri_state) {
+                (CheckState::Unchecked, _) => CheckState::Checked,
+                (CheckState::Checked, true) => CheckState::Indeterminate,
+                (CheckState::Checked, false) | (CheckState::Indeterminate, _) => CheckState::Unchecked,
+            };
+            self.changed = true;
+        }
+
+        place
+    }
+}
+
+impl<T: Widget> Checkbox<T> {
+    pub fn new(label: T) -> Self {
+        Checkbox {
+            id: Id::unique(),
+            indicator_id: Id::unique(),
+            click_area: ClickArea::new(),
+            state: CheckState::Unchecked,
+            changed: false,
+
+            label,
+            tri_state: false,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn state(&self) -> CheckState {
+        self.state
+    }
+
+    pub fn set_state(&mut self, state: CheckState) {
+        self.state = state;
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.state == CheckState::Checked
+    }
+
+    /// State was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+}
+
+#[test]
+fn test_checkbox_keyboard_activation() {
+    use Theme;
+    use gui::input::{Event, KeyEvent, ScanCode, Key};
+    use super::Dummy;
+
+    let mut gui = Gui::new(Theme::empty());
+    let mut checkbox = Checkbox::new(Dummy::new());
+    checkbox.tri_state = true;
+
+    let space = |pressed| Event::Key(KeyEvent { key: Some(Key::Space), scan_code: ScanCode(0), pressed });
+    let mut states = Vec::new();
+
+    for i in 0..8 {
+        // Focus is given on the second frame, pressing Space before that does nothing.
+        if i == 2 {
+            gui.focus(checkbox.id());
+        }
+
+        gui.input.event(&space(i % 2 == 0));
+        checkbox.appear(&mut gui);
+
+        if checkbox.changed() {
+            states.push(checkbox.state());
+        }
+
+        gui.advance();
+    }
+
+    assert_eq!(states, vec![CheckState::Checked, CheckState::Indeterminate, CheckState::Unchecked]);
+}
+
+#[test]
+fn test_checkbox_indicator_style() {
+    use std::sync::Arc;
+    use Theme;
+    use theme::ElementStyle;
+    use gui::scene::Command;
+    use super::Dummy;
+
+    let mut theme = Theme::empty();
+    let mut style = |variant, kind, opacity| {
+        theme.element_styles.insert((variant, kind), Arc::new(ElementStyle { opacity, ..ElementStyle::default() }));
+    };
+    style(style_variant!("default"), element_kind!("Checkbox"), 0.5);
+    style(style_variant!("default"), element_kind!("Checkbox").with_pseudo_class(pseudo_class!("checked")), 0.25);
+    style(style_variant!("error"), element_kind!("Checkbox"), 0.75);
+    style(style_variant!("indicator"), element_kind!("Checkbox"), 0.125);
+
+    let mut gui = Gui::new(theme);
+    let mut checkbox = Checkbox::new(Dummy::new());
+
+    let (id, indicator_id) = (checkbox.id(), checkbox.indicator_id);
+    let opacities = |gui: &mut Gui, checkbox: &mut Checkbox<Dummy>, variant| {
+        gui.styled(variant, |gui| { checkbox.appear(gui); });
+        let opacities: Vec<_> = gui.scene.commands().iter().filter_map(|command| match *command {
+            Command::CloseElement(ref element) if element.id == id || element.id == indicator_id => {
+                Some((element.id, element.style.opacity))
+            }
+            _ => None,
+        }).collect();
+        gui.advance();
+        opacities
+    };
+
+    // Only the rules of the indicator apply to it, whatever the state and the variant of the checkbox.
+    assert_eq!(opacities(&mut gui, &mut checkbox, style_variant!("default")), vec![(indicator_id, 0.125), (id, 0.5)]);
+    assert_eq!(opacities(&mut gui, &mut checkbox, style_variant!("error")), vec![(indicator_id, 0.125), (id, 0.75)]);
+
+    checkbox.set_state(CheckState::Checked);
+    assert_eq!(opacities(&mut gui, &mut checkbox, style_variant!("default")), vec![(indicator_id, 0.125), (id, 0.25)]);
+}
@@ -0,0 +1,676 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use {Id, Gui, Rect, Var, Vec2, Color};
+use gui::input::ButtonState;
+use gui::scene::{Mesh, MeshVertices, Vertex};
+use super::{Widget, TextInput, ClickArea};
+
+/// Distance between the parts of the picker.
+const SPACING: f64 = 8.0;
+
+/// Width of the hue and the alpha strips.
+const STRIP_WIDTH: f64 = 16.0;
+
+/// Width of the preview of the color.
+const PREVIEW_WIDTH: f64 = 30.0;
+
+/// Size of the thumb in the saturation/value area and the thickness of the thumbs of the strips.
+const THUMB_SIZE: f64 = 6.0;
+
+/// Gradients are interpolated linearly between vertices, these many quads make them close to the sRGB gradients.
+const AREA_QUADS: usize = 8;
+const HUE_QUADS: usize = 12;
+
+/// Control for choosing a color with alpha.
+///
+/// The color is chosen in the saturation/value square, on the hue strip and on the alpha strip, or typed as a hex code,
+/// RGB or HSL components. All of them are in the non-linear sRGB space like in CSS and the chosen color is always made
+/// of 8-bit components so it round-trips exactly through `Color::to_rgba32` and `Color::from_rgba32`.
+///
+/// The `ColorPicker` element contains the `ColorPickerArea`, the `ColorPickerHue` and `ColorPickerAlpha` strips, each
+/// with a `ColorPickerThumb`, the `ColorPickerPreview` and the `TextInput`s for the components. Gradients are drawn as
+/// meshes. When the `eyedropper` hook is set a `ColorPickerEyedropper` button calls it and takes the returned color.
+pub struct ColorPicker {
+    id: Id,
+    area_id: Id,
+    hue_id: Id,
+    alpha_id: Id,
+    thumb_ids: [Id; 3],
+    preview_id: Id,
+    eyedropper_id: Id,
+
+    rgba: [u8; 4],
+
+    /// Hue in degrees, saturation and value in `[0, 1]`. They are kept separately from `rgba` so that the hue and the
+    /// saturation are not lost for grays and black.
+    hsv: [f64; 3],
+
+    drag: Option<Part>,
+    changed: bool,
+
+    hex: TextInput,
+    rgb: [TextInput; 3],
+    hsl: [TextInput; 3],
+    eyedropper_area: ClickArea,
+
+    meshes: [Rc<RefCell<MeshVertices>>; 4],
+
+    /// Called when the eyedropper button is clicked, it should return the color picked from the screen.
+    pub eyedropper: Option<Box<FnMut() -> Option<Color>>>,
+    pub area_size: f64,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Part {
+    Area,
+    Hue,
+    Alpha,
+}
+
+/// Source of a change of the color, the fields showing it are not updated.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Source {
+    Hex,
+    Rgb,
+    Hsl,
+    Other,
+}
+
+impl Widget for ColorPicker {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let (area, hue, alpha) = (Rect::from(self.area_id), Rect::from(self.hue_id), Rect::from(self.alpha_id));
+        let (preview, eyedropper) = (Rect::from(self.preview_id), Rect::from(self.eyedropper_id));
+
+        self.handle_mouse(gui);
+
+        let (area_id, hue_id, alpha_id, thumb_ids) = (self.area_id, self.hue_id, self.alpha_id, self.thumb_ids);
+        let (preview_id, eyedropper_id) = (self.preview_id, self.eyedropper_id);
+        let meshes = self.meshes.clone();
+        let has_eyedropper = self.eyedropper.is_some();
+        let (hex, rgb, hsl, eyedropper_area) = (&mut self.hex, &mut self.rgb, &mut self.hsl, &mut self.eyedropper_area);
+
+        let (hex_place, rgb_places, hsl_places) = gui.element(self.id, element_kind!("ColorPicker"), |gui| {
+            let parts = [(area_id, element_kind!("ColorPickerArea")), (hue_id, element_kind!("ColorPickerHue")), (alpha_id, element_kind!("ColorPickerAlpha"))];
+            for (i, &(id, kind)) in parts.iter().enumerate() {
+                let mesh = meshes[i].clone();
+                gui.element(id, kind, |gui| {
+                    gui.scene.mesh(Mesh { data: mesh });
+                    gui.element(thumb_ids[i], element_kind!("ColorPickerThumb"), |_gui| {});
+                });
+            }
+
+            let mesh = meshes[3].clone();
+            gui.element(preview_id, element_kind!("ColorPickerPreview"), |gui| {
+                gui.scene.mesh(Mesh { data: mesh });
+            });
+
+            if has_eyedropper {
+                gui.element(eyedropper_id, element_kind!("ColorPickerEyedropper"), |gui| {
+                    let click_place = eyedropper_area.appear(gui);
+                    ::layout::equal(gui, eyedropper, click_place);
+                });
+            }
+
+            let hex_place = hex.appear(gui);
+            let rgb_places: Vec<Rect<Var>> = rgb.iter_mut().map(|input| input.appear(gui)).collect();
+            let hsl_places: Vec<Rect<Var>> = hsl.iter_mut().map(|input| input.appear(gui)).collect();
+            (hex_place, rgb_places, hsl_places)
+        });
+
+        self.handle_fields();
+
+        if has_eyedropper && self.eyedropper_area.clicked() {
+            let picked = self.eyedropper.as_mut().and_then(|eyedropper| eyedropper());
+            if let Some(color) = picked {
+                self.set_color_by_user(color);
+            }
+        }
+
+        let area_size = self.area_size;
+        add_constraints!(gui.layout, [
+            (area.left) == place.left,
+            (area.top) == place.top,
+            (area.right - area.left) == area_size,
+            (area.bottom - area.top) == area_size,
+
+            (hue.left) == area.right + SPACING,
+            (alpha.left) == hue.right + SPACING,
+            (place.right) == alpha.right,
+        ]);
+
+        for &strip in &[hue, alpha] {
+            add_constraints!(gui.layout, [
+                (strip.top) == area.top,
+                (strip.bottom) == area.bottom,
+                (strip.right - strip.left) == STRIP_WIDTH,
+            ]);
+        }
+
+        // The preview, the hex code and the eyedropper are in the row under the area followed by the rows of fields.
+        add_constraints!(gui.layout, [
+            (preview.left) == place.left,
+            (preview.right - preview.left) == PREVIEW_WIDTH,
+            (preview.top) == hex_place.top,
+            (preview.bottom) == hex_place.bottom,
+            (hex_place.top) == area.bottom + SPACING,
+            (hex_place.left) == preview.right + SPACING,
+        ]);
+
+        if has_eyedropper {
+            add_constraints!(gui.layout, [
+                (eyedropper.top) == hex_place.top,
+                (eyedropper.bottom) == hex_place.bottom,
+                (eyedropper.right - eyedropper.left) == eyedropper.bottom - eyedropper.top,
+                (eyedropper.left) == hex_place.right + SPACING,
+                (place.right) == eyedropper.right,
+            ]);
+        } else {
+            add_constraints!(gui.layout, [(place.right) == hex_place.right]);
+        }
+
+        fields_row(gui, place, &rgb_places, hex_place.bottom);
+        fields_row(gui, place, &hsl_places, rgb_places[0].bottom);
+        add_constraints!(gui.layout, [(place.bottom) == hsl_places[0].bottom]);
+
+        self.place_thumbs(gui);
+        self.update_meshes(gui);
+
+        place
+    }
+}
+
+impl ColorPicker {
+    pub fn new(color: Color) -> Self {
+        let input = |placeholder: &str| {
+            let mut input = TextInput::new();
+            input.placeholder = placeholder.to_string();
+            input
+        };
+
+        let mesh = || Rc::new(RefCell::new(MeshVertices { vertices: Vec::new(), indices: Vec::new() }));
+
+        let mut picker = ColorPicker {
+            id: Id::unique(),
+            area_id: Id::unique(),
+            hue_id: Id::unique(),
+            alpha_id: Id::unique(),
+            thumb_ids: [Id::unique(), Id::unique(), Id::unique()],
+            preview_id: Id::unique(),
+            eyedropper_id: Id::unique(),
+
+            rgba: [0, 0, 0, 255],
+            hsv: [0.0, 0.0, 0.0],
+
+            drag: None,
+            changed: false,
+
+            hex: input("#RRGGBB"),
+            rgb: [input("R"), input("G"), input("B")],
+            hsl: [input("H"), input("S"), input("L")],
+            eyedropper_area: ClickArea::new(),
+
+            meshes: [mesh(), mesh(), mesh(), mesh()],
+
+            eyedropper: None,
+            area_size: 150.0,
+        };
+
+        picker.set_color(color);
+        picker
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Chosen color.
+    pub fn color(&self) -> Color {
+        Color::from_rgba32(self.rgba[0], self.rgba[1], self.rgba[2], self.rgba[3])
+    }
+
+    /// Chosen color as 8-bit RGBA values.
+    pub fn rgba32(&self) -> (u8, u8, u8, u8) {
+        (self.rgba[0], self.rgba[1], self.rgba[2], self.rgba[3])
+    }
+
+    /// Set the color, it is rounded to 8-bit components.
+    pub fn set_color(&mut self, color: Color) {
+        self.set_rgba(color.to_rgba32());
+        self.update_fields(Source::Other);
+    }
+
+    /// Color was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+
+    fn set_color_by_user(&mut self, color: Color) {
+        self.set_color(color);
+        self.changed = true;
+    }
+
+    /// Set the color and derive HSV from it keeping the hue and the saturation where they are undefined.
+    fn set_rgba(&mut self, (r, g, b, a): (u8, u8, u8, u8)) {
+        let (h, s, v) = rgb_to_hsv(to_unit(r), to_unit(g), to_unit(b));
+        self.hsv = [
+            if s > 0.0 { h } else { self.hsv[0] },
+            if v > 0.0 { s } else { self.hsv[1] },
+            v,
+        ];
+        self.rgba = [r, g, b, a];
+    }
+
+    /// Set the RGB components from the HSV ones.
+    fn set_hsv(&mut self, hsv: [f64; 3]) {
+        let (r, g, b) = hsv_to_rgb(hsv[0], hsv[1], hsv[2]);
+        self.hsv = hsv;
+        self.rgba = [to_u8(r), to_u8(g), to_u8(b), self.rgba[3]];
+    }
+
+    fn handle_mouse(&mut self, gui: &mut Gui) {
+        let parts = [(Part::Area, self.area_id), (Part::Hue, self.hue_id), (Part::Alpha, self.alpha_id)];
+
+        for &(part, id) in &parts {
+            let place = gui.layout.prev_value_rect(Rect::from(id));
+
+            let position = if let Some(mouse) = gui.input.mouse_grabbed_by(id) {
+                if mouse.primary_button.is_pressed() && self.drag == Some(part) {
+                    gui.input.grab_mouse(id);
+                    Some(mouse.position)
+                } else {
+                    self.drag = None;
+                    None
+                }
+            } else if let Some(mouse) = gui.input.get_mouse(|pos| place.contains(pos)) {
+                if mouse.primary_button == ButtonState::JustPressed {
+                    gui.input.grab_mouse(id);
+                    self.drag = Some(part);
+                    Some(mouse.position)
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+
+            let position = match position {
+                Some(position) => position,
+                None => continue,
+            };
+
+            let fraction = |value: f64, start: f64, end: f64| if end > start { ((value - start)/(end - start)).max(0.0).min(1.0) } else { 0.0 };
+            let x = fraction(position.x, place.left, place.right);
+            let y = fraction(position.y, place.top, place.bottom);
+            let prev = self.rgba;
+
+            match part {
+                Part::Area => {
+                    let hue = self.hsv[0];
+                    self.set_hsv([hue, x, 1.0 - y]);
+                }
+                Part::Hue => {
+                    let (saturation, value) = (self.hsv[1], self.hsv[2]);
+                    self.set_hsv([y*360.0, saturation, value]);
+                }
+                Part::Alpha => self.rgba[3] = to_u8(1.0 - y),
+            }
+
+            if self.rgba != prev {
+                self.changed = true;
+            }
+            self.update_fields(Source::Other);
+        }
+    }
+
+    /// Take the color from the fields edited by the user if they are valid.
+    fn handle_fields(&mut self) {
+        if self.hex.changed() {
+            let text = self.hex.text().trim().trim_start_matches('#').to_string();
+            if let Some(color) = Color::try_from_css_hex(text.as_bytes()) {
+                self.set_rgba(color.to_rgba32());
+                self.edited(Source::Hex);
+            }
+        }
+
+        // Every field is asked so that none of them keeps reporting the change.
+        let rgb_changed = self.rgb.iter_mut().map(|input| input.changed()).filter(|&changed| changed).count() > 0;
+        if rgb_changed {
+            let components: Vec<Option<u8>> = self.rgb.iter().map(|input| input.text().trim().parse().ok()).collect();
+            if let (Some(r), Some(g), Some(b)) = (components[0], components[1], components[2]) {
+                let a = self.rgba[3];
+                self.set_rgba((r, g, b, a));
+                self.edited(Source::Rgb);
+            }
+        }
+
+        let hsl_changed = self.hsl.iter_mut().map(|input| input.changed()).filter(|&changed| changed).count() > 0;
+        if hsl_changed {
+            let components: Vec<Option<f64>> = self.hsl.iter().map(|input| input.text().trim().trim_end_matches('%').parse().ok()).collect();
+            let valid = |value: f64, max: f64| if value >= 0.0 && value <= max { Some(value) } else { None };
+            let h = components[0].and_then(|h| valid(h, 360.0));
+            let s = components[1].and_then(|s| valid(s, 100.0));
+            let l = components[2].and_then(|l| valid(l, 100.0));
+
+            if let (Some(h), Some(s), Some(l)) = (h, s, l) {
+                let (r, g, b) = hsl_to_rgb(h, s/100.0, l/100.0);
+                let a = self.rgba[3];
+                self.set_rgba((to_u8(r), to_u8(g), to_u8(b), a));
+                if s > 0.0 {
+                    self.hsv[0] = h % 360.0;
+                }
+                self.edited(Source::Hsl);
+            }
+        }
+    }
+
+    fn edited(&mut self, source: Source) {
+        self.changed = true;
+        self.update_fields(source);
+    }
+
+    /// Show the color in the fields except the ones which are being edited.
+    fn update_fields(&mut self, source: Source) {
+        let (r, g, b, a) = self.rgba32();
+
+        if source != Source::Hex {
+            let hex = if a == 255 {
+                format!("#{:02x}{:02x}{:02x}", r, g, b)
+            } else {
+                format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+            };
+            self.hex.set_text(hex);
+        }
+
+        if source != Source::Rgb {
+            for (input, &component) in self.rgb.iter_mut().zip(&[r, g, b]) {
+                input.set_text(component.to_string());
+            }
+        }
+
+        if source != Source::Hsl {
+            let (_, s, l) = rgb_to_hsl(to_unit(r), to_unit(g), to_unit(b));
+            let h = self.hsv[0];
+            for (input, &component) in self.hsl.iter_mut().zip(&[h, s*100.0, l*100.0]) {
+                input.set_text(format!("{}", component.round()));
+            }
+        }
+    }
+
+    fn place_thumbs(&self, gui: &mut Gui) {
+        let (area, hue, alpha) = (Rect::from(self.area_id), Rect::from(self.hue_id), Rect::from(self.alpha_id));
+        let thumbs = [Rect::from(self.thumb_ids[0]), Rect::from(self.thumb_ids[1]), Rect::from(self.thumb_ids[2])];
+
+        let (s, v) = (self.hsv[1], 1.0 - self.hsv[2]);
+        add_constraints!(gui.layout, [
+            (thumbs[0].right - thumbs[0].left) == THUMB_SIZE,
+            (thumbs[0].bottom - thumbs[0].top) == THUMB_SIZE,
+            (thumbs[0].left + thumbs[0].right) == area.left*(2.0*(1.0 - s)) + area.right*(2.0*s),
+            (thumbs[0].top + thumbs[0].bottom) == area.top*(2.0*(1.0 - v)) + area.bottom*(2.0*v),
+        ]);
+
+        let strips = [(hue, thumbs[1], self.hsv[0]/360.0), (alpha, thumbs[2], 1.0 - self.rgba[3] as f64/255.0)];
+        for &(strip, thumb, t) in &strips {
+            add_constraints!(gui.layout, [
+                (thumb.left) == strip.left,
+                (thumb.right) == strip.right,
+                (thumb.bottom - thumb.top) == THUMB_SIZE,
+                (thumb.top + thumb.bottom) == strip.top*(2.0*(1.0 - t)) + strip.bottom*(2.0*t),
+            ]);
+        }
+    }
+
+    fn update_meshes(&self, gui: &Gui) {
+        let place = |id| gui.layout.prev_value_rect(Rect::from(id));
+        let h = self.hsv[0];
+        let (r, g, b, a) = self.rgba32();
+        let opaque = Color::from_rgb24(r, g, b);
+
+        grid(&mut self.meshes[0].borrow_mut(), place(self.area_id), AREA_QUADS, AREA_QUADS, |x, y| {
+            let (r, g, b) = hsv_to_rgb(h, x, 1.0 - y);
+            Color::from_sRGB(r as f32, g as f32, b as f32)
+        });
+
+        grid(&mut self.meshes[1].borrow_mut(), place(self.hue_id), 1, HUE_QUADS, |_, y| {
+            let (r, g, b) = hsv_to_rgb(y*360.0, 1.0, 1.0);
+            Color::from_sRGB(r as f32, g as f32, b as f32)
+        });
+
+        // Colors are premultiplied by alpha so the linear interpolation between them is exact.
+        grid(&mut self.meshes[2].borrow_mut(), place(self.alpha_id), 1, 1, |_, y| {
+            let alpha = (1.0 - y) as f32;
+            Color { r: opaque.r*alpha, g: opaque.g*alpha, b: opaque.b*alpha, a: alpha }
+        });
+
+        let color = Color::from_rgba32(r, g, b, a);
+        grid(&mut self.meshes[3].borrow_mut(), place(self.preview_id), 1, 1, |_, _| color);
+    }
+}
+
+/// Place the `fields` in a row with equal widths spanning the `place` under the `above` edge.
+fn fields_row(gui: &mut Gui, place: Rect<Var>, fields: &[Rect<Var>], above: Var) {
+    let first = fields[0];
+    add_constraints!(gui.layout, [
+        (first.left) == place.left,
+        (first.top) == above + SPACING,
+    ]);
+
+    for pair in fields.windows(2) {
+        let (prev, field) = (pair[0], pair[1]);
+        add_constraints!(gui.layout, [
+            (field.left) == prev.right + SPACING,
+            (field.top) == prev.top,
+            (field.right - field.left) == prev.right - prev.left,
+        ]);
+    }
+
+    let last = fields[fields.len() - 1];
+    add_constraints!(gui.layout, [(place.right) == last.right]);
+}
+
+/// Replace the `vertices` with a grid of quads covering the `place` colored by `color_at` at the fractions of its width
+/// and height.
+fn grid<F: Fn(f64, f64) -> Color>(vertices: &mut MeshVertices, place: Rect<f64>, columns: usize, rows: usize, color_at: F) {
+    vertices.vertices.clear();
+    vertices.indices.clear();
+
+    for row in 0..(rows + 1) {
+        for column in 0..(columns + 1) {
+            let (x, y) = (column as f64/columns as f64, row as f64/rows as f64);
+            vertices.vertices.push(Vertex {
+                position: Vec2::new(place.left + place.width()*x, place.top + place.height()*y),
+                color: color_at(x, y),
+            });
+        }
+    }
+
+    let row_len = (columns + 1) as u32;
+    for row in 0..rows as u32 {
+        for column in 0..columns as u32 {
+            let first = row*row_len + column;
+            vertices.indices.extend_from_slice(&[first, first + 1, first + row_len, first + 1, first + row_len + 1, first + row_len]);
+        }
+    }
+}
+
+fn to_unit(value: u8) -> f64 {
+    value as f64/255.0
+}
+
+fn to_u8(value: f64) -> u8 {
+    (value*255.0).max(0.0).min(255.0).round() as u8
+}
+
+/// Hue in degrees, saturation and value of non-linear sRGB components in `[0, 1]`.
+fn rgb_to_hsv(r: f64, g: f64, b: f64) -> (f64, f64, f64) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+
+    let s = if max > 0.0 { chroma/max } else { 0.0 };
+    (hue(r, g, b, max, chroma), s, max)
+}
+
+fn hsv_to_rgb(h: f64, s: f64, v: f64) -> (f64, f64, f64) {
+    let chroma = v*s;
+    hue_to_rgb(h, chroma, v - chroma)
+}
+
+/// Hue in degrees, saturation and lightness of non-linear sRGB components in `[0, 1]`.
+fn rgb_to_hsl(r: f64, g: f64, b: f64) -> (f64, f64, f64) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+
+    let l = (max + min)/2.0;
+    let s = if l > 0.0 && l < 1.0 { chroma/(1.0 - (2.0*l - 1.0).abs()) } else { 0.0 };
+    (hue(r, g, b, max, chroma), s, l)
+}
+
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (f64, f64, f64) {
+    let chroma = (1.0 - (2.0*l - 1.0).abs())*s;
+    hue_to_rgb(h, chroma, l - chroma/2.0)
+}
+
+fn hue(r: f64, g: f64, b: f64, max: f64, chroma: f64) -> f64 {
+    let sector = if chroma <= 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b)/chroma + 6.0) % 6.0
+    } else if max == g {
+        (b - r)/chroma + 2.0
+    } else {
+        (r - g)/chroma + 4.0
+    };
+
+    sector*60.0
+}
+
+/// Components of the color with the hue `h` in degrees, the `chroma` and the smallest component `min`.
+fn hue_to_rgb(h: f64, chroma: f64, min: f64) -> (f64, f64, f64) {
+    let sector = (h % 360.0 + 360.0) % 360.0/60.0;
+    let x = chroma*(1.0 - (sector % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    (r + min, g + min, b + min)
+}
+
+#[test]
+fn test_color_picker() {
+    use std::cell::Cell;
+    use std::sync::Arc;
+    use Theme;
+    use theme::ElementStyle;
+    use super::text::MonospaceFont;
+    use gui::input::{Event, MouseButton};
+
+    // Colors round-trip through the picker exactly.
+    for &(r, g, b, a) in &[(0, 0, 0, 255), (255, 255, 255, 255), (117, 26, 255, 255), (12, 200, 99, 128), (1, 2, 3, 4)] {
+        let picker = ColorPicker::new(Color::from_rgba32(r, g, b, a));
+        assert_eq!(picker.rgba32(), (r, g, b, a));
+        assert_eq!(picker.color(), Color::from_rgba32(r, g, b, a));
+        assert_eq!(picker.color().to_rgba32(), (r, g, b, a));
+    }
+
+    let picked = Rc::new(Cell::new(0));
+    let mut picker = ColorPicker::new(Color::from_rgb24(255, 0, 0));
+    {
+        let picked = picked.clone();
+        picker.eyedropper = Some(Box::new(move || {
+            picked.set(picked.get() + 1);
+            Some(Color::from_rgb24(0, 0, 255))
+        }));
+    }
+    picker.area_size = 100.0;
+    assert_eq!(picker.hex.text(), "#ff0000");
+    assert_eq!(picker.hsl[0].text(), "0");
+
+    let mut theme = Theme::empty();
+    theme.element_styles.insert((Default::default(), element_kind!("TextInput")), Arc::new(ElementStyle {
+        font: Arc::new(MonospaceFont::default()),
+        ..ElementStyle::default()
+    }));
+
+    let mut gui = Gui::new(theme);
+    let frame = |gui: &mut Gui, picker: &mut ColorPicker, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = picker.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let click = |x, y| vec![Event::MouseMoved(Vec2::new(x, y)), Event::MouseButton(MouseButton::Primary, true)];
+    let release = || vec![Event::MouseButton(MouseButton::Primary, false)];
+
+    frame(&mut gui, &mut picker, &[Event::MouseEntered]);
+    frame(&mut gui, &mut picker, &[]);
+
+    // The middle of the area is half saturated and half bright, the hue is kept.
+    frame(&mut gui, &mut picker, &click(50.0, 50.0));
+    frame(&mut gui, &mut picker, &release());
+    assert!(picker.changed());
+    assert_eq!(picker.rgba32(), (128, 64, 64, 255));
+    assert_eq!(picker.rgb[1].text(), "64");
+
+    // Dragging to the bottom makes it black, the hue and the saturation survive.
+    frame(&mut gui, &mut picker, &click(50.0, 50.0));
+    frame(&mut gui, &mut picker, &[Event::MouseMoved(Vec2::new(50.0, 150.0))]);
+    frame(&mut gui, &mut picker, &release());
+    assert_eq!(picker.rgba32(), (0, 0, 0, 255));
+    frame(&mut gui, &mut picker, &click(50.0, 0.0));
+    frame(&mut gui, &mut picker, &release());
+    assert_eq!(picker.rgba32(), (255, 128, 128, 255));
+
+    // The middle of the alpha strip is half transparent.
+    let alpha = gui.layout.prev_value_rect(Rect::from(picker.alpha_id));
+    frame(&mut gui, &mut picker, &click(alpha.center().x, 50.0));
+    frame(&mut gui, &mut picker, &release());
+    assert_eq!(picker.rgba32(), (255, 128, 128, 128));
+    assert_eq!(picker.hex.text(), "#ff808080");
+
+    // Typed codes are taken only when they are valid.
+    gui.focus(picker.hex.id());
+    frame(&mut gui, &mut picker, &[]);
+    picker.hex.set_selection(0..picker.hex.text().len());
+    gui.input.ime_commit("#12ab");
+    frame(&mut gui, &mut picker, &[]);
+    assert_eq!(picker.rgba32(), (0x11, 0x22, 0xaa, 0xbb));
+    assert_eq!(picker.rgb[2].text(), "170");
+    frame(&mut gui, &mut picker, &[Event::Char('x')]);
+    assert_eq!(picker.rgba32(), (0x11, 0x22, 0xaa, 0xbb));
+
+    gui.focus(picker.hsl[2].id());
+    frame(&mut gui, &mut picker, &[]);
+    picker.hsl[2].set_selection(0..picker.hsl[2].text().len());
+    gui.input.ime_commit("100");
+    frame(&mut gui, &mut picker, &[]);
+    assert_eq!(picker.rgba32(), (255, 255, 255, 0xbb));
+    assert_eq!(picker.hex.text(), "#ffffffbb");
+
+    // Gradients are meshes in the places of the parts.
+    assert_eq!(picker.meshes[0].borrow().vertices.len(), (AREA_QUADS + 1)*(AREA_QUADS + 1));
+    assert_eq!(picker.meshes[1].borrow().vertices[0].color.to_rgba32(), (255, 0, 0, 255));
+
+    let eyedropper = gui.layout.prev_value_rect(Rect::from(picker.eyedropper_id));
+    frame(&mut gui, &mut picker, &click(eyedropper.center().x, eyedropper.center().y));
+    frame(&mut gui, &mut picker, &release());
+    assert_eq!(picked.get(), 1);
+    assert_eq!(picker.rgba32(), (0, 0, 255, 255));
+}
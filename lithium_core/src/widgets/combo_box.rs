@@ -0,0 +1,521 @@
+use std::collections::HashMap;
+use {Id, Gui, Rect, Var};
+use gui::input::{ButtonState, Key, Direction};
+use layout;
+use super::{Widget, ClickArea, Label, TextInput, Popup, VirtualList, ListModel, RowHeight};
+
+/// Dropdown choosing one of the options from a list shown in a popup.
+///
+/// The combo box is the `ComboBox` element containing its parts, which are `ComboBox` elements as well and are styled
+/// with the `button`, `list` and `item` style variants (`ComboBox.button` in CSS). The button shows the selected option
+/// and opens the list when clicked. In the editable mode the button is a square at the right side of a `TextInput` and
+/// any text can be entered, the options matching it are offered in the list. Items of the list have the `selected`
+/// pseudo-class for the selected option and `current` for the one chosen with the keyboard.
+///
+/// While focused Down opens the list, as do Space and Enter unless the combo box is editable. Up and Down move through the
+/// list, Enter selects the current item and Escape closes the list. Typing while the list is open filters it to the
+/// options containing the typed text, only the items in the view are built so long lists stay fast.
+pub struct ComboBox {
+    id: Id,
+    button_id: Id,
+    click_area: ClickArea,
+    label: Label,
+    input: TextInput,
+    popup: Popup<VirtualList<Options>>,
+
+    /// Text typed to filter the list, the text of the input in the editable mode.
+    filter: String,
+    changed: bool,
+
+    /// Any text can be entered, the options are only suggestions.
+    pub editable: bool,
+    pub row_height: f64,
+
+    /// Maximum number of items visible in the list at once.
+    pub max_rows: usize,
+}
+
+struct Options {
+    options: Vec<String>,
+
+    /// Indices of the options containing the filter.
+    matching: Vec<usize>,
+    labels: HashMap<usize, Label>,
+    selected: Option<usize>,
+}
+
+impl Widget for ComboBox {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let button = Rect::from(self.button_id);
+
+        if self.click_area.clicked() {
+            if self.popup.is_open() {
+                self.close();
+            } else {
+                self.open();
+            }
+
+            let focused = if self.editable { self.input.id() } else { self.id };
+            gui.focus(focused);
+        }
+
+        let (id, button_id, editable) = (self.id, self.button_id, self.editable);
+
+        gui.focus_scope(self.id, |gui| {
+            if !editable {
+                gui.focusable(id);
+            }
+
+            let (click_area, label, input) = (&mut self.click_area, &mut self.label, &mut self.input);
+
+            gui.element(id, element_kind!("ComboBox"), |gui| {
+                gui.styled(style_variant!("button"), |gui| {
+                    gui.element(button_id, element_kind!("ComboBox"), |gui| {
+                        let click_area_place = click_area.appear(gui);
+                        layout::equal(gui, button, click_area_place);
+
+                        if !editable {
+                            let label_place = label.appear(gui);
+                            layout::center_horizontal(gui, button, label_place);
+                            add_constraints!(gui.layout, [
+                                (label_place.left) == button.left,
+                                (button.right) >= label_place.right,
+                                (button.bottom - button.top) == label_place.bottom - label_place.top,
+                            ]);
+                        }
+                    });
+                });
+
+                if editable {
+                    let input_place = input.appear(gui);
+                    add_constraints!(gui.layout, [
+                        (input_place.left) == place.left,
+                        (input_place.right) == button.left,
+                        (button.right) == place.right,
+                        (button.right - button.left) == button.bottom - button.top,
+                    ]);
+                    layout::equal_horizontal(gui, place, input_place);
+                    layout::equal_horizontal(gui, place, button);
+                } else {
+                    layout::equal(gui, place, button);
+                }
+            });
+
+            if editable && self.input.changed() {
+                let text = self.input.text().to_owned();
+                self.set_filter(text);
+                self.changed = true;
+
+                if !self.popup.is_open() && !self.popup.content.model.matching.is_empty() {
+                    self.popup.open();
+                }
+            }
+
+            self.handle_keyboard(gui);
+
+            if self.popup.is_open() {
+                let rows = self.popup.content.model.matching.len().min(self.max_rows);
+                let height = rows as f64*self.row_height;
+                self.popup.content.row_height = RowHeight::Fixed(self.row_height);
+
+                let list = Rect::from(self.popup.content.id());
+                add_constraints!(gui.layout, [
+                    (list.right - list.left) == place.right - place.left,
+                    (list.bottom - list.top) == height,
+                ]);
+
+                let popup = &mut self.popup;
+                gui.styled(style_variant!("list"), |gui| {
+                    popup.appear(gui, place);
+                });
+
+                if self.popup.closed() {
+                    self.close();
+                }
+
+                if self.popup.content.changed() {
+                    if let Some(cursor) = self.popup.content.cursor() {
+                        self.choose_matching(gui, cursor);
+                    }
+                }
+            }
+        });
+
+        place
+    }
+}
+
+impl ListModel for Options {
+    fn len(&self) -> usize {
+        self.matching.len()
+    }
+
+    fn key(&self, index: usize) -> u64 {
+        self.matching[index] as u64
+    }
+
+    fn row(&mut self, gui: &mut Gui, index: usize, id: Id) {
+        let option = self.matching[index];
+        let options = &self.options;
+        let label = self.labels.entry(option).or_insert_with(|| Label::new(&options[option]));
+
+        let row = Rect::from(id);
+        let label_place = label.appear(gui);
+        layout::center_horizontal(gui, row, label_place);
+        add_constraints!(gui.layout, [(label_place.left) == row.left]);
+    }
+}
+
+impl ComboBox {
+    pub fn new(options: Vec<String>) -> Self {
+        let mut list = VirtualList::new(Options {
+            options: Vec::new(),
+            matching: Vec::new(),
+            labels: HashMap::new(),
+            selected: None,
+        }, RowHeight::Fixed(20.0));
+        list.kind = element_kind!("ComboBox");
+        list.row_kind = element_kind!("ComboBox");
+        list.row_variant = Some(style_variant!("item"));
+        list.multi_select = false;
+
+        let mut combo_box = ComboBox {
+            id: Id::unique(),
+            button_id: Id::unique(),
+            click_area: ClickArea::new(),
+            label: Label::new(""),
+            input: TextInput::new(),
+            popup: Popup::new(list),
+
+            filter: String::new(),
+            changed: false,
+
+            editable: false,
+            row_height: 20.0,
+            max_rows: 8,
+        };
+
+        combo_box.set_options(options);
+        combo_box
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn options(&self) -> &[String] {
+        &self.popup.content.model.options
+    }
+
+    /// Replace the options, the selection is kept if there is still an option at its index.
+    pub fn set_options(&mut self, options: Vec<String>) {
+        {
+            let model = &mut self.popup.content.model;
+            model.options = options;
+            model.labels.clear();
+        }
+
+        let selected = self.selected().filter(|&selected| selected < self.options().len());
+        self.set_selected(selected);
+
+        let filter = self.filter.clone();
+        self.set_filter(filter);
+    }
+
+    /// Index of the selected option.
+    pub fn selected(&self) -> Option<usize> {
+        self.popup.content.model.selected
+    }
+
+    pub fn set_selected(&mut self, selected: Option<usize>) {
+        let text = selected.map_or(String::new(), |selected| self.options()[selected].clone());
+
+        self.popup.content.model.selected = selected;
+        self.label.set_text(text.as_str());
+        if self.editable {
+            self.input.set_text(text);
+            self.input.changed();
+        }
+        self.sync_list_selection();
+    }
+
+    /// Text of the selected option, in the editable mode the entered text.
+    pub fn text(&self) -> &str {
+        if self.editable {
+            return self.input.text();
+        }
+
+        match self.selected() {
+            Some(selected) => &self.options()[selected],
+            None => "",
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.popup.is_open()
+    }
+
+    /// Open the list with the selected option as the current one.
+    pub fn open(&mut self) {
+        if !self.editable {
+            self.set_filter(String::new());
+        }
+
+        let current = self.selected().and_then(|selected| {
+            self.popup.content.model.matching.iter().position(|&option| option == selected)
+        });
+
+        self.popup.content.set_cursor(current);
+        if let Some(current) = current {
+            self.popup.content.scroll_to_index(current);
+        }
+        self.popup.open();
+    }
+
+    pub fn close(&mut self) {
+        self.popup.close();
+
+        if !self.editable {
+            self.set_filter(String::new());
+        }
+    }
+
+    /// Selection or the entered text was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+
+    fn handle_keyboard(&mut self, gui: &mut Gui) {
+        if !gui.is_focused_within(self.id) {
+            return;
+        }
+
+        gui.input.take_navigation(Direction::Up);
+        gui.input.take_navigation(Direction::Down);
+
+        let keyboard = &gui.input.keyboard;
+        let (down, up) = (keyboard.presses(Key::Down), keyboard.presses(Key::Up));
+        let enter = keyboard.key_state(Key::Return) == ButtonState::JustPressed;
+
+        if !self.popup.is_open() {
+            let space = !self.editable && keyboard.key_state(Key::Space) == ButtonState::JustPressed;
+            let typed = !self.editable && !keyboard.input().is_empty();
+
+            if down > 0 || space || (enter && !self.editable) {
+                self.open();
+            } else if typed {
+                let typed = keyboard.input().to_owned();
+                self.open();
+                self.set_filter(typed);
+            }
+            return;
+        }
+
+        if !self.editable {
+            let mut filter = self.filter.clone();
+            for _ in 0..keyboard.presses(Key::Backspace) {
+                filter.pop();
+            }
+            filter.extend(keyboard.input().chars().filter(|char| !char.is_control()));
+
+            if filter != self.filter {
+                self.set_filter(filter);
+            }
+        }
+
+        let len = self.popup.content.model.matching.len();
+        if len == 0 {
+            return;
+        }
+
+        let list = &mut self.popup.content;
+        if down + up > 0 {
+            let cursor = match list.cursor() {
+                Some(cursor) => (cursor + down).saturating_sub(up).min(len - 1),
+                None if down > 0 => 0,
+                None => len - 1,
+            };
+
+            list.set_cursor(Some(cursor));
+            list.scroll_to_index(cursor);
+        }
+
+        if enter {
+            if let Some(cursor) = list.cursor() {
+                self.choose_matching(gui, cursor);
+            }
+        }
+    }
+
+    /// Select the option shown at the `index` of the list and close it.
+    fn choose_matching(&mut self, gui: &mut Gui, index: usize) {
+        let option = self.popup.content.model.matching.get(index).cloned();
+
+        if let Some(option) = option {
+            self.set_selected(Some(option));
+            self.changed = true;
+        }
+
+        self.close();
+
+        let focused = if self.editable { self.input.id() } else { self.id };
+        gui.focus(focused);
+    }
+
+    /// Show only the options containing the `filter` ignoring case.
+    fn set_filter(&mut self, filter: String) {
+        let lowercase = filter.to_lowercase();
+        self.filter = filter;
+
+        {
+            let list = &mut self.popup.content;
+            list.model.matching = {
+                let options = &list.model.options;
+                (0..options.len()).filter(|&option| {
+                    lowercase.is_empty() || options[option].to_lowercase().contains(&lowercase)
+                }).collect()
+            };
+
+            // The best match is the current item so Enter picks it.
+            let len = list.model.matching.len();
+            let current = if len == 0 {
+                None
+            } else if lowercase.is_empty() {
+                list.cursor().filter(|&cursor| cursor < len)
+            } else {
+                Some(0)
+            };
+            list.set_cursor(current);
+            list.scroll_to_index(current.unwrap_or(0));
+        }
+
+        self.sync_list_selection();
+    }
+
+    /// Mark the selected option in the list if it is there.
+    fn sync_list_selection(&mut self) {
+        let list = &mut self.popup.content;
+        let selected = list.model.selected.and_then(|selected| list.model.matching.iter().position(|&option| option == selected));
+
+        list.clear_selection();
+        if let Some(selected) = selected {
+            list.select(selected);
+        }
+    }
+}
+
+#[test]
+fn test_combo_box() {
+    use std::sync::Arc;
+    use {Theme, Vec2};
+    use theme::ElementStyle;
+    use super::text::MonospaceFont;
+    use gui::input::{Event, MouseButton, KeyEvent, ScanCode};
+
+    let mut theme = Theme::empty();
+    for &kind in &[element_kind!("Label"), element_kind!("TextInput")] {
+        theme.element_styles.insert((Default::default(), kind), Arc::new(ElementStyle {
+            font: Arc::new(MonospaceFont::default()),
+            ..ElementStyle::default()
+        }));
+    }
+
+    let mut gui = Gui::new(theme);
+    gui.set_window_size(Vec2::new(400.0, 400.0));
+
+    let options: Vec<String> = (0..1000).map(|i| format!("Option {}", i)).collect();
+    let mut combo_box = ComboBox::new(options.clone());
+
+    let frame = |gui: &mut Gui, combo_box: &mut ComboBox, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = combo_box.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 10.0,
+            (place.top) == 10.0,
+            (place.right) == 210.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+    let tap = |gui: &mut Gui, combo_box: &mut ComboBox, k| {
+        frame(gui, combo_box, &[key(k, true)]);
+        frame(gui, combo_box, &[key(k, false)]);
+    };
+    let click = |gui: &mut Gui, combo_box: &mut ComboBox, x, y| {
+        frame(gui, combo_box, &[Event::MouseMoved(Vec2::new(x, y)), Event::MouseButton(MouseButton::Primary, true)]);
+        frame(gui, combo_box, &[Event::MouseButton(MouseButton::Primary, false)]);
+        frame(gui, combo_box, &[]);
+    };
+
+    frame(&mut gui, &mut combo_box, &[Event::MouseEntered]);
+    click(&mut gui, &mut combo_box, 20.0, 20.0);
+    assert!(combo_box.is_open());
+
+    // The list is below the button and only the rows in its view are built.
+    let list = gui.layout.prev_value_rect(Rect::from(combo_box.popup.content.id()));
+    assert_eq!(list, Rect { left: 10.0, top: 30.0, right: 210.0, bottom: 190.0 });
+    frame(&mut gui, &mut combo_box, &[]);
+    assert_eq!(combo_box.popup.content.visible_rows().len(), 8);
+
+    // Clicking the third row selects its option.
+    click(&mut gui, &mut combo_box, 50.0, 75.0);
+    assert!(combo_box.changed());
+    assert_eq!(combo_box.selected(), Some(2));
+    assert_eq!(combo_box.text(), "Option 2");
+    assert!(!combo_box.is_open());
+    assert!(gui.is_focused(combo_box.id()));
+
+    // Typing opens the list filtered to the matching options.
+    frame(&mut gui, &mut combo_box, &[Event::Char('9'), Event::Char('9')]);
+    frame(&mut gui, &mut combo_box, &[Event::Char('9')]);
+    frame(&mut gui, &mut combo_box, &[]);
+    assert!(combo_box.is_open());
+    assert_eq!(combo_box.popup.content.model.matching, vec![999]);
+
+    frame(&mut gui, &mut combo_box, &[key(Key::Backspace, true)]);
+    frame(&mut gui, &mut combo_box, &[key(Key::Backspace, false)]);
+    assert_eq!(combo_box.popup.content.model.matching.len(), 19);
+
+    tap(&mut gui, &mut combo_box, Key::Down);
+    tap(&mut gui, &mut combo_box, Key::Return);
+    assert_eq!(combo_box.selected(), Some(199));
+    assert!(!combo_box.is_open());
+
+    // Opening with the keyboard starts at the selected option, Escape closes without changing it.
+    tap(&mut gui, &mut combo_box, Key::Down);
+    assert!(combo_box.is_open());
+    assert_eq!(combo_box.popup.content.cursor(), Some(199));
+    frame(&mut gui, &mut combo_box, &[]);
+    assert!(combo_box.popup.content.visible_rows().iter().any(|&(index, _)| index == 199));
+    tap(&mut gui, &mut combo_box, Key::Up);
+    tap(&mut gui, &mut combo_box, Key::Escape);
+    assert!(!combo_box.is_open());
+    assert_eq!(combo_box.selected(), Some(199));
+
+    // The editable combo box accepts any text and suggests the matching options.
+    let mut editable = ComboBox::new(vec!["red".to_owned(), "green".to_owned(), "grey".to_owned()]);
+    editable.editable = true;
+
+    frame(&mut gui, &mut editable, &[]);
+    click(&mut gui, &mut editable, 20.0, 20.0);
+    frame(&mut gui, &mut editable, &[Event::Char('g')]);
+    frame(&mut gui, &mut editable, &[Event::Char('r')]);
+    frame(&mut gui, &mut editable, &[]);
+    assert!(editable.changed());
+    assert_eq!(editable.text(), "gr");
+    assert_eq!(editable.popup.content.model.matching, vec![1, 2]);
+
+    tap(&mut gui, &mut editable, Key::Down);
+    tap(&mut gui, &mut editable, Key::Return);
+    assert_eq!(editable.text(), "grey");
+    assert_eq!(editable.selected(), Some(2));
+    assert!(!editable.is_open());
+}
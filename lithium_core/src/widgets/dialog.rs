@@ -0,0 +1,564 @@
+use {Id, Gui, Rect, Var};
+use gui::input::{ButtonState, Key};
+use layout;
+use super::{Widget, Label, TextInput, modifiers};
+use super::button::Button;
+
+/// Space between the parts of the standard dialogs.
+const SPACING: f64 = 10.0;
+
+/// Minimal width of the text field of a prompt.
+const PROMPT_WIDTH: f64 = 200.0;
+
+/// How the user has finished with a dialog.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub enum DialogResult<R> {
+    Accepted(R),
+
+    /// Closed with Escape or a cancel button.
+    Cancelled,
+}
+
+/// Widget shown inside of a `Dialog`.
+pub trait DialogContent: Widget {
+    type Output;
+
+    /// Result chosen during the last appearance, for example with a button, or `None` while the dialog stays open.
+    fn result(&mut self) -> Option<DialogResult<Self::Output>>;
+
+    /// Result of pressing Enter in the dialog or `None` if Enter doesn't close it.
+    fn accept(&mut self) -> Option<Self::Output> {
+        None
+    }
+}
+
+/// Modal dialog blocking the rest of the window while it is open.
+///
+/// The dialog consists of the `DialogBackdrop` element covering the window and the `Dialog` element centered in it which
+/// contains the title in a `DialogTitle` element above the content. Window size must be set with `Gui::set_window_size`
+/// for the backdrop to cover it.
+///
+/// The mouse doesn't reach the widgets under the backdrop and the focus stays inside of the dialog, Tab and Shift+Tab move
+/// it between the focusable widgets of the content. Escape cancels the dialog and Enter accepts it if the content allows.
+/// A dialog opened while another one is open is stacked above it and only the topmost one reacts to the keyboard.
+pub struct Dialog<T: DialogContent> {
+    id: Id,
+    frame_id: Id,
+    title_id: Id,
+    open: bool,
+    result: Option<DialogResult<T::Output>>,
+
+    pub title: Label,
+    pub content: T,
+}
+
+/// Content of `Dialog::alert` showing a message with an OK button.
+pub struct Alert {
+    message: Label,
+    ok: Button<Label>,
+    buttons: Buttons,
+}
+
+/// Content of `Dialog::confirm` asking the user to confirm or cancel an action.
+pub struct Confirm {
+    message: Label,
+    ok: Button<Label>,
+    cancel: Button<Label>,
+    buttons: Buttons,
+}
+
+/// Content of `Dialog::prompt` asking the user to enter a line of text.
+pub struct Prompt {
+    message: Label,
+    ok: Button<Label>,
+    cancel: Button<Label>,
+    buttons: Buttons,
+
+    pub input: TextInput,
+}
+
+/// Row of buttons aligned to the right below a message.
+struct Buttons {
+    id: Id,
+}
+
+impl<T: DialogContent> Widget for Dialog<T> {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+
+        if !self.open {
+            return place;
+        }
+
+        // Only the topmost popup from the previous frame takes the keyboard, which also leaves Escape to popups opened
+        // from the dialog.
+        let topmost = gui.input.popups().last().map(|&(id, _)| id) == Some(self.id);
+
+        let (frame_id, title_id) = (self.frame_id, self.title_id);
+        let frame = Rect::from(frame_id);
+        let (title, content) = (&mut self.title, &mut self.content);
+
+        gui.modal(self.id, element_kind!("DialogBackdrop"), |gui| {
+            gui.element(frame_id, element_kind!("Dialog"), |gui| {
+                let title_place = Rect::from(title_id);
+
+                gui.element(title_id, element_kind!("DialogTitle"), |gui| {
+                    let label_place = title.appear(gui);
+                    layout::equal(gui, title_place, label_place);
+                });
+
+                let content_place = content.appear(gui);
+
+                add_constraints!(gui.layout, [
+                    (title_place.top) == frame.top,
+                    (title_place.left) == frame.left,
+                    (frame.right) >= title_place.right,
+                    (content_place.top) == title_place.bottom,
+                    (frame.bottom) == content_place.bottom,
+                ]);
+                layout::equal_vertical(gui, frame, content_place);
+            });
+
+        });
+
+        add_constraints!(gui.layout, [
+            (frame.left - place.left) == place.right - frame.right,
+            (frame.top - place.top) == place.bottom - frame.bottom,
+        ]);
+
+        if let Some(window) = gui.window() {
+            add_constraints!(gui.layout, [
+                (place.left) == window.left,
+                (place.top) == window.top,
+                (place.right) == window.right,
+                (place.bottom) == window.bottom,
+            ]);
+        }
+
+        let mut result = self.content.result();
+
+        if topmost {
+            let keyboard = &gui.input.keyboard;
+            let (shift, control) = modifiers(gui);
+
+            // Result chosen by the content, for example with a button pressed by Space, is kept.
+            if result.is_none() {
+                if keyboard.key_state(Key::Escape) == ButtonState::JustPressed {
+                    result = Some(DialogResult::Cancelled);
+                } else if keyboard.key_state(Key::Return) == ButtonState::JustPressed {
+                    if let Some(output) = self.content.accept() {
+                        result = Some(DialogResult::Accepted(output));
+                    }
+                }
+            }
+
+            if !control {
+                for _ in 0..keyboard.presses(Key::Tab) {
+                    gui.focus.cycle(Some(self.id), !shift);
+                }
+            }
+
+            // Focus moves into the dialog when it opens. Content without focusable widgets still takes the keyboard from
+            // the widgets under the dialog.
+            if !gui.is_focused_within(self.id) {
+                gui.focus.cycle(Some(self.id), true);
+            }
+            if !gui.is_focused_within(self.id) {
+                gui.focus.blur();
+            }
+        }
+
+        if result.is_some() {
+            self.open = false;
+            self.result = result;
+        }
+
+        place
+    }
+}
+
+impl Widget for Alert {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let message_place = self.message.appear(gui);
+        self.buttons.appear(gui, message_place, &mut [&mut self.ok])
+    }
+}
+
+impl Widget for Confirm {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let message_place = self.message.appear(gui);
+        self.buttons.appear(gui, message_place, &mut [&mut self.cancel, &mut self.ok])
+    }
+}
+
+impl Widget for Prompt {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let message_place = self.message.appear(gui);
+        let input_place = self.input.appear(gui);
+
+        add_constraints!(gui.layout, [
+            (input_place.top) == message_place.bottom + SPACING,
+            (input_place.left) == message_place.left,
+            (input_place.right - input_place.left) >= PROMPT_WIDTH,
+            (input_place.right) >= message_place.right,
+        ]);
+
+        let place = self.buttons.appear(gui, input_place, &mut [&mut self.cancel, &mut self.ok]);
+        add_constraints!(gui.layout, [(input_place.right) == place.right - SPACING]);
+
+        place
+    }
+}
+
+impl DialogContent for Alert {
+    type Output = ();
+
+    fn result(&mut self) -> Option<DialogResult<()>> {
+        if self.ok.clicked() { Some(DialogResult::Accepted(())) } else { None }
+    }
+
+    fn accept(&mut self) -> Option<()> {
+        Some(())
+    }
+}
+
+impl DialogContent for Confirm {
+    type Output = ();
+
+    fn result(&mut self) -> Option<DialogResult<()>> {
+        if self.ok.clicked() {
+            Some(DialogResult::Accepted(()))
+        } else if self.cancel.clicked() {
+            Some(DialogResult::Cancelled)
+        } else {
+            None
+        }
+    }
+
+    fn accept(&mut self) -> Option<()> {
+        Some(())
+    }
+}
+
+impl DialogContent for Prompt {
+    type Output = String;
+
+    fn result(&mut self) -> Option<DialogResult<String>> {
+        if self.ok.clicked() {
+            Some(DialogResult::Accepted(self.input.text().to_owned()))
+        } else if self.cancel.clicked() {
+            Some(DialogResult::Cancelled)
+        } else {
+            None
+        }
+    }
+
+    fn accept(&mut self) -> Option<String> {
+        Some(self.input.text().to_owned())
+    }
+}
+
+impl Buttons {
+    fn new() -> Self {
+        Buttons {
+            id: Id::unique(),
+        }
+    }
+
+    /// Place the `buttons` in a row below the `above` part and return the place of the whole content.
+    fn appear(&mut self, gui: &mut Gui, above: Rect<Var>, buttons: &mut [&mut Button<Label>]) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let mut next: Option<Rect<Var>> = None;
+
+        // Buttons are placed from the right.
+        for button in buttons.iter_mut().rev() {
+            let button_place = button.appear(gui);
+
+            match next {
+                Some(next) => { add_constraints!(gui.layout, [(button_place.right) == next.left - SPACING]); }
+                None => { add_constraints!(gui.layout, [(button_place.right) == place.right - SPACING]); }
+            }
+            add_constraints!(gui.layout, [
+                (button_place.top) == above.bottom + SPACING,
+                (place.bottom) == button_place.bottom + SPACING,
+                (button_place.left) >= place.left + SPACING,
+            ]);
+            next = Some(button_place);
+        }
+
+        add_constraints!(gui.layout, [
+            (above.top) == place.top + SPACING,
+            (above.left) == place.left + SPACING,
+            (place.right) >= above.right + SPACING,
+        ]);
+
+        place
+    }
+}
+
+impl<T: DialogContent> Dialog<T> {
+    pub fn new(title: &str, content: T) -> Self {
+        Dialog {
+            id: Id::unique(),
+            frame_id: Id::unique(),
+            title_id: Id::unique(),
+            open: false,
+            result: None,
+
+            title: Label::new(title),
+            content,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.result = None;
+    }
+
+    /// Close the dialog without a result.
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Result with which the user has closed the dialog since the last call.
+    pub fn take_result(&mut self) -> Option<DialogResult<T::Output>> {
+        self.result.take()
+    }
+}
+
+impl Dialog<Alert> {
+    pub fn alert(title: &str, message: &str) -> Self {
+        Dialog::new(title, Alert {
+            message: Label::new(message),
+            ok: Button::new(Label::new("OK")),
+            buttons: Buttons::new(),
+        })
+    }
+}
+
+impl Dialog<Confirm> {
+    pub fn confirm(title: &str, message: &str) -> Self {
+        Dialog::new(title, Confirm {
+            message: Label::new(message),
+            ok: Button::new(Label::new("OK")),
+            cancel: Button::new(Label::new("Cancel")),
+            buttons: Buttons::new(),
+        })
+    }
+}
+
+impl Dialog<Prompt> {
+    /// Dialog asking for a line of text, the field initially contains the `text`.
+    pub fn prompt(title: &str, message: &str, text: &str) -> Self {
+        let mut input = TextInput::new();
+        input.set_text(text);
+
+        Dialog::new(title, Prompt {
+            message: Label::new(message),
+            ok: Button::new(Label::new("OK")),
+            cancel: Button::new(Label::new("Cancel")),
+            buttons: Buttons::new(),
+            input,
+        })
+    }
+}
+
+#[test]
+fn test_dialog() {
+    use {Theme, Vec2};
+    use gui::input::{Event, MouseButton, KeyEvent, ScanCode};
+    use widgets::ClickArea;
+
+    struct Fields {
+        id: Id,
+        fields: Vec<TextInput>,
+        result: Option<DialogResult<usize>>,
+    }
+
+    impl Widget for Fields {
+        fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+            let place = Rect::from(self.id);
+            let mut top = place.top;
+
+            for field in &mut self.fields {
+                let field_place = field.appear(gui);
+                add_constraints!(gui.layout, [
+                    (field_place.top) == top,
+                    (field_place.left) == place.left,
+                    (field_place.right) == place.left + 50.0,
+                ]);
+                top = field_place.bottom;
+            }
+
+            add_constraints!(gui.layout, [
+                (place.right) == place.left + 50.0,
+                (place.bottom) == top,
+            ]);
+
+            place
+        }
+    }
+
+    impl DialogContent for Fields {
+        type Output = usize;
+
+        fn result(&mut self) -> Option<DialogResult<usize>> {
+            self.result.take()
+        }
+
+        fn accept(&mut self) -> Option<usize> {
+            Some(self.fields.len())
+        }
+    }
+
+    let mut gui = Gui::new(Theme::empty());
+    gui.set_window_size(Vec2::new(200.0, 200.0));
+
+    let mut background = ClickArea::new();
+    let outside = TextInput::new();
+    let fields = |count| Fields { id: Id::unique(), fields: (0..count).map(|_| TextInput::new()).collect(), result: None };
+    let mut dialogs = (Dialog::new("First", fields(3)), Dialog::new("Second", fields(2)));
+
+    let frame = |gui: &mut Gui, background: &mut ClickArea, dialogs: &mut (Dialog<Fields>, Dialog<Fields>), events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = background.appear(gui);
+        gui.focusable(outside.id());
+        dialogs.0.appear(gui);
+        dialogs.1.appear(gui);
+
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 200.0,
+            (place.bottom) == 200.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+    let tap = |gui: &mut Gui, background: &mut ClickArea, dialogs: &mut (Dialog<Fields>, Dialog<Fields>), keys: &[Key]| {
+        frame(gui, background, dialogs, &keys.iter().map(|&k| key(k, true)).collect::<Vec<_>>());
+        frame(gui, background, dialogs, &keys.iter().map(|&k| key(k, false)).collect::<Vec<_>>());
+    };
+    let click = |gui: &mut Gui, background: &mut ClickArea, dialogs: &mut (Dialog<Fields>, Dialog<Fields>)| {
+        frame(gui, background, dialogs, &[Event::MouseMoved(Vec2::new(5.0, 5.0)), Event::MouseButton(MouseButton::Primary, true)]);
+        frame(gui, background, dialogs, &[Event::MouseButton(MouseButton::Primary, false)]);
+    };
+
+    gui.focus(outside.id());
+    frame(&mut gui, &mut background, &mut dialogs, &[Event::MouseEntered]);
+    click(&mut gui, &mut background, &mut dialogs);
+    assert!(background.clicked());
+
+    // The open dialog takes the focus and blocks the mouse.
+    dialogs.0.open();
+    frame(&mut gui, &mut background, &mut dialogs, &[]);
+    frame(&mut gui, &mut background, &mut dialogs, &[]);
+    assert!(gui.focus.is_focused(dialogs.0.content.fields[0].id()));
+    click(&mut gui, &mut background, &mut dialogs);
+    assert!(!background.clicked());
+
+    // Tab cycles through the fields of the dialog only.
+    tap(&mut gui, &mut background, &mut dialogs, &[Key::Tab]);
+    tap(&mut gui, &mut background, &mut dialogs, &[Key::Tab]);
+    assert!(gui.focus.is_focused(dialogs.0.content.fields[2].id()));
+    tap(&mut gui, &mut background, &mut dialogs, &[Key::Tab]);
+    assert!(gui.focus.is_focused(dialogs.0.content.fields[0].id()));
+    tap(&mut gui, &mut background, &mut dialogs, &[Key::LShift, Key::Tab]);
+    assert!(gui.focus.is_focused(dialogs.0.content.fields[2].id()));
+
+    let first = gui.layout.prev_value_rect(Rect::from(dialogs.0.frame_id));
+    assert_eq!((first.left + first.right, first.top + first.bottom), (200.0, 200.0));
+
+    // A stacked dialog takes over the keyboard, Escape closes only the topmost one.
+    dialogs.1.open();
+    frame(&mut gui, &mut background, &mut dialogs, &[]);
+    frame(&mut gui, &mut background, &mut dialogs, &[]);
+    assert!(gui.focus.is_focused(dialogs.1.content.fields[0].id()));
+    tap(&mut gui, &mut background, &mut dialogs, &[Key::Escape]);
+    assert_eq!(dialogs.1.take_result(), Some(DialogResult::Cancelled));
+    assert!(dialogs.0.is_open());
+
+    frame(&mut gui, &mut background, &mut dialogs, &[]);
+    tap(&mut gui, &mut background, &mut dialogs, &[Key::Return]);
+    assert_eq!(dialogs.0.take_result(), Some(DialogResult::Accepted(3)));
+    assert!(!dialogs.0.is_open());
+
+    dialogs.0.open();
+    dialogs.0.content.result = Some(DialogResult::Accepted(7));
+    frame(&mut gui, &mut background, &mut dialogs, &[]);
+    assert_eq!(dialogs.0.take_result(), Some(DialogResult::Accepted(7)));
+    assert_eq!(dialogs.0.take_result(), None);
+
+    // Enter doesn't replace the result chosen by the content in the same frame.
+    dialogs.0.open();
+    frame(&mut gui, &mut background, &mut dialogs, &[]);
+    dialogs.0.content.result = Some(DialogResult::Cancelled);
+    tap(&mut gui, &mut background, &mut dialogs, &[Key::Return]);
+    assert_eq!(dialogs.0.take_result(), Some(DialogResult::Cancelled));
+
+    // Standard dialogs accept with Enter.
+    let mut prompt = Dialog::prompt("Rename", "New name:", "old");
+    prompt.open();
+
+    let events = [vec![], vec![], vec![key(Key::End, true), Event::Char('!')], vec![key(Key::End, false)], vec![key(Key::Return, true)]];
+    for events in &events {
+        for event in events {
+            gui.input.event(event);
+        }
+        prompt.appear(&mut gui);
+        gui.advance();
+    }
+
+    assert_eq!(prompt.take_result(), Some(DialogResult::Accepted("old!".to_owned())));
+}
+
+#[test]
+fn test_alert_takes_keyboard() {
+    use Theme;
+    use gui::input::{Event, KeyEvent, ScanCode};
+
+    let mut gui = Gui::new(Theme::empty());
+    let mut input = TextInput::new();
+    let mut alert = Dialog::alert("Error", "Something went wrong.");
+
+    let frame = |gui: &mut Gui, input: &mut TextInput, alert: &mut Dialog<Alert>, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+        input.appear(gui);
+        alert.appear(gui);
+        gui.advance();
+    };
+
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+
+    gui.focus(input.id());
+    frame(&mut gui, &mut input, &mut alert, &[Event::Char('a')]);
+    assert_eq!(input.text(), "a");
+
+    // Typing while the alert is open doesn't reach the field under it.
+    alert.open();
+    frame(&mut gui, &mut input, &mut alert, &[]);
+    frame(&mut gui, &mut input, &mut alert, &[]);
+    assert!(gui.focus.is_focused(alert.content.ok.id()));
+    frame(&mut gui, &mut input, &mut alert, &[Event::Char('b')]);
+    assert_eq!(input.text(), "a");
+
+    // The focused OK button is pressed with Space.
+    frame(&mut gui, &mut input, &mut alert, &[key(Key::Space, true), Event::Char(' ')]);
+    assert_eq!(alert.take_result(), Some(DialogResult::Accepted(())));
+    assert_eq!(input.text(), "a");
+}
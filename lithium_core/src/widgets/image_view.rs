@@ -0,0 +1,164 @@
+use std::sync::Arc;
+use {Id, Gui, Rect, Var, Vec2};
+use image::Image;
+use gui::scene::{self, Sampling};
+use solver::Strength;
+use super::Widget;
+
+/// Shows an image scaled into the place of the widget according to `fit`.
+///
+/// Image size in pixels is the preferred size of the widget. Pixels changed through `image_mut` are uploaded again by
+/// the renderer, only the changed regions if the image is not shared.
+pub struct ImageView {
+    id: Id,
+    image: Arc<Image>,
+    pub fit: ObjectFit,
+    pub sampling: Sampling,
+}
+
+/// How the image is scaled into the place of the widget, like the `object-fit` CSS property.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum ObjectFit {
+    /// Scale keeping the aspect ratio so that the whole image is visible.
+    Contain,
+    /// Scale keeping the aspect ratio so that the whole place is covered, the image is cropped.
+    Cover,
+    /// Stretch the image to the place.
+    Fill,
+    /// Keep the image size, it is centered and cropped.
+    None,
+}
+
+impl Default for ObjectFit {
+    fn default() -> Self {
+        ObjectFit::Contain
+    }
+}
+
+impl ObjectFit {
+    /// Place of an image of `size` shown in `frame`. It is centered inside of the frame unless it is stretched.
+    pub fn place(self, frame: Rect<f64>, size: Vec2<f64>) -> Rect<f64> {
+        if self == ObjectFit::Fill || size.x <= 0.0 || size.y <= 0.0 {
+            return frame;
+        }
+
+        let scale_x = frame.width()/size.x;
+        let scale_y = frame.height()/size.y;
+
+        let scale = match self {
+            ObjectFit::Contain => scale_x.min(scale_y),
+            ObjectFit::Cover => scale_x.max(scale_y),
+            ObjectFit::Fill | ObjectFit::None => 1.0,
+        };
+
+        Rect::from_center_and_half_size(frame.center(), size*(scale/2.0))
+    }
+}
+
+impl Widget for ImageView {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let size = self.image.size();
+        let size = Vec2::new(size.x as f64, size.y as f64);
+
+        let frame = gui.layout.prev_value_rect(place);
+        let image = scene::Image {
+            id: self.id,
+            image: self.image.clone(),
+            place: self.fit.place(frame, size),
+            clip: frame,
+            sampling: self.sampling,
+        };
+
+        gui.element(self.id, element_kind!("ImageView"), |gui| {
+            gui.scene.image(image);
+        });
+
+        gui.layout.with_strength(Strength::Weak, |layout| {
+            add_constraints!(layout, [
+                (place.right - place.left) == size.x,
+                (place.bottom - place.top) == size.y,
+            ]);
+        });
+
+        place
+    }
+}
+
+impl ImageView {
+    pub fn new(image: Arc<Image>) -> Self {
+        ImageView {
+            id: Id::unique(),
+            image,
+            fit: ObjectFit::default(),
+            sampling: Sampling::default(),
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn image(&self) -> &Arc<Image> {
+        &self.image
+    }
+
+    /// The image is cloned first if it is shared with something else.
+    pub fn image_mut(&mut self) -> &mut Image {
+        Arc::make_mut(&mut self.image)
+    }
+
+    pub fn set_image(&mut self, image: Arc<Image>) {
+        self.image = image;
+    }
+}
+
+#[test]
+fn test_image_view() {
+    use {Color, Theme};
+    use gui::scene::Command;
+
+    let image = Arc::new(Image::new(Vec2::new(40, 20), vec![Color::black(); 800]));
+    let mut view = ImageView::new(image);
+    let mut gui = Gui::new(Theme::empty());
+
+    // Returns the place of the image drawn during the frame.
+    let frame = |gui: &mut Gui, view: &mut ImageView, width: Option<f64>| {
+        let place = view.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+        ]);
+
+        if let Some(width) = width {
+            add_constraints!(gui.layout, [
+                (place.right) == width,
+                (place.bottom) == 100.0,
+            ]);
+        }
+
+        let drawn = gui.scene.commands().iter().filter_map(|command| match *command {
+            Command::Image(ref image) => Some(image.place),
+            _ => None,
+        }).next();
+
+        gui.advance();
+        drawn.unwrap()
+    };
+
+    // Without other constraints the widget takes the size of the image.
+    frame(&mut gui, &mut view, None);
+    assert_eq!(frame(&mut gui, &mut view, None), Rect { left: 0.0, top: 0.0, right: 40.0, bottom: 20.0 });
+
+    frame(&mut gui, &mut view, Some(100.0));
+    assert_eq!(frame(&mut gui, &mut view, Some(100.0)), Rect { left: 0.0, top: 25.0, right: 100.0, bottom: 75.0 });
+
+    view.fit = ObjectFit::Cover;
+    assert_eq!(frame(&mut gui, &mut view, Some(100.0)), Rect { left: -50.0, top: 0.0, right: 150.0, bottom: 100.0 });
+
+    view.fit = ObjectFit::Fill;
+    assert_eq!(frame(&mut gui, &mut view, Some(100.0)), Rect { left: 0.0, top: 0.0, right: 100.0, bottom: 100.0 });
+
+    view.fit = ObjectFit::None;
+    assert_eq!(frame(&mut gui, &mut view, Some(100.0)), Rect { left: 30.0, top: 40.0, right: 70.0, bottom: 60.0 });
+}
@@ -0,0 +1,142 @@
+use std::rc::Rc;
+use std::sync::Arc;
+use std::cell::RefCell;
+use {Id, Gui, Rect, Var, Vec2, Font};
+use font::Segment;
+use gui::scene::{Text, Glyph};
+use super::Widget;
+
+/// Single line of text.
+///
+/// Text is shaped with the font of the `Label` element style. Shaping is repeated only when the text or the font changes.
+pub struct Label {
+    id: Id,
+    text: String,
+    shaped: Option<Shaped>,
+}
+
+struct Shaped {
+    font: Arc<Font>,
+    glyphs: Rc<RefCell<Vec<Glyph>>>,
+    size: Vec2<f64>,
+}
+
+impl Widget for Label {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let kind = element_kind!("Label");
+        let font = gui.scene.element_style(kind).font.clone();
+
+        let reshape = match self.shaped {
+            Some(ref shaped) => !Arc::ptr_eq(&shaped.font, &font),
+            None => true,
+        };
+
+        if reshape {
+            self.shaped = Some(shape(&self.text, font));
+        }
+
+        let (glyphs, size) = match self.shaped {
+            Some(ref shaped) => (shaped.glyphs.clone(), shaped.size),
+            None => unreachable!(),
+        };
+
+        gui.element(self.id, kind, |gui| {
+            gui.scene.text(Text {
+                id: self.id,
+                glyphs: glyphs,
+            });
+        });
+
+        add_constraints!(gui.layout, [
+            (place.right - place.left) == size.x,
+            (place.bottom - place.top) == size.y,
+        ]);
+
+        place
+    }
+}
+
+impl Label {
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        Label {
+            id: Id::unique(),
+            text: text.into(),
+            shaped: None,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text<S: Into<String>>(&mut self, text: S) {
+        let text = text.into();
+
+        if text != self.text {
+            self.text = text;
+            self.shaped = None;
+        }
+    }
+
+    /// Size of the text measured during the last appearance.
+    pub fn size(&self) -> Vec2<f64> {
+        self.shaped.as_ref().map_or(Vec2::new(0.0, 0.0), |shaped| shaped.size)
+    }
+}
+
+fn shape(text: &str, font: Arc<Font>) -> Shaped {
+    let mut segments: Vec<Segment> = Vec::new();
+    font.shape(text, &mut segments);
+
+    let glyphs = segments.iter().map(|segment| Glyph {
+        position: segment.position,
+        scale: Vec2::new(1.0, 1.0),
+        glyph_id: segment.glyph_index,
+    }).collect();
+
+    let width = segments.iter().fold(0.0, |width: f64, segment| width.max(segment.position_end));
+
+    Shaped {
+        size: Vec2::new(width, font.line_height()),
+        font: font,
+        glyphs: Rc::new(RefCell::new(glyphs)),
+    }
+}
+
+#[test]
+fn test_label_shaping() {
+    use std::sync::atomic::Ordering;
+    use Theme;
+    use theme::ElementStyle;
+    use super::text::MonospaceFont;
+
+    let font = Arc::new(MonospaceFont::default());
+    let mut theme = Theme::empty();
+    theme.element_styles.insert((Default::default(), element_kind!("Label")), Arc::new(ElementStyle {
+        font: font.clone(),
+        ..ElementStyle::default()
+    }));
+
+    let mut gui = Gui::new(theme);
+    let mut label = Label::new("abc");
+
+    let frame = |gui: &mut Gui, label: &mut Label| {
+        label.appear(gui);
+        gui.advance();
+    };
+
+    frame(&mut gui, &mut label);
+    frame(&mut gui, &mut label);
+    assert_eq!(font.shaped.load(Ordering::SeqCst), 1);
+    assert_eq!(label.size(), Vec2::new(30.0, 20.0));
+
+    label.set_text("abc");
+    frame(&mut gui, &mut label);
+    assert_eq!(font.shaped.load(Ordering::SeqCst), 1);
+
+    label.set_text("abcd");
+    frame(&mut gui, &mut label);
+    assert_eq!(font.shaped.load(Ordering::SeqCst), 2);
+    assert_eq!(label.size(), Vec2::new(40.0, 20.0));
+}
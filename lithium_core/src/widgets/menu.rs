@@ -0,0 +1,1076 @@
+use {Id, Gui, Rect, Var, Vec2};
+use gui::input::{ButtonState, Key, Direction};
+use solver::Strength;
+use theme::PseudoClass;
+use layout;
+use super::{Widget, ClickArea, Label, Popup, Placement};
+
+/// Height of a separator between menu items.
+const SEPARATOR_HEIGHT: f64 = 9.0;
+
+/// Minimal space between the label of a menu item and its accelerator.
+const ACCELERATOR_GAP: f64 = 24.0;
+
+/// Definition of a menu which can be shown by a `MenuBar` or a `ContextMenu`.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Menu {
+    pub items: Vec<MenuItem>,
+}
+
+/// Item of a `Menu`.
+///
+/// The label marks its mnemonic with `&` before the letter, like `&File`, and `&&` stands for `&` itself. While the menu
+/// is open typing the mnemonic activates the item, in a menu bar Alt with the mnemonic opens the menu.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MenuItem {
+    pub label: String,
+    pub kind: MenuItemKind,
+
+    /// Reported by `activated` of the menu widget after the item is activated.
+    pub command: u64,
+    pub enabled: bool,
+
+    /// Keyboard shortcut shown next to the label, like `Ctrl+S`. Handling the shortcut is up to the application.
+    pub accelerator: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum MenuItemKind {
+    Action,
+    Check { checked: bool },
+
+    /// Activating the item checks it and unchecks the other items of the same menu in the same `group`.
+    Radio { group: u32, checked: bool },
+    Submenu(Menu),
+    Separator,
+}
+
+/// Menu bar showing the submenu items of the `menu` as menus opening below it.
+///
+/// The bar is the `MenuBar` element with a `MenuBarItem` for each item, the item whose menu is open has the `open`
+/// pseudo-class. Menus are shown in popups as `Menu` elements containing a `MenuItem` or a `MenuSeparator` for each
+/// item. Menu items are in the `highlighted`, `disabled` and `checked` states, check and radio items contain a
+/// `MenuItemCheck` or a `MenuItemRadio` indicator and submenu items contain a `MenuItemArrow`.
+///
+/// While a menu is open Up and Down move the highlight, Right and Left open and close submenus or switch to the
+/// neighbouring menus of the bar, Enter activates the highlighted item and Escape closes the innermost menu.
+pub struct MenuBar {
+    id: Id,
+    items: Vec<BarItem>,
+
+    /// Index of the item whose menu is open.
+    open: Option<usize>,
+    popups: MenuPopups,
+    activated: Option<u64>,
+
+    pub menu: Menu,
+}
+
+/// Menu opened by clicking the `target` with the secondary mouse button at the pointer.
+///
+/// Menus look and behave the same as the menus of a `MenuBar`.
+pub struct ContextMenu<T: Widget> {
+    anchor_id: Id,
+    position: Vec2<f64>,
+    popups: MenuPopups,
+    activated: Option<u64>,
+
+    pub target: T,
+    pub menu: Menu,
+}
+
+struct BarItem {
+    id: Id,
+    label: Label,
+}
+
+/// Open levels of a menu from the outermost, each one is shown in a popup.
+struct MenuPopups {
+    levels: Vec<Level>,
+}
+
+struct Level {
+    /// Index of the item in the outer level whose submenu this is.
+    parent: usize,
+    popup: Popup<MenuList>,
+}
+
+/// Items of a single level of a menu.
+struct MenuList {
+    id: Id,
+    rows: Vec<MenuRow>,
+    highlighted: Option<usize>,
+
+    /// Row clicked and row entered by the pointer during the last appearance.
+    clicked: Option<usize>,
+    entered: Option<usize>,
+}
+
+struct MenuRow {
+    id: Id,
+    indicator_id: Id,
+    arrow_id: Id,
+    click_area: ClickArea,
+    label: Label,
+    accelerator: Option<Label>,
+    kind: RowKind,
+    enabled: bool,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum RowKind {
+    Action,
+    Check(bool),
+    Radio(bool),
+    Submenu,
+    Separator,
+}
+
+enum MenuEvent {
+    Activated(u64),
+
+    /// Keyboard asks for the previous or the next menu of the bar.
+    Previous,
+    Next,
+}
+
+impl Widget for MenuBar {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let place_value = gui.layout.prev_value_rect(place);
+
+        self.sync_items();
+
+        if !self.popups.is_open() {
+            self.open = None;
+        }
+
+        let item_at = |gui: &Gui, items: &[BarItem], position| {
+            items.iter().position(|item| gui.layout.prev_value_rect(Rect::from(item.id)).contains(position))
+        };
+
+        if let Some(mouse) = gui.input.get_mouse(|pos| place_value.contains(pos)) {
+            let pointed = item_at(gui, &self.items, mouse.position);
+
+            if let (ButtonState::JustPressed, Some(pointed)) = (mouse.primary_button, pointed) {
+                if self.open == Some(pointed) {
+                    self.close();
+                } else {
+                    self.open_item(pointed, false);
+                }
+            } else if let (true, Some(pointed)) = (self.open.is_some(), pointed) {
+                // While a menu is open pointing at another item of the bar switches to its menu.
+                if self.open != Some(pointed) {
+                    self.open_item(pointed, false);
+                }
+            }
+        }
+
+        self.handle_alt_mnemonics(gui);
+
+        let open = self.open;
+        let items = &mut self.items;
+        gui.element(self.id, element_kind!("MenuBar"), |gui| {
+            let mut previous: Option<Rect<Var>> = None;
+
+            for (i, item) in items.iter_mut().enumerate() {
+                let item_place = Rect::from(item.id);
+                let pseudo_classes = if open == Some(i) { vec![pseudo_class!("open")] } else { vec![] };
+                let label = &mut item.label;
+
+                gui.element_in_state(item.id, element_kind!("MenuBarItem"), &pseudo_classes, |gui| {
+                    let label_place = label.appear(gui);
+                    layout::center(gui, item_place, label_place);
+                    add_constraints!(gui.layout, [
+                        (item_place.bottom - item_place.top) == label_place.bottom - label_place.top,
+                        (item_place.right - item_place.left) == label_place.right - label_place.left,
+                    ]);
+                });
+
+                match previous {
+                    Some(previous) => { add_constraints!(gui.layout, [(item_place.left) == previous.right]); }
+                    None => { add_constraints!(gui.layout, [(item_place.left) == place.left]); }
+                }
+                add_constraints!(gui.layout, [
+                    (item_place.top) == place.top,
+                    (place.bottom) == item_place.bottom,
+                ]);
+                previous = Some(item_place);
+            }
+        });
+
+        if let Some(open) = self.open {
+            let anchor = Rect::from(self.items[open].id);
+
+            let event = match self.menu.items[open].kind {
+                MenuItemKind::Submenu(ref mut submenu) => self.popups.appear(gui, submenu, anchor, Placement::Below),
+                _ => None,
+            };
+
+            match event {
+                Some(MenuEvent::Activated(command)) => {
+                    self.activated = Some(command);
+                    self.open = None;
+                }
+                Some(MenuEvent::Previous) => self.open_neighbour(open, false),
+                Some(MenuEvent::Next) => self.open_neighbour(open, true),
+                None => {}
+            }
+        }
+
+        place
+    }
+}
+
+impl<T: Widget> Widget for ContextMenu<T> {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let target_place = self.target.appear(gui);
+        let target_value = gui.layout.prev_value_rect(target_place);
+
+        if let Some(mouse) = gui.input.peek_mouse() {
+            let position = mouse.position;
+            if mouse.secondary_button == ButtonState::JustPressed && target_value.contains(position) && gui.input.reaches(position) {
+                self.position = position;
+                self.popups.open(&self.menu, false);
+            }
+        }
+
+        if self.popups.is_open() {
+            let anchor = Rect::from(self.anchor_id);
+            let position = self.position;
+
+            add_constraints!(gui.layout, [
+                (anchor.left) == position.x,
+                (anchor.right) == position.x,
+                (anchor.top) == position.y,
+                (anchor.bottom) == position.y,
+            ]);
+
+            if let Some(MenuEvent::Activated(command)) = self.popups.appear(gui, &mut self.menu, anchor, Placement::Below) {
+                self.activated = Some(command);
+            }
+        }
+
+        target_place
+    }
+}
+
+impl Widget for MenuList {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let highlighted = self.highlighted;
+        let rows = &mut self.rows;
+        let mut clicked = None;
+        let mut entered = None;
+
+        gui.element(self.id, element_kind!("Menu"), |gui| {
+            let mut previous: Option<Rect<Var>> = None;
+
+            for (i, row) in rows.iter_mut().enumerate() {
+                let row_place = row.appear(gui, highlighted == Some(i));
+
+                if row.enabled && row.kind != RowKind::Separator {
+                    if row.click_area.clicked() {
+                        clicked = Some(i);
+                    }
+                    if gui.hover_entered(row.id) {
+                        entered = Some(i);
+                    }
+                }
+
+                let top = previous.map(|previous| previous.bottom);
+                match top {
+                    Some(top) => { add_constraints!(gui.layout, [(row_place.top) == top]); }
+                    None => { add_constraints!(gui.layout, [(row_place.top) == place.top]); }
+                }
+                layout::equal_vertical(gui, place, row_place);
+                previous = Some(row_place);
+            }
+
+            match previous {
+                Some(last) => { add_constraints!(gui.layout, [(place.bottom) == last.bottom]); }
+                None => { add_constraints!(gui.layout, [(place.bottom) == place.top]); }
+            }
+
+            // The menu is as narrow as its items allow.
+            gui.layout.with_strength(Strength::Weak, |layout| {
+                add_constraints!(layout, [(place.right - place.left) == 0.0]);
+            });
+        });
+
+        self.clicked = clicked;
+        self.entered = entered;
+
+        place
+    }
+}
+
+impl MenuRow {
+    fn appear(&mut self, gui: &mut Gui, highlighted: bool) -> Rect<Var> {
+        let place = Rect::from(self.id);
+
+        if self.kind == RowKind::Separator {
+            gui.element(self.id, element_kind!("MenuSeparator"), |_gui| {});
+            add_constraints!(gui.layout, [(place.bottom - place.top) == SEPARATOR_HEIGHT]);
+            return place;
+        }
+
+        let mut pseudo_classes: Vec<PseudoClass> = Vec::new();
+        if highlighted {
+            pseudo_classes.push(pseudo_class!("highlighted"));
+        }
+        if !self.enabled {
+            pseudo_classes.push(pseudo_class!("disabled"));
+        }
+        if let RowKind::Check(true) | RowKind::Radio(true) = self.kind {
+            pseudo_classes.push(pseudo_class!("checked"));
+        }
+
+        let (indicator, arrow) = (Rect::from(self.indicator_id), Rect::from(self.arrow_id));
+        let (indicator_id, arrow_id, kind) = (self.indicator_id, self.arrow_id, self.kind);
+        let click_area = &mut self.click_area;
+        let label = &mut self.label;
+        let accelerator = &mut self.accelerator;
+
+        gui.element_in_state(self.id, element_kind!("MenuItem"), &pseudo_classes, |gui| {
+            let click_area_place = click_area.appear(gui);
+            layout::equal(gui, place, click_area_place);
+
+            match kind {
+                RowKind::Check(_) => gui.element_in_state(indicator_id, element_kind!("MenuItemCheck"), &pseudo_classes, |_gui| {}),
+                RowKind::Radio(_) => gui.element_in_state(indicator_id, element_kind!("MenuItemRadio"), &pseudo_classes, |_gui| {}),
+                RowKind::Submenu => gui.element_in_state(arrow_id, element_kind!("MenuItemArrow"), &pseudo_classes, |_gui| {}),
+                RowKind::Action | RowKind::Separator => {}
+            }
+
+            let label_place = label.appear(gui);
+
+            // Labels of all items are aligned after the space for the check and radio indicators.
+            add_constraints!(gui.layout, [
+                (indicator.left) == place.left,
+                (indicator.right - indicator.left) == indicator.bottom - indicator.top,
+                (arrow.right) == place.right,
+                (arrow.right - arrow.left) == arrow.bottom - arrow.top,
+                (label_place.left) == indicator.right,
+                (arrow.left) >= label_place.right,
+                (place.bottom - place.top) == label_place.bottom - label_place.top,
+            ]);
+            layout::equal_horizontal(gui, place, indicator);
+            layout::equal_horizontal(gui, place, arrow);
+            layout::center_horizontal(gui, place, label_place);
+
+            if let Some(ref mut accelerator) = *accelerator {
+                let accelerator_place = accelerator.appear(gui);
+                add_constraints!(gui.layout, [
+                    (accelerator_place.right) == arrow.left,
+                    (accelerator_place.left) >= label_place.right + ACCELERATOR_GAP,
+                ]);
+                layout::center_horizontal(gui, place, accelerator_place);
+            }
+        });
+
+        place
+    }
+}
+
+impl MenuPopups {
+    fn new() -> Self {
+        MenuPopups {
+            levels: Vec::new(),
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        !self.levels.is_empty()
+    }
+
+    /// Open the outermost level of the `menu`, with `highlight_first` its first item is highlighted like after opening
+    /// it with the keyboard.
+    fn open(&mut self, menu: &Menu, highlight_first: bool) {
+        self.levels.clear();
+        self.levels.push(Level::new(0, menu, highlight_first));
+    }
+
+    fn close(&mut self) {
+        self.levels.clear();
+    }
+
+    /// Show the open levels of the `menu`, the outermost one at the `placement` side of the `anchor`.
+    fn appear(&mut self, gui: &mut Gui, menu: &mut Menu, anchor: Rect<Var>, placement: Placement) -> Option<MenuEvent> {
+        if self.levels.is_empty() {
+            return None;
+        }
+
+        let mut event = self.handle_keyboard(gui, menu);
+
+        let mut k = 0;
+        while k < self.levels.len() {
+            let anchor = if k == 0 {
+                anchor
+            } else {
+                Rect::from(self.levels[k - 1].popup.content.rows[self.levels[k].parent].id)
+            };
+
+            let path = self.path(k);
+            let level_menu = match menu_at(menu, &path) {
+                Some(level_menu) => level_menu,
+                None => {
+                    // The menu definition changed and there is no such submenu anymore.
+                    self.levels.truncate(k);
+                    break;
+                }
+            };
+
+            let (clicked, entered) = {
+                let level = &mut self.levels[k];
+                level.popup.placement = if k == 0 { placement } else { Placement::Right };
+                level.popup.content.sync(level_menu);
+                level.popup.appear(gui, anchor);
+
+                if level.popup.closed() {
+                    self.levels.truncate(k);
+                    break;
+                }
+
+                let list = &mut level.popup.content;
+                (list.clicked.take(), list.entered.take())
+            };
+
+            if let Some(entered) = entered {
+                self.levels[k].popup.content.highlighted = Some(entered);
+                self.levels.truncate(k + 1);
+
+                if let MenuItemKind::Submenu(ref submenu) = level_menu.items[entered].kind {
+                    self.levels.push(Level::new(entered, submenu, false));
+                }
+            }
+
+            if let Some(clicked) = clicked {
+                if let Some(activated) = self.activate(level_menu, k, clicked, false) {
+                    event = Some(activated);
+                }
+            }
+
+            k += 1;
+        }
+
+        event
+    }
+
+    /// Indices of the items whose submenus lead to the level `k`.
+    fn path(&self, k: usize) -> Vec<usize> {
+        self.levels[1..k + 1].iter().map(|level| level.parent).collect()
+    }
+
+    /// Activate the item at the `index` of the `menu` shown at the level `k`.
+    fn activate(&mut self, menu: &mut Menu, k: usize, index: usize, by_keyboard: bool) -> Option<MenuEvent> {
+        if !menu.items[index].enabled {
+            return None;
+        }
+
+        let command = menu.items[index].command;
+
+        let group = match menu.items[index].kind {
+            MenuItemKind::Submenu(ref submenu) => {
+                self.levels.truncate(k + 1);
+                self.levels[k].popup.content.highlighted = Some(index);
+                self.levels.push(Level::new(index, submenu, by_keyboard));
+                return None;
+            }
+            MenuItemKind::Separator => return None,
+            MenuItemKind::Check { ref mut checked } => {
+                *checked = !*checked;
+                None
+            }
+            MenuItemKind::Radio { group, .. } => Some(group),
+            MenuItemKind::Action => None,
+        };
+
+        if let Some(group) = group {
+            for (i, item) in menu.items.iter_mut().enumerate() {
+                if let MenuItemKind::Radio { group: item_group, ref mut checked } = item.kind {
+                    if item_group == group {
+                        *checked = i == index;
+                    }
+                }
+            }
+        }
+
+        self.close();
+        Some(MenuEvent::Activated(command))
+    }
+
+    /// Keyboard controls the innermost open level.
+    fn handle_keyboard(&mut self, gui: &mut Gui, menu: &mut Menu) -> Option<MenuEvent> {
+        for &direction in &[Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+            gui.input.take_navigation(direction);
+        }
+
+        let k = self.levels.len() - 1;
+        let path = self.path(k);
+        let menu = menu_at(menu, &path)?;
+
+        let keyboard = &gui.input.keyboard;
+        let highlighted = self.levels[k].popup.content.highlighted;
+
+        let (down, up) = (keyboard.presses(Key::Down), keyboard.presses(Key::Up));
+        if down + up > 0 {
+            let mut next = highlighted;
+            for _ in 0..down {
+                next = next_selectable(menu, next, true);
+            }
+            for _ in 0..up {
+                next = next_selectable(menu, next, false);
+            }
+            self.levels[k].popup.content.highlighted = next;
+            return None;
+        }
+
+        if keyboard.presses(Key::Right) > 0 {
+            return match highlighted {
+                Some(index) if menu.items[index].inner_menu().is_some() => self.activate(menu, k, index, true),
+                _ => Some(MenuEvent::Next),
+            };
+        }
+
+        if keyboard.presses(Key::Left) > 0 {
+            if k > 0 {
+                self.levels.pop();
+                return None;
+            }
+            return Some(MenuEvent::Previous);
+        }
+
+        let enter = keyboard.presses(Key::Return) + keyboard.presses(Key::NumpadEnter) + keyboard.presses(Key::Space) > 0;
+        if let (true, Some(index)) = (enter, highlighted) {
+            return self.activate(menu, k, index, true);
+        }
+
+        let typed = keyboard.input().chars().flat_map(char::to_lowercase).find_map(|typed| {
+            menu.items.iter().position(|item| item.is_selectable() && item.mnemonic() == Some(typed))
+        });
+
+        if let Some(index) = typed {
+            self.levels[k].popup.content.highlighted = Some(index);
+            return self.activate(menu, k, index, true);
+        }
+
+        None
+    }
+}
+
+impl Level {
+    fn new(parent: usize, menu: &Menu, highlight_first: bool) -> Self {
+        let mut list = MenuList {
+            id: Id::unique(),
+            rows: Vec::new(),
+            highlighted: None,
+            clicked: None,
+            entered: None,
+        };
+
+        if highlight_first {
+            list.highlighted = next_selectable(menu, None, true);
+        }
+
+        let mut popup = Popup::new(list);
+        popup.open();
+
+        Level {
+            parent,
+            popup,
+        }
+    }
+}
+
+impl MenuList {
+    /// Update the rows to show the items of the `menu`.
+    fn sync(&mut self, menu: &Menu) {
+        self.rows.truncate(menu.items.len());
+
+        while self.rows.len() < menu.items.len() {
+            self.rows.push(MenuRow {
+                id: Id::unique(),
+                indicator_id: Id::unique(),
+                arrow_id: Id::unique(),
+                click_area: ClickArea::new(),
+                label: Label::new(""),
+                accelerator: None,
+                kind: RowKind::Action,
+                enabled: true,
+            });
+        }
+
+        for (row, item) in self.rows.iter_mut().zip(&menu.items) {
+            row.label.set_text(item.text());
+            row.enabled = item.enabled;
+            row.kind = match item.kind {
+                MenuItemKind::Action => RowKind::Action,
+                MenuItemKind::Check { checked } => RowKind::Check(checked),
+                MenuItemKind::Radio { checked, .. } => RowKind::Radio(checked),
+                MenuItemKind::Submenu(_) => RowKind::Submenu,
+                MenuItemKind::Separator => RowKind::Separator,
+            };
+
+            match (row.accelerator.as_mut(), item.accelerator.as_ref()) {
+                (Some(label), Some(text)) => label.set_text(text.as_str()),
+                (_, text) => row.accelerator = text.map(|text| Label::new(text.as_str())),
+            }
+        }
+
+        if self.highlighted.map(|highlighted| highlighted >= self.rows.len()) == Some(true) {
+            self.highlighted = None;
+        }
+    }
+}
+
+impl MenuBar {
+    pub fn new(menu: Menu) -> Self {
+        MenuBar {
+            id: Id::unique(),
+            items: Vec::new(),
+
+            open: None,
+            popups: MenuPopups::new(),
+            activated: None,
+
+            menu,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Index of the item of the bar whose menu is open.
+    pub fn open_menu(&self) -> Option<usize> {
+        self.open
+    }
+
+    pub fn close(&mut self) {
+        self.open = None;
+        self.popups.close();
+    }
+
+    /// Command of the item activated by the user since the last call.
+    pub fn activated(&mut self) -> Option<u64> {
+        self.activated.take()
+    }
+
+    fn sync_items(&mut self) {
+        self.items.truncate(self.menu.items.len());
+
+        while self.items.len() < self.menu.items.len() {
+            self.items.push(BarItem {
+                id: Id::unique(),
+                label: Label::new(""),
+            });
+        }
+
+        for (bar_item, item) in self.items.iter_mut().zip(&self.menu.items) {
+            bar_item.label.set_text(item.text());
+        }
+
+        if self.open.map(|open| open >= self.items.len()) == Some(true) {
+            self.close();
+        }
+    }
+
+    /// Open the menu of the item at the `index` or activate the item if it has no menu.
+    fn open_item(&mut self, index: usize, highlight_first: bool) {
+        let item = &mut self.menu.items[index];
+
+        if !item.enabled {
+            return;
+        }
+
+        match item.kind {
+            MenuItemKind::Submenu(ref submenu) => {
+                self.open = Some(index);
+                self.popups.open(submenu, highlight_first);
+            }
+            MenuItemKind::Check { ref mut checked } => {
+                *checked = !*checked;
+                self.activated = Some(item.command);
+            }
+            MenuItemKind::Action | MenuItemKind::Radio { .. } => self.activated = Some(item.command),
+            MenuItemKind::Separator => {}
+        }
+    }
+
+    /// Switch from the menu of the item at `from` to the closest item with a menu after or before it.
+    fn open_neighbour(&mut self, from: usize, forward: bool) {
+        let len = self.menu.items.len();
+
+        for step in 1..len {
+            let index = if forward { (from + step) % len } else { (from + len - step) % len };
+            let item = &self.menu.items[index];
+
+            if item.enabled && item.inner_menu().is_some() {
+                self.open_item(index, true);
+                return;
+            }
+        }
+    }
+
+    /// Alt with the mnemonic of an item opens its menu.
+    fn handle_alt_mnemonics(&mut self, gui: &Gui) {
+        let keyboard = &gui.input.keyboard;
+        if !keyboard.is_pressed(Key::LAlt) && !keyboard.is_pressed(Key::RAlt) {
+            return;
+        }
+
+        let pressed = self.menu.items.iter().position(|item| {
+            item.mnemonic().and_then(letter_key).map(|key| keyboard.key_state(key)) == Some(ButtonState::JustPressed)
+        });
+
+        if let Some(pressed) = pressed {
+            self.open_item(pressed, true);
+        }
+    }
+}
+
+impl<T: Widget> ContextMenu<T> {
+    pub fn new(target: T, menu: Menu) -> Self {
+        ContextMenu {
+            anchor_id: Id::unique(),
+            position: Vec2::zero(),
+            popups: MenuPopups::new(),
+            activated: None,
+
+            target,
+            menu,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.popups.is_open()
+    }
+
+    /// Open the menu at the `position` as if the target was clicked there.
+    pub fn open(&mut self, position: Vec2<f64>) {
+        self.position = position;
+        self.popups.open(&self.menu, false);
+    }
+
+    pub fn close(&mut self) {
+        self.popups.close();
+    }
+
+    /// Command of the item activated by the user since the last call.
+    pub fn activated(&mut self) -> Option<u64> {
+        self.activated.take()
+    }
+}
+
+impl Menu {
+    pub fn new(items: Vec<MenuItem>) -> Self {
+        Menu {
+            items,
+        }
+    }
+}
+
+impl MenuItem {
+    pub fn action<S: Into<String>>(label: S, command: u64) -> Self {
+        MenuItem::new(label.into(), MenuItemKind::Action, command)
+    }
+
+    pub fn check<S: Into<String>>(label: S, command: u64, checked: bool) -> Self {
+        MenuItem::new(label.into(), MenuItemKind::Check { checked }, command)
+    }
+
+    pub fn radio<S: Into<String>>(label: S, command: u64, group: u32, checked: bool) -> Self {
+        MenuItem::new(label.into(), MenuItemKind::Radio { group, checked }, command)
+    }
+
+    pub fn submenu<S: Into<String>>(label: S, menu: Menu) -> Self {
+        MenuItem::new(label.into(), MenuItemKind::Submenu(menu), 0)
+    }
+
+    pub fn separator() -> Self {
+        MenuItem::new(String::new(), MenuItemKind::Separator, 0)
+    }
+
+    fn new(label: String, kind: MenuItemKind, command: u64) -> Self {
+        MenuItem {
+            label,
+            kind,
+            command,
+            enabled: true,
+            accelerator: None,
+        }
+    }
+
+    /// Label without the mnemonic markers.
+    pub fn text(&self) -> String {
+        let mut text = String::with_capacity(self.label.len());
+        let mut chars = self.label.chars();
+
+        while let Some(char) = chars.next() {
+            if char == '&' {
+                text.extend(chars.next());
+            } else {
+                text.push(char);
+            }
+        }
+
+        text
+    }
+
+    /// Lowercase letter marked with `&` in the label.
+    pub fn mnemonic(&self) -> Option<char> {
+        let mut chars = self.label.chars();
+
+        while let Some(char) = chars.next() {
+            if char == '&' {
+                match chars.next() {
+                    Some('&') | None => {}
+                    Some(mnemonic) => return mnemonic.to_lowercase().next(),
+                }
+            }
+        }
+
+        None
+    }
+
+    fn inner_menu(&self) -> Option<&Menu> {
+        match self.kind {
+            MenuItemKind::Submenu(ref submenu) => Some(submenu),
+            _ => None,
+        }
+    }
+
+    fn is_selectable(&self) -> bool {
+        self.enabled && self.kind != MenuItemKind::Separator
+    }
+}
+
+/// Submenu reached from the `menu` through the items at the `path`.
+fn menu_at<'a>(menu: &'a mut Menu, path: &[usize]) -> Option<&'a mut Menu> {
+    let mut menu = menu;
+
+    for &index in path {
+        let current = menu;
+        menu = match current.items.get_mut(index).map(|item| &mut item.kind) {
+            Some(&mut MenuItemKind::Submenu(ref mut submenu)) => submenu,
+            _ => return None,
+        };
+    }
+
+    Some(menu)
+}
+
+/// Next selectable item after the `from` one (or before it if not `forward`), wrapping around.
+fn next_selectable(menu: &Menu, from: Option<usize>, forward: bool) -> Option<usize> {
+    let len = menu.items.len();
+
+    for step in 1..len + 1 {
+        let index = match (from, forward) {
+            (Some(from), true) => (from + step) % len,
+            (Some(from), false) => (from + len - step % len) % len,
+            (None, true) => step - 1,
+            (None, false) => len - step,
+        };
+
+        if menu.items[index].is_selectable() {
+            return Some(index);
+        }
+    }
+
+    from
+}
+
+/// Key of an ASCII letter.
+fn letter_key(letter: char) -> Option<Key> {
+    const LETTERS: [Key; 26] = [
+        Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J, Key::K, Key::L, Key::M,
+        Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T, Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    ];
+
+    if letter.is_ascii_lowercase() {
+        Some(LETTERS[(letter as u8 - b'a') as usize])
+    } else {
+        None
+    }
+}
+
+#[test]
+fn test_menu_bar() {
+    use std::sync::Arc;
+    use {Theme};
+    use theme::ElementStyle;
+    use super::text::MonospaceFont;
+    use gui::input::{Event, MouseButton, KeyEvent, ScanCode};
+
+    let mut theme = Theme::empty();
+    theme.element_styles.insert((Default::default(), element_kind!("Label")), Arc::new(ElementStyle {
+        font: Arc::new(MonospaceFont::default()),
+        ..ElementStyle::default()
+    }));
+
+    let mut quit = MenuItem::action("&Quit", 5);
+    quit.enabled = false;
+    let mut new = MenuItem::action("&New", 1);
+    new.accelerator = Some("Ctrl+N".to_owned());
+
+    let file = Menu::new(vec![
+        new,
+        MenuItem::separator(),
+        MenuItem::submenu("&Recent", Menu::new(vec![MenuItem::action("&A", 2), MenuItem::action("&B", 3)])),
+        MenuItem::check("&Wrap", 4, false),
+        quit,
+    ]);
+    let view = Menu::new(vec![MenuItem::radio("&Small", 6, 0, true), MenuItem::radio("&Large", 7, 0, false)]);
+
+    let mut gui = Gui::new(theme);
+    gui.set_window_size(Vec2::new(400.0, 300.0));
+    let mut bar = MenuBar::new(Menu::new(vec![MenuItem::submenu("&File", file), MenuItem::submenu("&View", view)]));
+
+    let frame = |gui: &mut Gui, bar: &mut MenuBar, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = bar.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 400.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+    let tap = |gui: &mut Gui, bar: &mut MenuBar, keys: &[Key]| {
+        let press: Vec<Event> = keys.iter().map(|&k| key(k, true)).collect();
+        let release: Vec<Event> = keys.iter().map(|&k| key(k, false)).collect();
+        frame(gui, bar, &press);
+        frame(gui, bar, &release);
+    };
+    let click = |gui: &mut Gui, bar: &mut MenuBar, x, y| {
+        frame(gui, bar, &[Event::MouseMoved(Vec2::new(x, y)), Event::MouseButton(MouseButton::Primary, true)]);
+        frame(gui, bar, &[Event::MouseButton(MouseButton::Primary, false)]);
+        frame(gui, bar, &[]);
+    };
+    let highlighted = |bar: &MenuBar| bar.popups.levels.last().and_then(|level| level.popup.content.highlighted);
+
+    frame(&mut gui, &mut bar, &[Event::MouseEntered]);
+    frame(&mut gui, &mut bar, &[]);
+
+    // Alt+F opens the first menu with its first item highlighted, Down skips the separator.
+    tap(&mut gui, &mut bar, &[Key::LAlt, Key::F]);
+    assert_eq!(bar.open_menu(), Some(0));
+    assert_eq!(highlighted(&bar), Some(0));
+    tap(&mut gui, &mut bar, &[Key::Down]);
+    assert_eq!(highlighted(&bar), Some(2));
+
+    // Up from the first item wraps around skipping the disabled one.
+    tap(&mut gui, &mut bar, &[Key::Up]);
+    tap(&mut gui, &mut bar, &[Key::Up]);
+    assert_eq!(highlighted(&bar), Some(3));
+    tap(&mut gui, &mut bar, &[Key::Down, Key::Down]);
+
+    tap(&mut gui, &mut bar, &[Key::Right]);
+    assert_eq!(bar.popups.levels.len(), 2);
+    tap(&mut gui, &mut bar, &[Key::Down]);
+    tap(&mut gui, &mut bar, &[Key::Return]);
+    assert_eq!(bar.activated(), Some(3));
+    assert_eq!(bar.open_menu(), None);
+
+    // Typing the mnemonic activates the item.
+    tap(&mut gui, &mut bar, &[Key::LAlt, Key::F]);
+    frame(&mut gui, &mut bar, &[Event::Char('w')]);
+    frame(&mut gui, &mut bar, &[]);
+    assert_eq!(bar.activated(), Some(4));
+    if let MenuItemKind::Submenu(ref file) = bar.menu.items[0].kind {
+        assert_eq!(file.items[3].kind, MenuItemKind::Check { checked: true });
+    }
+
+    // "View" is after "File" in the bar, its items are below it.
+    click(&mut gui, &mut bar, 45.0, 10.0);
+    assert_eq!(bar.open_menu(), Some(1));
+    let large = gui.layout.prev_value_rect(Rect::from(bar.popups.levels[0].popup.content.rows[1].id));
+    assert_eq!((large.left, large.top, large.bottom), (40.0, 40.0, 60.0));
+
+    click(&mut gui, &mut bar, 60.0, 50.0);
+    assert_eq!(bar.activated(), Some(7));
+    if let MenuItemKind::Submenu(ref view) = bar.menu.items[1].kind {
+        assert_eq!(view.items[0].kind, MenuItemKind::Radio { group: 0, checked: false });
+        assert_eq!(view.items[1].kind, MenuItemKind::Radio { group: 0, checked: true });
+    }
+
+    // Escape closes the menu.
+    click(&mut gui, &mut bar, 5.0, 10.0);
+    assert_eq!(bar.open_menu(), Some(0));
+    tap(&mut gui, &mut bar, &[Key::Escape]);
+    assert_eq!(bar.open_menu(), None);
+}
+
+#[test]
+fn test_context_menu() {
+    use {Theme};
+    use gui::input::{Event, MouseButton, KeyEvent, ScanCode};
+
+    struct Target {
+        id: Id,
+    }
+
+    impl Widget for Target {
+        fn appear(&mut self, _gui: &mut Gui) -> Rect<Var> {
+            Rect::from(self.id)
+        }
+    }
+
+    let mut gui = Gui::new(Theme::empty());
+    gui.set_window_size(Vec2::new(200.0, 200.0));
+
+    let menu = Menu::new(vec![MenuItem::action("&Copy", 1), MenuItem::action("&Paste", 2)]);
+    let mut context_menu = ContextMenu::new(Target { id: Id::unique() }, menu);
+
+    let frame = |gui: &mut Gui, context_menu: &mut ContextMenu<Target>, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = context_menu.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 100.0,
+            (place.bottom) == 100.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+    let right_click = |gui: &mut Gui, context_menu: &mut ContextMenu<Target>, x, y| {
+        frame(gui, context_menu, &[Event::MouseMoved(Vec2::new(x, y)), Event::MouseButton(MouseButton::Secondary, true)]);
+        frame(gui, context_menu, &[Event::MouseButton(MouseButton::Secondary, false)]);
+    };
+
+    frame(&mut gui, &mut context_menu, &[Event::MouseEntered]);
+    frame(&mut gui, &mut context_menu, &[]);
+
+    // Clicks outside of the target are ignored.
+    right_click(&mut gui, &mut context_menu, 150.0, 150.0);
+    assert!(!context_menu.is_open());
+
+    right_click(&mut gui, &mut context_menu, 50.0, 60.0);
+    assert!(context_menu.is_open());
+    let menu_place = gui.layout.prev_value_rect(Rect::from(context_menu.popups.levels[0].popup.id()));
+    assert_eq!((menu_place.left, menu_place.top), (50.0, 60.0));
+
+    frame(&mut gui, &mut context_menu, &[Event::Char('p')]);
+    frame(&mut gui, &mut context_menu, &[]);
+    assert_eq!(context_menu.activated(), Some(2));
+    assert!(!context_menu.is_open());
+
+    right_click(&mut gui, &mut context_menu, 50.0, 60.0);
+    frame(&mut gui, &mut context_menu, &[key(Key::Escape, true)]);
+    frame(&mut gui, &mut context_menu, &[key(Key::Escape, false)]);
+    assert!(!context_menu.is_open());
+    assert_eq!(context_menu.activated(), None);
+}
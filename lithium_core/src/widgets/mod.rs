@@ -1,12 +1,98 @@
-use {Gui, Rect, Var};
+use std::time::Duration;
+use {Id, Gui, Rect, Var};
+use gui::input::{ButtonState, Key};
+use layout;
 
 pub mod button;
+pub mod canvas;
+pub mod checkbox;
+pub mod combo_box;
 pub mod click_area;
+pub mod color_picker;
+pub mod dialog;
 pub mod dummy;
+pub mod image_view;
+pub mod label;
+pub mod menu;
+pub mod popup;
+pub mod progress;
+pub mod radio_group;
+pub mod scroll_view;
+pub mod slider;
+pub mod spin_box;
+pub mod splitter;
+pub mod table;
+pub mod tabs;
+pub mod text_input;
+pub mod text_editor;
+pub mod toggle;
+pub mod tree_view;
+pub mod virtual_list;
+mod text;
 
+pub use self::canvas::{Canvas, Painter};
+pub use self::checkbox::{Checkbox, CheckState};
 pub use self::click_area::ClickArea;
+pub use self::color_picker::ColorPicker;
+pub use self::combo_box::ComboBox;
+pub use self::dialog::{Dialog, DialogContent, DialogResult, Alert, Confirm, Prompt};
 pub use self::dummy::Dummy;
+pub use self::image_view::{ImageView, ObjectFit};
+pub use self::label::Label;
+pub use self::menu::{Menu, MenuItem, MenuItemKind, MenuBar, ContextMenu};
+pub use self::popup::{Popup, Placement};
+pub use self::progress::{ProgressBar, Spinner, BusyOverlay};
+pub use self::radio_group::RadioGroup;
+pub use self::scroll_view::ScrollView;
+pub use self::slider::{Slider, RangeSlider, SliderRange, Orientation};
+pub use self::spin_box::SpinBox;
+pub use self::splitter::{Splitter, Pane};
+pub use self::table::{Table, TableModel, Column, SortOrder};
+pub use self::tabs::{Tabs, Tab};
+pub use self::text_input::TextInput;
+pub use self::text_editor::TextEditor;
+pub use self::toggle::Toggle;
+pub use self::tree_view::{TreeView, TreeModel};
+pub use self::virtual_list::{VirtualList, ListModel, RowHeight};
 
 pub trait Widget {
     fn appear(&mut self, gui: &mut Gui) -> Rect<Var>;
 }
+
+impl<W: Widget + ?Sized> Widget for Box<W> {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        (**self).appear(gui)
+    }
+}
+
+/// Focused widget was activated with the Space key during this frame.
+fn activated_by_keyboard(gui: &Gui, id: Id) -> bool {
+    gui.is_focused(id) && gui.input.keyboard.key_state(Key::Space) == ButtonState::JustPressed
+}
+
+/// Duration as a fractional number of seconds.
+fn seconds(duration: Duration) -> f64 {
+    duration.as_secs() as f64 + (duration.subsec_nanos() as f64)*1e-9
+}
+
+/// Whether Shift and Control are held.
+fn modifiers(gui: &Gui) -> (bool, bool) {
+    let keyboard = &gui.input.keyboard;
+    (keyboard.is_pressed(Key::LShift) || keyboard.is_pressed(Key::RShift),
+     keyboard.is_pressed(Key::LControl) || keyboard.is_pressed(Key::RControl))
+}
+
+/// Place a square indicator (like the box of a checkbox) at the left side of `place` followed by the label.
+fn indicator_with_label(gui: &mut Gui, place: Rect<Var>, indicator: Rect<Var>, label: Rect<Var>) {
+    add_constraints!(gui.layout, [
+        (indicator.left) == place.left,
+        (indicator.top) == place.top,
+        (indicator.bottom) == place.bottom,
+        (indicator.right - indicator.left) == indicator.bottom - indicator.top,
+        (label.left) == indicator.right,
+        (place.right) == label.right,
+        (place.bottom - place.top) == label.bottom - label.top,
+    ]);
+
+    layout::center_horizontal(gui, place, label);
+}
@@ -0,0 +1,254 @@
+use {Id, Gui, Rect, Var};
+use gui::input::{ButtonState, Key};
+use layout;
+use super::Widget;
+
+/// Side of the anchor at which a popup is placed.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Placement {
+    Below,
+    Above,
+    Right,
+    Left,
+}
+
+/// Content shown above all other widgets next to an anchor, such as a dropdown list, a menu or a tooltip.
+///
+/// The popup is placed at the preferred side of the anchor. If it doesn't fit into the window there but fits at the
+/// opposite side it flips, and it is shifted along the anchor to stay inside of the window. An open popup is closed by
+/// pressing the mouse button outside of it and its anchor, the press still reaches the widgets under the pointer. It is
+/// also closed by Escape while it is the topmost popup.
+///
+/// The popup is the `Popup` element containing the content, it is built with `Gui::popup` so it gets the mouse first.
+pub struct Popup<T: Widget> {
+    id: Id,
+    open: bool,
+    closed: bool,
+
+    pub content: T,
+    pub placement: Placement,
+}
+
+impl<T: Widget> Popup<T> {
+    pub fn new(content: T) -> Self {
+        Popup {
+            id: Id::unique(),
+            open: false,
+            closed: false,
+
+            content,
+            placement: Placement::Below,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Popup was closed by the user since the last call.
+    pub fn closed(&mut self) -> bool {
+        let prev = self.closed;
+        self.closed = false;
+        prev
+    }
+
+    /// Show the popup next to the `anchor` while it is open.
+    ///
+    /// This should be called from `appear` of the widget owning the anchor. The content appears inside of the popup layer
+    /// regardless of where in the element tree this is called.
+    pub fn appear(&mut self, gui: &mut Gui, anchor: Rect<Var>) -> Rect<Var> {
+        let place = Rect::from(self.id);
+
+        if !self.open {
+            return place;
+        }
+
+        let place_value = gui.layout.prev_value_rect(place);
+        let anchor_value = gui.layout.prev_value_rect(anchor);
+
+        if self.dismissed(gui, place_value, anchor_value) {
+            self.open = false;
+            self.closed = true;
+            return place;
+        }
+
+        let content = &mut self.content;
+        gui.popup(self.id, element_kind!("Popup"), |gui| {
+            let content_place = content.appear(gui);
+            layout::equal(gui, place, content_place);
+        });
+
+        self.position(gui, place, anchor, place_value, anchor_value);
+
+        place
+    }
+
+    /// Mouse button was pressed outside of the popup or Escape was pressed while it is the topmost popup.
+    fn dismissed(&self, gui: &Gui, place: Rect<f64>, anchor: Rect<f64>) -> bool {
+        let popups = gui.input.popups();
+        let own = popups.iter().position(|&(id, _)| id == self.id);
+
+        let topmost = own.is_some() && own == popups.len().checked_sub(1);
+        if topmost && gui.input.keyboard.key_state(Key::Escape) == ButtonState::JustPressed {
+            return true;
+        }
+
+        match gui.input.peek_mouse() {
+            Some(mouse) if mouse.primary_button == ButtonState::JustPressed => {
+                let position = mouse.position;
+
+                // Popups painted later, like submenus, count as a part of this one.
+                let later = own.map_or(&[][..], |own| &popups[own + 1..]);
+                let inside = place.contains(position) || later.iter().any(|&(_, later)| later.contains(position));
+
+                !inside && !anchor.contains(position)
+            }
+            _ => false,
+        }
+    }
+
+    /// Place the popup at the side of the anchor where it fits and shift it along the anchor into the window.
+    fn position(&self, gui: &mut Gui, place: Rect<Var>, anchor: Rect<Var>, place_value: Rect<f64>, anchor_value: Rect<f64>) {
+        let (width, height) = (place_value.width(), place_value.height());
+        let window = gui.window();
+
+        let fits = |placement| match window {
+            Some(window) => match placement {
+                Placement::Below => anchor_value.bottom + height <= window.bottom,
+                Placement::Above => anchor_value.top - height >= window.top,
+                Placement::Right => anchor_value.right + width <= window.right,
+                Placement::Left => anchor_value.left - width >= window.left,
+            },
+            None => true,
+        };
+
+        let opposite = match self.placement {
+            Placement::Below => Placement::Above,
+            Placement::Above => Placement::Below,
+            Placement::Right => Placement::Left,
+            Placement::Left => Placement::Right,
+        };
+
+        let placement = if !fits(self.placement) && fits(opposite) { opposite } else { self.placement };
+
+        // Offset along the anchor which keeps the popup inside of the window.
+        let shift = |start: f64, size: f64, min: f64, max: f64| start.min(max - size).max(min) - start;
+
+        match placement {
+            Placement::Below | Placement::Above => {
+                let shift = window.map_or(0.0, |window| shift(anchor_value.left, width, window.left, window.right));
+                add_constraints!(gui.layout, [(place.left) == anchor.left + shift]);
+            }
+            Placement::Right | Placement::Left => {
+                let shift = window.map_or(0.0, |window| shift(anchor_value.top, height, window.top, window.bottom));
+                add_constraints!(gui.layout, [(place.top) == anchor.top + shift]);
+            }
+        }
+
+        match placement {
+            Placement::Below => { add_constraints!(gui.layout, [(place.top) == anchor.bottom]); }
+            Placement::Above => { add_constraints!(gui.layout, [(place.bottom) == anchor.top]); }
+            Placement::Right => { add_constraints!(gui.layout, [(place.left) == anchor.right]); }
+            Placement::Left => { add_constraints!(gui.layout, [(place.right) == anchor.left]); }
+        }
+    }
+}
+
+#[test]
+fn test_popup() {
+    use {Theme, Vec2};
+    use gui::input::{Event, MouseButton, KeyEvent, ScanCode};
+    use widgets::ClickArea;
+
+    struct Sized {
+        id: Id,
+    }
+
+    impl Widget for Sized {
+        fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+            let place = Rect::from(self.id);
+            add_constraints!(gui.layout, [
+                (place.right - place.left) == 50.0,
+                (place.bottom - place.top) == 40.0,
+            ]);
+            place
+        }
+    }
+
+    let mut gui = Gui::new(Theme::empty());
+    gui.set_window_size(Vec2::new(200.0, 200.0));
+
+    let mut background = ClickArea::new();
+    let mut popup = Popup::new(Sized { id: Id::unique() });
+    let anchor = Rect::from(Id::unique());
+
+    let frame = |gui: &mut Gui, background: &mut ClickArea, popup: &mut Popup<Sized>, anchor_left: f64, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let background_place = background.appear(gui);
+        popup.appear(gui, anchor);
+
+        add_constraints!(gui.layout, [
+            (background_place.left) == 0.0,
+            (background_place.top) == 0.0,
+            (background_place.right) == 200.0,
+            (background_place.bottom) == 200.0,
+            (anchor.left) == anchor_left,
+            (anchor.right) == anchor_left + 20.0,
+            (anchor.top) == 170.0,
+            (anchor.bottom) == 190.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let press = |x, y| vec![Event::MouseMoved(Vec2::new(x, y)), Event::MouseButton(MouseButton::Primary, true)];
+    let release = || vec![Event::MouseButton(MouseButton::Primary, false)];
+    let escape = |pressed| Event::Key(KeyEvent { key: Some(Key::Escape), scan_code: ScanCode(0), pressed });
+    let popup_place = |gui: &Gui, popup: &Popup<Sized>| gui.layout.prev_value_rect(Rect::from(popup.id()));
+
+    popup.open();
+    frame(&mut gui, &mut background, &mut popup, 10.0, &[Event::MouseEntered]);
+    frame(&mut gui, &mut background, &mut popup, 10.0, &[]);
+    frame(&mut gui, &mut background, &mut popup, 10.0, &[]);
+
+    // There is no room below the anchor so the popup flips above it.
+    assert_eq!(popup_place(&gui, &popup), Rect { left: 10.0, top: 130.0, right: 60.0, bottom: 170.0 });
+
+    // Near the right edge of the window it is shifted to the left.
+    frame(&mut gui, &mut background, &mut popup, 170.0, &[]);
+    frame(&mut gui, &mut background, &mut popup, 170.0, &[]);
+    assert_eq!(popup_place(&gui, &popup).left, 150.0);
+
+    // Clicks on the popup don't reach the widgets under it.
+    frame(&mut gui, &mut background, &mut popup, 170.0, &press(160.0, 150.0));
+    frame(&mut gui, &mut background, &mut popup, 170.0, &release());
+    assert!(!background.clicked());
+    assert!(popup.is_open());
+
+    frame(&mut gui, &mut background, &mut popup, 170.0, &[escape(true)]);
+    frame(&mut gui, &mut background, &mut popup, 170.0, &[escape(false)]);
+    assert!(!popup.is_open());
+    assert!(popup.closed());
+
+    popup.open();
+    frame(&mut gui, &mut background, &mut popup, 170.0, &[]);
+    frame(&mut gui, &mut background, &mut popup, 170.0, &press(20.0, 20.0));
+    frame(&mut gui, &mut background, &mut popup, 170.0, &release());
+    assert!(!popup.is_open());
+    assert!(background.clicked());
+}
@@ -0,0 +1,321 @@
+use std::f64::consts::PI;
+use std::rc::Rc;
+use std::cell::RefCell;
+use {Id, Gui, Rect, Var, Vec2, Color};
+use gui::scene::{Mesh, MeshVertices, Vertex};
+use solver::Strength;
+use super::{Widget, seconds};
+
+/// Seconds it takes the fill of an indeterminate progress bar to cross the bar.
+const SLIDE_PERIOD: f64 = 1.5;
+
+/// Length of the fill of an indeterminate progress bar as a fraction of the bar.
+const SLIDE_LENGTH: f64 = 0.3;
+
+/// Seconds of one revolution of a spinner.
+const SPIN_PERIOD: f64 = 1.0;
+
+/// Angle covered by the arc of a spinner.
+const SPIN_ARC: f64 = 1.5*PI;
+
+/// Thickness of the arc of a spinner as a fraction of its radius.
+const SPIN_THICKNESS: f64 = 0.25;
+
+/// Number of quads the arc of a spinner is made of.
+const SPIN_SEGMENTS: u32 = 32;
+
+/// Bar showing how much of some work is done.
+///
+/// The `ProgressBar` element contains the `ProgressBarFill` which extends from the left to the done fraction. When the
+/// progress is unknown the bar is in the `indeterminate` state and a short fill slides across it, the theme should give
+/// `ProgressBar` `overflow: hidden` so that the fill is clipped.
+pub struct ProgressBar {
+    id: Id,
+    fill_id: Id,
+    value: Option<f64>,
+}
+
+/// Circular activity indicator, an arc with a fading tail which rotates while the spinner is shown.
+///
+/// The arc is drawn inside of the `Spinner` element with the `color` of its style. Its preferred size is `size`.
+pub struct Spinner {
+    id: Id,
+    vertices: Rc<RefCell<MeshVertices>>,
+
+    pub size: f64,
+}
+
+/// Shows a spinner above the content while `busy` and blocks the pointer from reaching the content.
+///
+/// The `BusyOverlay` element is a popup covering the content with the `Spinner` in its center. Keyboard focus is not
+/// blocked.
+pub struct BusyOverlay<T: Widget> {
+    id: Id,
+    spinner: Spinner,
+
+    pub content: T,
+    pub busy: bool,
+}
+
+impl Widget for ProgressBar {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let (place, fill) = (Rect::from(self.id), Rect::from(self.fill_id));
+        let fill_id = self.fill_id;
+
+        let state = match self.value {
+            Some(_) => vec![],
+            None => vec![pseudo_class!("indeterminate")],
+        };
+
+        gui.element_in_state(self.id, element_kind!("ProgressBar"), &state, |gui| {
+            gui.element(fill_id, element_kind!("ProgressBarFill"), |_gui| {});
+        });
+
+        let (start, end) = match self.value {
+            Some(value) => (0.0, value),
+            None => {
+                gui.input.request_animation_frame();
+
+                // The fill enters from the left edge and leaves through the right one.
+                let phase = (seconds(gui.input.time())/SLIDE_PERIOD).fract();
+                let start = phase*(1.0 + SLIDE_LENGTH) - SLIDE_LENGTH;
+                (start, start + SLIDE_LENGTH)
+            }
+        };
+
+        add_constraints!(gui.layout, [
+            (fill.top) == place.top,
+            (fill.bottom) == place.bottom,
+            (fill.left) == place.left*(1.0 - start) + place.right*start,
+            (fill.right) == place.left*(1.0 - end) + place.right*end,
+        ]);
+
+        place
+    }
+}
+
+impl ProgressBar {
+    /// Determinate progress bar with nothing done.
+    pub fn new() -> Self {
+        ProgressBar {
+            id: Id::unique(),
+            fill_id: Id::unique(),
+            value: Some(0.0),
+        }
+    }
+
+    /// Progress bar showing that the amount of work is unknown.
+    pub fn indeterminate() -> Self {
+        ProgressBar {
+            value: None,
+            .. ProgressBar::new()
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn fill_id(&self) -> Id {
+        self.fill_id
+    }
+
+    /// Done fraction or `None` if the progress is indeterminate.
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    /// Set the done fraction, it is clamped to `[0, 1]`. `None` makes the progress bar indeterminate.
+    pub fn set_value(&mut self, value: Option<f64>) {
+        self.value = value.map(|value| value.max(0.0).min(1.0));
+    }
+}
+
+impl Widget for Spinner {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let kind = element_kind!("Spinner");
+        let color = gui.scene.element_style(kind).font_color;
+        let frame = gui.layout.prev_value_rect(place);
+
+        gui.input.request_animation_frame();
+
+        let angle = (seconds(gui.input.time())/SPIN_PERIOD).fract()*2.0*PI;
+        let radius = frame.width().min(frame.height())/2.0;
+        arc(&mut self.vertices.borrow_mut(), frame.center(), radius, radius*SPIN_THICKNESS, angle, color);
+
+        let vertices = self.vertices.clone();
+        gui.element(self.id, kind, |gui| {
+            gui.scene.mesh(Mesh { data: vertices });
+        });
+
+        gui.layout.with_strength(Strength::Weak, |layout| {
+            add_constraints!(layout, [
+                (place.right - place.left) == self.size,
+                (place.bottom - place.top) == self.size,
+            ]);
+        });
+
+        place
+    }
+}
+
+impl Spinner {
+    pub fn new() -> Self {
+        Spinner {
+            id: Id::unique(),
+            vertices: Rc::new(RefCell::new(MeshVertices { vertices: Vec::new(), indices: Vec::new() })),
+
+            size: 24.0,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+}
+
+impl<T: Widget> Widget for BusyOverlay<T> {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = self.content.appear(gui);
+
+        if self.busy {
+            let overlay = Rect::from(self.id);
+            let spinner = &mut self.spinner;
+
+            gui.popup(self.id, element_kind!("BusyOverlay"), |gui| {
+                let spinner_place = spinner.appear(gui);
+
+                add_constraints!(gui.layout, [
+                    (spinner_place.left - overlay.left) == overlay.right - spinner_place.right,
+                    (spinner_place.top - overlay.top) == overlay.bottom - spinner_place.bottom,
+                ]);
+            });
+
+            ::layout::equal(gui, overlay, place);
+        }
+
+        place
+    }
+}
+
+impl<T: Widget> BusyOverlay<T> {
+    pub fn new(content: T) -> Self {
+        BusyOverlay {
+            id: Id::unique(),
+            spinner: Spinner::new(),
+
+            content,
+            busy: false,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+}
+
+/// Replace the `vertices` with a ring arc of `SPIN_ARC` ending at the `angle`, it fades out towards its start.
+fn arc(vertices: &mut MeshVertices, center: Vec2<f64>, radius: f64, thickness: f64, angle: f64, color: Color) {
+    vertices.vertices.clear();
+    vertices.indices.clear();
+
+    for i in 0..(SPIN_SEGMENTS + 1) {
+        let t = i as f64/SPIN_SEGMENTS as f64;
+        let direction = Vec2::from_angle(angle - SPIN_ARC*(1.0 - t));
+
+        // Colors are premultiplied by alpha so all of the components fade.
+        let t = t as f32;
+        let color = Color { r: color.r*t, g: color.g*t, b: color.b*t, a: color.a*t };
+
+        vertices.vertices.push(Vertex { position: center + direction*radius, color });
+        vertices.vertices.push(Vertex { position: center + direction*(radius - thickness), color });
+    }
+
+    for i in 0..SPIN_SEGMENTS {
+        let first = 2*i;
+        vertices.indices.extend_from_slice(&[first, first + 1, first + 2, first + 1, first + 3, first + 2]);
+    }
+}
+
+#[test]
+fn test_progress_indicators() {
+    use std::time::Duration;
+    use Theme;
+    use gui::input::{Event, MouseButton};
+    use gui::scene::Command;
+    use super::ClickArea;
+
+    let mut gui = Gui::new(Theme::empty());
+
+    // Fill of a progress bar as the fractions of the bar width.
+    let progress = |gui: &mut Gui, bar: &mut ProgressBar, millis: u64| {
+        gui.input.set_time(Duration::from_millis(millis));
+        let place = bar.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 200.0,
+            (place.bottom) == 10.0,
+        ]);
+
+        let animating = gui.input.animation_frame_requested();
+        gui.advance();
+        let fill = gui.layout.prev_value_rect(Rect::from(bar.fill_id()));
+        (fill.left/200.0, fill.right/200.0, animating)
+    };
+
+    let mut bar = ProgressBar::new();
+    bar.set_value(Some(0.25));
+    assert_eq!(progress(&mut gui, &mut bar, 0), (0.0, 0.25, false));
+    bar.set_value(Some(3.0));
+    assert_eq!(progress(&mut gui, &mut bar, 0), (0.0, 1.0, false));
+
+    let mut bar = ProgressBar::indeterminate();
+    let (left, right, animating) = progress(&mut gui, &mut bar, 0);
+    assert!(animating);
+    assert!((left + SLIDE_LENGTH).abs() < 1e-6 && right.abs() < 1e-6);
+    let (left, _, _) = progress(&mut gui, &mut bar, 750);
+    assert!((left - (0.5*(1.0 + SLIDE_LENGTH) - SLIDE_LENGTH)).abs() < 1e-6);
+
+    // Busy overlay shows a spinner and keeps clicks from the content.
+    let mut overlay = BusyOverlay::new(ClickArea::new());
+    let frame = |gui: &mut Gui, overlay: &mut BusyOverlay<ClickArea>, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = overlay.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 100.0,
+            (place.bottom) == 100.0,
+        ]);
+
+        let meshes: Vec<usize> = gui.scene.commands().iter().filter_map(|command| match *command {
+            Command::Mesh(ref mesh) => Some(mesh.data.borrow().indices.len()),
+            _ => None,
+        }).collect();
+
+        gui.advance();
+        meshes
+    };
+
+    let click = |x, y| vec![Event::MouseMoved(Vec2::new(x, y)), Event::MouseButton(MouseButton::Primary, true)];
+    let release = || vec![Event::MouseButton(MouseButton::Primary, false)];
+
+    frame(&mut gui, &mut overlay, &[Event::MouseEntered]);
+    frame(&mut gui, &mut overlay, &click(50.0, 50.0));
+    frame(&mut gui, &mut overlay, &release());
+    assert!(overlay.content.clicked());
+
+    overlay.busy = true;
+    assert_eq!(frame(&mut gui, &mut overlay, &[]), vec![6*SPIN_SEGMENTS as usize]);
+    frame(&mut gui, &mut overlay, &click(50.0, 50.0));
+    frame(&mut gui, &mut overlay, &release());
+    assert!(!overlay.content.clicked());
+
+    let spinner = gui.layout.prev_value_rect(Rect::from(overlay.spinner.id()));
+    assert_eq!(spinner, Rect { left: 38.0, top: 38.0, right: 62.0, bottom: 62.0 });
+}
@@ -0,0 +1,210 @@
+use {Id, Gui, Rect, Var};
+use layout;
+use super::{Widget, ClickArea, activated_by_keyboard, indicator_with_label};
+
+/// Vertical list of options where only one can be selected.
+///
+/// Each option is a `Radio` element with an indicator followed by the label, the indicator is a `Radio` element styled
+/// with the `indicator` style variant (`Radio.indicator` in CSS) which rules for the whole `Radio` don't apply to. Both
+/// of the selected option get the `checked` pseudo-class. Options form a focus scope so directional navigation moves
+/// between them first.
+pub struct RadioGroup<T: Widget> {
+    id: Id,
+    options: Vec<RadioOption<T>>,
+    selected: Option<usize>,
+    changed: bool,
+}
+
+struct RadioOption<T: Widget> {
+    id: Id,
+    indicator_id: Id,
+    click_area: ClickArea,
+    label: T,
+}
+
+impl<T: Widget> Widget for RadioGroup<T> {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let id = self.id;
+        let place = Rect::from(id);
+        let selected = self.selected;
+        let options = &mut self.options;
+        let mut activated = None;
+
+        gui.element(id, element_kind!("RadioGroup"), |gui| {
+            gui.focus_scope(id, |gui| {
+                let mut previous: Option<Rect<Var>> = None;
+
+                for (i, option) in options.iter_mut().enumerate() {
+                    let option_place = option.appear(gui, selected == Some(i));
+
+                    if option.click_area.clicked() {
+                        gui.focus(option.id);
+                        activated = Some(i);
+                    } else if activated_by_keyboard(gui, option.id) {
+                        activated = Some(i);
+                    }
+
+                    add_constraints!(gui.layout, [
+                        (option_place.left) == place.left,
+                        (place.right) >= option_place.right,
+                    ]);
+
+                    let top = previous.map_or(place.top, |previous| previous.bottom);
+                    add_constraints!(gui.layout, [(option_place.top) == top]);
+                    previous = Some(option_place);
+                }
+
+                let bottom = previous.map_or(place.top, |last| last.bottom);
+                add_constraints!(gui.layout, [(place.bottom) == bottom]);
+            });
+        });
+
+        if let Some(activated) = activated {
+            if self.selected != Some(activated) {
+                self.selected = Some(activated);
+                self.changed = true;
+            }
+        }
+
+        place
+    }
+}
+
+impl<T: Widget> RadioOption<T> {
+    fn appear(&mut self, gui: &mut Gui, checked: bool) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let indicator_place = Rect::from(self.indicator_id);
+        let pseudo_classes = if checked { vec![pseudo_class!("checked")] } else { vec![] };
+
+        gui.focusable(self.id);
+
+        let indicator_id = self.indicator_id;
+        let click_area = &mut self.click_area;
+        let label = &mut self.label;
+
+        gui.element_in_state(self.id, element_kind!("Radio"), &pseudo_classes, |gui| {
+            let click_area_place = click_area.appear(gui);
+            gui.part(indicator_id, element_kind!("Radio"), style_variant!("indicator"), &pseudo_classes, |_gui| {});
+            let label_place = label.appear(gui);
+
+            layout::equal(gui, place, click_area_place);
+            indicator_with_label(gui, place, indicator_place, label_place);
+        });
+
+        place
+    }
+}
+
+impl<T: Widget> RadioGroup<T> {
+    pub fn new() -> Self {
+        RadioGroup {
+            id: Id::unique(),
+            options: Vec::new(),
+            selected: None,
+            changed: false,
+        }
+    }
+
+    /// Add an option with the `label` and return its index.
+    pub fn add(&mut self, label: T) -> usize {
+        self.options.push(RadioOption {
+            id: Id::unique(),
+            indicator_id: Id::unique(),
+            click_area: ClickArea::new(),
+            label,
+        });
+
+        self.options.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.options.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.options.is_empty()
+    }
+
+    pub fn label(&self, index: usize) -> &T {
+        &self.options[index].label
+    }
+
+    pub fn label_mut(&mut self, index: usize) -> &mut T {
+        &mut self.options[index].label
+    }
+
+    /// Identifier of the option element which can be used to focus it.
+    pub fn option_id(&self, index: usize) -> Id {
+        self.options[index].id
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: Option<usize>) {
+        assert!(index.map_or(true, |index| index < self.options.len()));
+        self.selected = index;
+    }
+
+    /// Selection was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+}
+
+#[test]
+fn test_radio_group_exclusive_selection() {
+    use std::sync::Arc;
+    use Theme;
+    use theme::ElementStyle;
+    use gui::input::{Event, KeyEvent, ScanCode, Key};
+    use gui::scene::Command;
+    use super::Dummy;
+
+    let mut theme = Theme::empty();
+    theme.element_styles.insert((Default::default(), element_kind!("Radio").with_pseudo_class(pseudo_class!("checked"))),
+        Arc::new(ElementStyle { opacity: 0.5, ..ElementStyle::default() }));
+
+    let mut gui = Gui::new(theme);
+    let mut group = RadioGroup::new();
+    for _ in 0..3 {
+        group.add(Dummy::new());
+    }
+
+    // Returns elements which were shown as checked, the rule for the whole option doesn't apply to the indicator.
+    let frame = |gui: &mut Gui, group: &mut RadioGroup<Dummy>, space: bool| {
+        gui.input.event(&Event::Key(KeyEvent { key: Some(Key::Space), scan_code: ScanCode(0), pressed: space }));
+        group.appear(gui);
+
+        let checked: Vec<_> = gui.scene.commands().iter().filter_map(|command| match *command {
+            Command::CloseElement(ref element) if element.style.opacity == 0.5 => Some(element.id),
+            _ => None,
+        }).collect();
+
+        gui.advance();
+        checked
+    };
+
+    assert_eq!(frame(&mut gui, &mut group, false), vec![]);
+
+    gui.focus(group.option_id(0));
+    frame(&mut gui, &mut group, true);
+    assert_eq!(group.selected(), Some(0));
+    assert!(group.changed());
+    assert_eq!(frame(&mut gui, &mut group, false), vec![group.option_id(0)]);
+
+    // Selecting another option unchecks the previous one.
+    gui.focus(group.option_id(2));
+    frame(&mut gui, &mut group, true);
+    assert_eq!(group.selected(), Some(2));
+    assert!(group.changed());
+    assert_eq!(frame(&mut gui, &mut group, false), vec![group.option_id(2)]);
+
+    // Activating the selected option again is not a change.
+    frame(&mut gui, &mut group, true);
+    assert_eq!(group.selected(), Some(2));
+    assert!(!group.changed());
+}
@@ -0,0 +1,425 @@
+use std::time::Duration;
+use {Id, Gui, Rect, Var, Vec2};
+use gui::input::ButtonState;
+use theme::element_style::Overflow;
+use super::{Widget, seconds};
+
+/// Pixels scrolled by one line of the mouse wheel.
+const LINE_HEIGHT: f64 = 40.0;
+
+/// Shortest scroll bar thumb so that it can be grabbed even when the content is very long.
+const MIN_THUMB_LENGTH: f64 = 20.0;
+
+/// Fraction of the panning speed which remains after each second of momentum scrolling.
+const MOMENTUM_DECAY: f64 = 0.05;
+
+/// Momentum scrolling stops below this speed in pixels per second.
+const MIN_SPEED: f64 = 10.0;
+
+/// Container which shows a part of its content and lets the user scroll it.
+///
+/// Axes along which the content scrolls are chosen by the `overflow` of the `ScrollView` style. Along axes where it is
+/// `visible` the content is as large as the view, along the others it is at least as large. Only `scroll` and `auto`
+/// axes can be scrolled by the user, `hidden` ones can still be scrolled by `set_scroll` and `scroll_into_view`.
+///
+/// Content is scrolled by the mouse wheel, by panning with a touch which continues with momentum after the touch is
+/// released and by dragging the scroll bars. Scroll bars are `ScrollBar` elements containing a `ScrollBarThumb`, both
+/// in the `horizontal` or `vertical` state. They are placed over the content along the bottom and the right edge.
+pub struct ScrollView<T: Widget> {
+    id: Id,
+    bar_ids: [Id; 2],
+    thumb_ids: [Id; 2],
+    content_place: Option<Rect<Var>>,
+
+    scroll: Vec2<f64>,
+    velocity: Vec2<f64>,
+
+    /// Touch which pans the content and its position in the previous frame.
+    pan: Option<(u64, Vec2<f64>)>,
+
+    /// Offset of the grabbed point from the start of the dragged thumb.
+    drag_offset: f64,
+
+    reveal: Option<Id>,
+    prev_time: Option<Duration>,
+
+    pub content: T,
+    pub scroll_bar_width: f64,
+}
+
+impl<T: Widget> Widget for ScrollView<T> {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let view = gui.layout.prev_value_rect(place);
+        let content = self.content_place.map_or(view, |content| gui.layout.prev_value_rect(content));
+
+        let overflow = gui.scene.element_style(element_kind!("ScrollView")).overflow;
+        let overflow = [overflow.x, overflow.y];
+
+        // Scrolling is limited by the content size from the previous frame.
+        let max_scroll = [
+            if overflow[0] == Overflow::Visible { 0.0 } else { (content.width() - view.width()).max(0.0) },
+            if overflow[1] == Overflow::Visible { 0.0 } else { (content.height() - view.height()).max(0.0) },
+        ];
+        let user_scrollable = [
+            (overflow[0] == Overflow::Scroll || overflow[0] == Overflow::Auto) && max_scroll[0] > 0.0,
+            (overflow[1] == Overflow::Scroll || overflow[1] == Overflow::Auto) && max_scroll[1] > 0.0,
+        ];
+        let bar_shown = [
+            overflow[0] == Overflow::Scroll || user_scrollable[0],
+            overflow[1] == Overflow::Scroll || user_scrollable[1],
+        ];
+
+        let time = gui.input.time();
+        let dt = self.prev_time.and_then(|prev| time.checked_sub(prev)).map_or(0.0, seconds);
+        self.prev_time = Some(time);
+
+        let mut scroll = [self.scroll.x, self.scroll.y];
+
+        // Scroll bars are above the content so they take the mouse before it.
+        for axis in 0..2 {
+            if bar_shown[axis] {
+                let view_length = along(axis, view.size());
+                self.scroll_bar_input(gui, axis, view_length, max_scroll[axis], &mut scroll[axis]);
+            }
+        }
+
+        let (bar_ids, thumb_ids) = (self.bar_ids, self.thumb_ids);
+        let content_widget = &mut self.content;
+
+        let content_place = gui.element(self.id, element_kind!("ScrollView"), |gui| {
+            let content_place = content_widget.appear(gui);
+
+            for axis in 0..2 {
+                if bar_shown[axis] {
+                    let state = [if axis == 0 { pseudo_class!("horizontal") } else { pseudo_class!("vertical") }];
+
+                    gui.element_in_state(bar_ids[axis], element_kind!("ScrollBar"), &state, |gui| {
+                        gui.element_in_state(thumb_ids[axis], element_kind!("ScrollBarThumb"), &state, |_gui| {});
+                    });
+                }
+            }
+
+            content_place
+        });
+        self.content_place = Some(content_place);
+
+        // Nested scroll views appear as a part of the content so they take the wheel first.
+        if user_scrollable[0] || user_scrollable[1] {
+            if let Some(wheel) = gui.input.take_wheel(LINE_HEIGHT, |pos| view.contains(pos)) {
+                let delta = [wheel.x, -wheel.y];
+                for axis in 0..2 {
+                    if user_scrollable[axis] {
+                        scroll[axis] += delta[axis];
+                    }
+                }
+                self.velocity = Vec2::zero();
+            }
+        }
+
+        self.pan_input(gui, view, dt, user_scrollable, &mut scroll);
+
+        if let Some(id) = self.reveal.take() {
+            let target = gui.layout.prev_value_rect(Rect::from(id));
+            scroll[0] += reveal_offset(view.left, view.right, target.left, target.right);
+            scroll[1] += reveal_offset(view.top, view.bottom, target.top, target.bottom);
+        }
+
+        for axis in 0..2 {
+            let clamped = scroll[axis].max(0.0).min(max_scroll[axis]);
+
+            // Momentum stops at the edges.
+            if clamped != scroll[axis] {
+                if axis == 0 { self.velocity.x = 0.0 } else { self.velocity.y = 0.0 }
+            }
+
+            scroll[axis] = clamped;
+        }
+        self.scroll = Vec2::new(scroll[0], scroll[1]);
+
+        add_constraints!(gui.layout, [
+            (content_place.left) == place.left - self.scroll.x,
+            (content_place.top) == place.top - self.scroll.y,
+        ]);
+
+        if overflow[0] == Overflow::Visible {
+            add_constraints!(gui.layout, [(content_place.right) == place.right]);
+        } else {
+            add_constraints!(gui.layout, [(content_place.right) >= place.right]);
+        }
+
+        if overflow[1] == Overflow::Visible {
+            add_constraints!(gui.layout, [(content_place.bottom) == place.bottom]);
+        } else {
+            add_constraints!(gui.layout, [(content_place.bottom) >= place.bottom]);
+        }
+
+        let width = self.scroll_bar_width;
+
+        if bar_shown[0] {
+            let bar = Rect::from(self.bar_ids[0]);
+            let corner = if bar_shown[1] { width } else { 0.0 };
+
+            add_constraints!(gui.layout, [
+                (bar.left) == place.left,
+                (bar.right) == place.right - corner,
+                (bar.top) == place.bottom - width,
+                (bar.bottom) == place.bottom,
+            ]);
+        }
+
+        if bar_shown[1] {
+            let bar = Rect::from(self.bar_ids[1]);
+            let corner = if bar_shown[0] { width } else { 0.0 };
+
+            add_constraints!(gui.layout, [
+                (bar.left) == place.right - width,
+                (bar.right) == place.right,
+                (bar.top) == place.top,
+                (bar.bottom) == place.bottom - corner,
+            ]);
+        }
+
+        for axis in 0..2 {
+            if bar_shown[axis] {
+                let view_length = along(axis, view.size());
+                let content_length = along(axis, content.size());
+                self.place_thumb(gui, axis, view_length, content_length, max_scroll[axis]);
+            }
+        }
+
+        place
+    }
+}
+
+impl<T: Widget> ScrollView<T> {
+    pub fn new(content: T) -> Self {
+        ScrollView {
+            id: Id::unique(),
+            bar_ids: [Id::unique(), Id::unique()],
+            thumb_ids: [Id::unique(), Id::unique()],
+            content_place: None,
+
+            scroll: Vec2::zero(),
+            velocity: Vec2::zero(),
+            pan: None,
+            drag_offset: 0.0,
+
+            reveal: None,
+            prev_time: None,
+
+            content,
+            scroll_bar_width: 10.0,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Distance by which the content is scrolled from its top left corner.
+    pub fn scroll(&self) -> Vec2<f64> {
+        self.scroll
+    }
+
+    /// Scroll the content and stop momentum scrolling. The scroll is clamped to the content size in the next frame.
+    pub fn set_scroll(&mut self, scroll: Vec2<f64>) {
+        self.scroll = scroll;
+        self.velocity = Vec2::zero();
+    }
+
+    /// Scroll as little as possible during the next frame so that the element `id` inside of the content is visible.
+    ///
+    /// Element place from the previous frame is used. If it does not fit into the view then its start is shown.
+    pub fn scroll_into_view(&mut self, id: Id) {
+        self.reveal = Some(id);
+        self.velocity = Vec2::zero();
+    }
+
+    fn scroll_bar_input(&mut self, gui: &mut Gui, axis: usize, view_length: f64, max_scroll: f64, scroll: &mut f64) {
+        let bar = gui.layout.prev_value_rect(Rect::from(self.bar_ids[axis]));
+        let thumb = gui.layout.prev_value_rect(Rect::from(self.thumb_ids[axis]));
+
+        let (bar_start, bar_length) = (along(axis, bar.top_left()), along(axis, bar.size()));
+        let (thumb_start, thumb_length) = (along(axis, thumb.top_left()), along(axis, thumb.size()));
+
+        let scroll_at = |thumb_start: f64| if bar_length > thumb_length {
+            (thumb_start - bar_start)/(bar_length - thumb_length)*max_scroll
+        } else {
+            0.0
+        };
+
+        if let Some(mouse) = gui.input.mouse_grabbed_by(self.thumb_ids[axis]) {
+            if mouse.primary_button.is_pressed() {
+                gui.input.grab_mouse(self.thumb_ids[axis]);
+                *scroll = scroll_at(along(axis, mouse.position) - self.drag_offset);
+            }
+        } else if let Some(mouse) = gui.input.get_mouse(|pos| bar.contains(pos)) {
+            if mouse.primary_button == ButtonState::JustPressed {
+                if thumb.contains(mouse.position) {
+                    gui.input.grab_mouse(self.thumb_ids[axis]);
+                    self.drag_offset = along(axis, mouse.position) - thumb_start;
+                } else if along(axis, mouse.position) < thumb_start {
+                    // Clicking the bar outside of the thumb scrolls by a page.
+                    *scroll -= view_length;
+                } else {
+                    *scroll += view_length;
+                }
+            }
+        }
+    }
+
+    fn pan_input(&mut self, gui: &mut Gui, view: Rect<f64>, dt: f64, user_scrollable: [bool; 2], scroll: &mut [f64; 2]) {
+        let pan = self.pan.and_then(|(id, prev_position)| {
+            gui.input.touches.iter().find(|touch| touch.id == id).map(|&touch| (touch, prev_position))
+        });
+
+        let delta = if let Some((touch, prev_position)) = pan {
+            // Content follows the finger.
+            let delta = prev_position - touch.position;
+
+            if dt > 0.0 {
+                self.velocity = self.velocity*0.5 + delta*(0.5/dt);
+            }
+
+            self.pan = if touch.state.is_pressed() { Some((touch.id, touch.position)) } else { None };
+            delta
+        } else if let Some(&touch) = gui.input.touches.iter().find(|touch| touch.state == ButtonState::JustPressed && view.contains(touch.position)) {
+            self.pan = Some((touch.id, touch.position));
+            self.velocity = Vec2::zero();
+            Vec2::zero()
+        } else {
+            self.pan = None;
+
+            let delta = self.velocity*dt;
+            self.velocity *= MOMENTUM_DECAY.powf(dt);
+            if self.velocity.norm() < MIN_SPEED {
+                self.velocity = Vec2::zero();
+            } else {
+                gui.input.request_animation_frame();
+            }
+            delta
+        };
+
+        let delta = [delta.x, delta.y];
+        for axis in 0..2 {
+            if user_scrollable[axis] {
+                scroll[axis] += delta[axis];
+            }
+        }
+    }
+
+    /// Thumb is as much shorter than the bar as the view is shorter than the content.
+    fn place_thumb(&self, gui: &mut Gui, axis: usize, view_length: f64, content_length: f64, max_scroll: f64) {
+        let bar = Rect::from(self.bar_ids[axis]);
+        let thumb = Rect::from(self.thumb_ids[axis]);
+        let bar_length = along(axis, gui.layout.prev_value_rect(bar).size());
+
+        let length = if content_length > 0.0 { bar_length*view_length/content_length } else { bar_length };
+        let length = length.max(MIN_THUMB_LENGTH).min(bar_length);
+        let offset = if max_scroll > 0.0 { (bar_length - length)*along(axis, self.scroll)/max_scroll } else { 0.0 };
+
+        if axis == 0 {
+            add_constraints!(gui.layout, [
+                (thumb.top) == bar.top,
+                (thumb.bottom) == bar.bottom,
+                (thumb.left) == bar.left + offset,
+                (thumb.right) == thumb.left + length,
+            ]);
+        } else {
+            add_constraints!(gui.layout, [
+                (thumb.left) == bar.left,
+                (thumb.right) == bar.right,
+                (thumb.top) == bar.top + offset,
+                (thumb.bottom) == thumb.top + length,
+            ]);
+        }
+    }
+}
+
+fn along(axis: usize, v: Vec2<f64>) -> f64 {
+    if axis == 0 { v.x } else { v.y }
+}
+
+/// Smallest scroll which moves the range `start..end` inside of the view, preferring its start.
+fn reveal_offset(view_start: f64, view_end: f64, start: f64, end: f64) -> f64 {
+    if start < view_start {
+        start - view_start
+    } else if end > view_end {
+        (end - view_end).min(start - view_start)
+    } else {
+        0.0
+    }
+}
+
+#[test]
+fn test_scroll_view() {
+    use std::sync::Arc;
+    use Theme;
+    use theme::{ElementStyle, StyleVariant};
+    use gui::input::{Event, MouseButton, WheelDelta};
+
+    struct Tall {
+        id: Id,
+        row: Id,
+    }
+
+    impl Widget for Tall {
+        fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+            let (place, row) = (Rect::from(self.id), Rect::from(self.row));
+            gui.element(self.id, element_kind!("Tall"), |_gui| {});
+            add_constraints!(gui.layout, [
+                (place.bottom) == place.top + 400.0,
+                (row.top) == place.top + 300.0,
+                (row.bottom) == place.top + 340.0,
+            ]);
+            place
+        }
+    }
+
+    let mut theme = Theme::empty();
+    theme.element_styles.insert((StyleVariant::default(), element_kind!("ScrollView")), Arc::new(ElementStyle {
+        overflow: Vec2::new(Overflow::Hidden, Overflow::Auto),
+        ..ElementStyle::default()
+    }));
+
+    let mut gui = Gui::new(theme);
+    let mut view = ScrollView::new(Tall { id: Id::unique(), row: Id::unique() });
+
+    let frame = |gui: &mut Gui, view: &mut ScrollView<Tall>, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = view.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 100.0,
+            (place.bottom) == 100.0,
+        ]);
+
+        gui.advance();
+    };
+
+    frame(&mut gui, &mut view, &[]);
+    frame(&mut gui, &mut view, &[]);
+
+    frame(&mut gui, &mut view, &[Event::MouseEntered, Event::MouseMoved(Vec2::new(50.0, 50.0)), Event::MouseWheel(WheelDelta::Lines(Vec2::new(0.0, -2.0)))]);
+    assert_eq!(view.scroll(), Vec2::new(0.0, 80.0));
+    assert_eq!(gui.layout.prev_value(view.content_place.unwrap().top), -80.0);
+
+    // Row at 220..260 is moved to the bottom of the view.
+    view.scroll_into_view(view.content.row);
+    frame(&mut gui, &mut view, &[]);
+    assert_eq!(view.scroll(), Vec2::new(0.0, 240.0));
+
+    // Thumb of a quarter of the bar is at 60..85, dragging it by 45 pixels scrolls by 180.
+    frame(&mut gui, &mut view, &[Event::MouseMoved(Vec2::new(95.0, 70.0)), Event::MouseButton(MouseButton::Primary, true)]);
+    frame(&mut gui, &mut view, &[Event::MouseMoved(Vec2::new(95.0, 25.0))]);
+    assert_eq!(view.scroll(), Vec2::new(0.0, 60.0));
+
+    view.set_scroll(Vec2::new(0.0, 1000.0));
+    frame(&mut gui, &mut view, &[Event::MouseButton(MouseButton::Primary, false)]);
+    assert_eq!(view.scroll(), Vec2::new(0.0, 300.0));
+}
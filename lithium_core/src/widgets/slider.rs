@@ -0,0 +1,531 @@
+use {Id, Gui, Rect, Var, Vec2};
+use gui::input::{ButtonState, Key, Direction};
+use super::Widget;
+
+/// Distance in pixels of touchpad scrolling which changes the value by one step.
+const WHEEL_STEP: f64 = 20.0;
+
+/// Number of steps changed by PageUp and PageDown.
+const PAGE_STEPS: f64 = 10.0;
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Orientation {
+    Horizontal,
+
+    /// Minimum is at the bottom.
+    Vertical,
+}
+
+/// Range of values shared by `Slider` and `RangeSlider`.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct SliderRange {
+    pub min: f64,
+    pub max: f64,
+
+    /// Values are rounded to `min + step*n`. Zero step allows any value.
+    pub step: f64,
+}
+
+/// Control for choosing a value from a range by moving a thumb along a track.
+///
+/// The value can be changed by dragging the thumb, clicking the track, scrolling the mouse wheel over it and by the arrow
+/// keys along the slider as well as PageUp, PageDown, Home and End while focused.
+///
+/// Consists of the `Slider` element containing the track with the fill which extends from the minimum to the thumb and the
+/// square thumb. The parts are `Slider` elements styled with the `track`, `fill` and `thumb` style variants
+/// (`Slider.track`, `Slider.fill` and `Slider.thumb` in CSS), see `Gui::part`.
+pub struct Slider {
+    id: Id,
+    track_id: Id,
+    fill_id: Id,
+    thumb_id: Id,
+
+    value: f64,
+    changed: bool,
+
+    /// Offset of the grabbed point from the thumb center while it is being dragged.
+    drag_offset: f64,
+
+    pub range: SliderRange,
+    pub orientation: Orientation,
+}
+
+/// Slider with two thumbs for choosing a range of values.
+///
+/// Uses the same elements as `Slider` with two thumbs, the fill extends between them. Each thumb can be focused
+/// separately to be moved with the keyboard.
+pub struct RangeSlider {
+    id: Id,
+    track_id: Id,
+    fill_id: Id,
+    thumb_ids: [Id; 2],
+
+    values: [f64; 2],
+    changed: bool,
+
+    /// Thumb which is being dragged and the offset of the grabbed point from its center.
+    drag: Option<(usize, f64)>,
+
+    pub range: SliderRange,
+    pub orientation: Orientation,
+}
+
+impl SliderRange {
+    pub fn new(min: f64, max: f64, step: f64) -> Self {
+        SliderRange { min, max, step }
+    }
+
+    /// Clamp the `value` to the range and round it to the step.
+    pub fn snap(&self, value: f64) -> f64 {
+        let value = value.max(self.min).min(self.max);
+
+        if self.step > 0.0 {
+            (self.min + ((value - self.min)/self.step).round()*self.step).min(self.max)
+        } else {
+            value
+        }
+    }
+
+    /// Step used by the keyboard and the wheel. If the range is continuous it is one hundredth of the range.
+    pub fn key_step(&self) -> f64 {
+        if self.step > 0.0 { self.step } else { (self.max - self.min)/100.0 }
+    }
+
+    fn fraction(&self, value: f64) -> f64 {
+        if self.max > self.min { (value - self.min)/(self.max - self.min) } else { 0.0 }
+    }
+
+    fn value_at(&self, t: f64) -> f64 {
+        self.min + (self.max - self.min)*t
+    }
+}
+
+impl Widget for Slider {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let place_value = gui.layout.prev_value_rect(place);
+        let thumb_value = gui.layout.prev_value_rect(Rect::from(self.thumb_id));
+        let orientation = self.orientation;
+
+        gui.focusable(self.id);
+
+        let mut value = self.value;
+
+        if let Some(mouse) = gui.input.mouse_grabbed_by(self.id) {
+            if mouse.primary_button.is_pressed() {
+                gui.input.grab_mouse(self.id);
+                value = self.range.value_at(position_fraction(place_value, thumb_value, orientation, mouse.position, self.drag_offset));
+            }
+        } else if let Some(mouse) = gui.input.get_mouse(|pos| place_value.contains(pos)) {
+            if mouse.primary_button == ButtonState::JustPressed {
+                gui.input.grab_mouse(self.id);
+                gui.focus(self.id);
+
+                // Grabbing the thumb keeps it under the same point of the pointer, clicking the track moves it there.
+                self.drag_offset = if thumb_value.contains(mouse.position) { along(orientation, mouse.position - thumb_value.center()) } else { 0.0 };
+                value = self.range.value_at(position_fraction(place_value, thumb_value, orientation, mouse.position, self.drag_offset));
+            }
+
+        }
+
+        if let Some(steps) = wheel_steps(gui, place_value) {
+            value += steps*self.range.key_step();
+        }
+
+        if gui.is_focused(self.id) {
+            value += keyboard_steps(gui, orientation)*self.range.key_step();
+            value = keyboard_edges(gui, self.range).unwrap_or(value);
+        }
+
+        self.set_value_by_user(value);
+
+        let t = self.range.fraction(self.value);
+        let (track_id, fill_id, thumb_id) = (self.track_id, self.fill_id, self.thumb_id);
+
+        gui.element(self.id, element_kind!("Slider"), |gui| {
+            gui.part(track_id, element_kind!("Slider"), style_variant!("track"), &[], |gui| {
+                gui.part(fill_id, element_kind!("Slider"), style_variant!("fill"), &[], |_gui| {});
+            });
+            gui.part(thumb_id, element_kind!("Slider"), style_variant!("thumb"), &[], |_gui| {});
+
+            let (track, fill, thumb) = (Rect::from(track_id), Rect::from(fill_id), Rect::from(thumb_id));
+            ::layout::equal(gui, place, track);
+            place_thumb(gui, place, thumb, orientation, t);
+            place_fill(gui, place, fill, orientation, None, Some(thumb));
+        });
+
+        place
+    }
+}
+
+impl Slider {
+    pub fn new(range: SliderRange) -> Self {
+        Slider {
+            id: Id::unique(),
+            track_id: Id::unique(),
+            fill_id: Id::unique(),
+            thumb_id: Id::unique(),
+
+            value: range.min,
+            changed: false,
+            drag_offset: 0.0,
+
+            range,
+            orientation: Orientation::Horizontal,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Set the value, it is clamped to the range and rounded to the step.
+    pub fn set_value(&mut self, value: f64) {
+        self.value = self.range.snap(value);
+    }
+
+    /// Value was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+
+    fn set_value_by_user(&mut self, value: f64) {
+        let value = self.range.snap(value);
+        if value != self.value {
+            self.value = value;
+            self.changed = true;
+        }
+    }
+}
+
+impl Widget for RangeSlider {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let place_value = gui.layout.prev_value_rect(place);
+        let thumb_values = [
+            gui.layout.prev_value_rect(Rect::from(self.thumb_ids[0])),
+            gui.layout.prev_value_rect(Rect::from(self.thumb_ids[1])),
+        ];
+        let orientation = self.orientation;
+
+        gui.focusable(self.thumb_ids[0]);
+        gui.focusable(self.thumb_ids[1]);
+
+        let mut values = self.values;
+
+        if let Some(mouse) = gui.input.mouse_grabbed_by(self.id) {
+            if let (true, Some((thumb, offset))) = (mouse.primary_button.is_pressed(), self.drag) {
+                gui.input.grab_mouse(self.id);
+                values[thumb] = self.range.value_at(position_fraction(place_value, thumb_values[thumb], orientation, mouse.position, offset));
+            }
+        } else if let Some(mouse) = gui.input.get_mouse(|pos| place_value.contains(pos)) {
+            let thumb = closest_thumb(thumb_values, orientation, mouse.position);
+
+            if mouse.primary_button == ButtonState::JustPressed {
+                gui.input.grab_mouse(self.id);
+                gui.focus(self.thumb_ids[thumb]);
+
+                let offset = if thumb_values[thumb].contains(mouse.position) { along(orientation, mouse.position - thumb_values[thumb].center()) } else { 0.0 };
+                self.drag = Some((thumb, offset));
+                values[thumb] = self.range.value_at(position_fraction(place_value, thumb_values[thumb], orientation, mouse.position, offset));
+            }
+        }
+
+        if let Some(steps) = wheel_steps(gui, place_value) {
+            let thumb = gui.input.mouse_position().map_or(0, |position| closest_thumb(thumb_values, orientation, position));
+            values[thumb] += steps*self.range.key_step();
+        }
+
+        for (value, &thumb_id) in values.iter_mut().zip(&self.thumb_ids) {
+            if gui.is_focused(thumb_id) {
+                *value += keyboard_steps(gui, orientation)*self.range.key_step();
+                *value = keyboard_edges(gui, self.range).unwrap_or(*value);
+            }
+        }
+
+        // Thumbs cannot pass each other.
+        let low = self.range.snap(values[0].min(self.values[1]));
+        let high = self.range.snap(values[1].max(low));
+        if [low, high] != self.values {
+            self.values = [low, high];
+            self.changed = true;
+        }
+
+        let t = [self.range.fraction(self.values[0]), self.range.fraction(self.values[1])];
+        let (track_id, fill_id, thumb_ids) = (self.track_id, self.fill_id, self.thumb_ids);
+
+        gui.element(self.id, element_kind!("Slider"), |gui| {
+            gui.part(track_id, element_kind!("Slider"), style_variant!("track"), &[], |gui| {
+                gui.part(fill_id, element_kind!("Slider"), style_variant!("fill"), &[], |_gui| {});
+            });
+            gui.part(thumb_ids[0], element_kind!("Slider"), style_variant!("thumb"), &[], |_gui| {});
+            gui.part(thumb_ids[1], element_kind!("Slider"), style_variant!("thumb"), &[], |_gui| {});
+
+            let (track, fill) = (Rect::from(track_id), Rect::from(fill_id));
+            let thumbs = [Rect::from(thumb_ids[0]), Rect::from(thumb_ids[1])];
+            ::layout::equal(gui, place, track);
+            place_thumb(gui, place, thumbs[0], orientation, t[0]);
+            place_thumb(gui, place, thumbs[1], orientation, t[1]);
+            place_fill(gui, place, fill, orientation, Some(thumbs[0]), Some(thumbs[1]));
+        });
+
+        place
+    }
+}
+
+impl RangeSlider {
+    pub fn new(range: SliderRange) -> Self {
+        RangeSlider {
+            id: Id::unique(),
+            track_id: Id::unique(),
+            fill_id: Id::unique(),
+            thumb_ids: [Id::unique(), Id::unique()],
+
+            values: [range.min, range.max],
+            changed: false,
+            drag: None,
+
+            range,
+            orientation: Orientation::Horizontal,
+        }
+    }
+
+    /// Identifiers of the thumbs which can be focused.
+    pub fn thumb_ids(&self) -> [Id; 2] {
+        self.thumb_ids
+    }
+
+    /// Lower and upper values.
+    pub fn values(&self) -> (f64, f64) {
+        (self.values[0], self.values[1])
+    }
+
+    /// Set the values, they are clamped to the range, rounded to the step and swapped if `low` is greater than `high`.
+    pub fn set_values(&mut self, low: f64, high: f64) {
+        let (low, high) = (self.range.snap(low.min(high)), self.range.snap(low.max(high)));
+        self.values = [low, high];
+    }
+
+    /// Values were changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+}
+
+fn along(orientation: Orientation, v: Vec2<f64>) -> f64 {
+    match orientation {
+        Orientation::Horizontal => v.x,
+        Orientation::Vertical => -v.y,
+    }
+}
+
+/// Position of the thumb center between the minimum and the maximum if its grabbed point is moved to the `position`.
+fn position_fraction(place: Rect<f64>, thumb: Rect<f64>, orientation: Orientation, position: Vec2<f64>, offset: f64) -> f64 {
+    let (start, length, thumb_length) = match orientation {
+        Orientation::Horizontal => (place.left, place.width(), thumb.width()),
+        Orientation::Vertical => (-place.bottom, place.height(), thumb.height()),
+    };
+
+    let travel = length - thumb_length;
+    if travel <= 0.0 {
+        return 0.0;
+    }
+
+    ((along(orientation, position) - offset - start - thumb_length*0.5)/travel).max(0.0).min(1.0)
+}
+
+/// The thumb which follows the pointer is the one closest to it.
+fn closest_thumb(thumbs: [Rect<f64>; 2], orientation: Orientation, position: Vec2<f64>) -> usize {
+    let distance = |thumb: Rect<f64>| along(orientation, position - thumb.center()).abs();
+    if distance(thumbs[1]) < distance(thumbs[0]) { 1 } else { 0 }
+}
+
+/// Steps made by the wheel over the slider, scrolling up or to the right increases the value.
+fn wheel_steps(gui: &mut Gui, place: Rect<f64>) -> Option<f64> {
+    gui.input.take_wheel(WHEEL_STEP, |pos| place.contains(pos)).map(|wheel| (wheel.x + wheel.y)/WHEEL_STEP)
+}
+
+/// Steps made by the arrow keys along the slider and by PageUp and PageDown.
+fn keyboard_steps(gui: &mut Gui, orientation: Orientation) -> f64 {
+    let (decrease, increase) = match orientation {
+        Orientation::Horizontal => (Key::Left, Key::Right),
+        Orientation::Vertical => (Key::Down, Key::Up),
+    };
+
+    // Arrows along the slider change the value instead of moving the focus.
+    match orientation {
+        Orientation::Horizontal => {
+            gui.input.take_navigation(Direction::Left);
+            gui.input.take_navigation(Direction::Right);
+        }
+        Orientation::Vertical => {
+            gui.input.take_navigation(Direction::Up);
+            gui.input.take_navigation(Direction::Down);
+        }
+    }
+
+    let keyboard = &gui.input.keyboard;
+    let steps = keyboard.presses(increase) as f64 - keyboard.presses(decrease) as f64;
+    let pages = keyboard.presses(Key::PageUp) as f64 - keyboard.presses(Key::PageDown) as f64;
+
+    steps + pages*PAGE_STEPS
+}
+
+/// Home and End move the value to the minimum and the maximum.
+fn keyboard_edges(gui: &Gui, range: SliderRange) -> Option<f64> {
+    if gui.input.keyboard.presses(Key::Home) > 0 {
+        Some(range.min)
+    } else if gui.input.keyboard.presses(Key::End) > 0 {
+        Some(range.max)
+    } else {
+        None
+    }
+}
+
+/// Square thumb as thick as the slider placed at `t` between the ends of the slider.
+fn place_thumb(gui: &mut Gui, place: Rect<Var>, thumb: Rect<Var>, orientation: Orientation, t: f64) {
+    match orientation {
+        Orientation::Horizontal => {
+            add_constraints!(gui.layout, [
+                (thumb.top) == place.top,
+                (thumb.bottom) == place.bottom,
+                (thumb.right - thumb.left) == thumb.bottom - thumb.top,
+                (thumb.left) == place.left*(1.0 - t) + place.right*t - (thumb.right - thumb.left)*t,
+            ]);
+        }
+        Orientation::Vertical => {
+            add_constraints!(gui.layout, [
+                (thumb.left) == place.left,
+                (thumb.right) == place.right,
+                (thumb.bottom - thumb.top) == thumb.right - thumb.left,
+                (thumb.bottom) == place.bottom*(1.0 - t) + place.top*t + (thumb.bottom - thumb.top)*t,
+            ]);
+        }
+    }
+}
+
+/// Fill between the centers of the thumbs, missing thumb means the start or the end of the slider.
+fn place_fill(gui: &mut Gui, place: Rect<Var>, fill: Rect<Var>, orientation: Orientation, from: Option<Rect<Var>>, to: Option<Rect<Var>>) {
+    match orientation {
+        Orientation::Horizontal => {
+            add_constraints!(gui.layout, [
+                (fill.top) == place.top,
+                (fill.bottom) == place.bottom,
+            ]);
+
+            match from {
+                Some(from) => { add_constraints!(gui.layout, [(fill.left) == (from.left + from.right)*0.5]); }
+                None => { add_constraints!(gui.layout, [(fill.left) == place.left]); }
+            }
+
+            match to {
+                Some(to) => { add_constraints!(gui.layout, [(fill.right) == (to.left + to.right)*0.5]); }
+                None => { add_constraints!(gui.layout, [(fill.right) == place.right]); }
+            }
+        }
+        Orientation::Vertical => {
+            add_constraints!(gui.layout, [
+                (fill.left) == place.left,
+                (fill.right) == place.right,
+            ]);
+
+            match from {
+                Some(from) => { add_constraints!(gui.layout, [(fill.bottom) == (from.top + from.bottom)*0.5]); }
+                None => { add_constraints!(gui.layout, [(fill.bottom) == place.bottom]); }
+            }
+
+            match to {
+                Some(to) => { add_constraints!(gui.layout, [(fill.top) == (to.top + to.bottom)*0.5]); }
+                None => { add_constraints!(gui.layout, [(fill.top) == place.top]); }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_slider_input() {
+    use Theme;
+    use gui::input::{Event, KeyEvent, ScanCode, MouseButton, WheelDelta};
+
+    let mut gui = Gui::new(Theme::empty());
+    let mut slider = Slider::new(SliderRange::new(0.0, 100.0, 10.0));
+
+    let frame = |gui: &mut Gui, slider: &mut Slider, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = slider.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 110.0,
+            (place.bottom) == 10.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+
+    frame(&mut gui, &mut slider, &[]);
+    frame(&mut gui, &mut slider, &[]);
+
+    // Track is clicked at 55% of the way between the thumb positions at the ends.
+    frame(&mut gui, &mut slider, &[Event::MouseEntered, Event::MouseMoved(Vec2::new(60.0, 5.0)), Event::MouseButton(MouseButton::Primary, true)]);
+    assert_eq!(slider.value(), 60.0);
+    assert!(slider.changed());
+
+    frame(&mut gui, &mut slider, &[Event::MouseButton(MouseButton::Primary, false), key(Key::Right, true)]);
+    assert_eq!(slider.value(), 70.0);
+
+    frame(&mut gui, &mut slider, &[key(Key::Right, false), key(Key::End, true)]);
+    assert_eq!(slider.value(), 100.0);
+
+    frame(&mut gui, &mut slider, &[key(Key::End, false), Event::MouseWheel(WheelDelta::Lines(Vec2::new(0.0, -2.0)))]);
+    assert_eq!(slider.value(), 80.0);
+
+    let thumb = gui.layout.prev_value_rect(Rect::from(slider.thumb_id));
+    assert!((thumb.left - 80.0).abs() < 1e-6 && (thumb.right - 90.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_slider_parts_style() {
+    use std::sync::Arc;
+    use Theme;
+    use theme::ElementStyle;
+    use gui::scene::Command;
+
+    let mut theme = Theme::empty();
+    let mut style = |variant, kind, opacity| {
+        theme.element_styles.insert((variant, kind), Arc::new(ElementStyle { opacity, ..ElementStyle::default() }));
+    };
+    style(style_variant!("default"), element_kind!("Slider"), 0.5);
+    style(style_variant!("track"), element_kind!("Slider"), 0.25);
+    style(style_variant!("thumb"), element_kind!("Slider"), 0.125);
+
+    let mut gui = Gui::new(theme);
+    let mut slider = Slider::new(SliderRange::new(0.0, 100.0, 10.0));
+
+    let opacities = |gui: &mut Gui, slider: &mut Slider| {
+        slider.appear(gui);
+        let opacities: Vec<_> = gui.scene.commands().iter().filter_map(|command| match *command {
+            Command::CloseElement(ref element) => Some((element.id, element.style.opacity)),
+            _ => None,
+        }).collect();
+        gui.advance();
+        opacities
+    };
+
+    // The fill has no style of its own and the rule for the whole slider doesn't apply to it.
+    let (id, track_id, fill_id, thumb_id) = (slider.id, slider.track_id, slider.fill_id, slider.thumb_id);
+    assert_eq!(opacities(&mut gui, &mut slider), vec![(fill_id, 1.0), (track_id, 0.25), (thumb_id, 0.125), (id, 0.5)]);
+}
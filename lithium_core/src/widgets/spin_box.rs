@@ -0,0 +1,383 @@
+use {Id, Gui, Rect, Var};
+use gui::input::{ButtonState, Key, Direction};
+use super::{Widget, TextInput, SliderRange};
+
+/// Width of the increment and decrement buttons.
+const BUTTON_WIDTH: f64 = 16.0;
+
+/// Distance in pixels of touchpad scrolling which changes the value by one step.
+const WHEEL_STEP: f64 = 20.0;
+
+/// Number of steps changed by PageUp and PageDown.
+const PAGE_STEPS: f64 = 10.0;
+
+/// Distance the pointer should move while a button is held before the value follows the pointer.
+const DRAG_THRESHOLD: f64 = 4.0;
+
+/// Distance the pointer is dragged for each step.
+const DRAG_STEP: f64 = 5.0;
+
+/// Field for entering a number.
+///
+/// The number can be typed, changed by the increment and decrement buttons, by the mouse wheel over the field and by
+/// Up, Down, PageUp and PageDown while focused. Holding a button and dragging the pointer up or down adjusts the value
+/// continuously. The value is kept in the `range`, rounded to its step and displayed with `precision` decimal digits
+/// followed by the `unit`.
+///
+/// Typed text is parsed while it is edited, the unit may be omitted. Text which is not a number in the range switches
+/// the whole spin box to the `error` style variant (`SpinBox.error` and `TextInput.error` in CSS) until it is corrected,
+/// Escape or moving the focus away restores the text of the value. The `SpinBox` element contains the `TextInput`
+/// followed by the `SpinBoxUp` and `SpinBoxDown` buttons which are `disabled` at the ends of the range.
+pub struct SpinBox {
+    id: Id,
+    up_id: Id,
+    down_id: Id,
+    input: TextInput,
+
+    value: f64,
+    valid: bool,
+    changed: bool,
+    was_focused: bool,
+
+    /// Button being held, the pointer position where it was pressed, the value at that moment and whether the value is
+    /// being dragged.
+    hold: Option<(Id, f64, f64, bool)>,
+
+    pub range: SliderRange,
+    /// Number of digits after the decimal point.
+    pub precision: usize,
+    /// Text shown after the number, for example `" px"` or `"%"`.
+    pub unit: String,
+}
+
+impl Widget for SpinBox {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let place_value = gui.layout.prev_value_rect(place);
+        let input_id = self.input.id();
+
+        let mut value = self.value;
+
+        if let Some(steps) = gui.input.take_wheel(WHEEL_STEP, |pos| place_value.contains(pos)).map(|wheel| (wheel.x + wheel.y)/WHEEL_STEP) {
+            value += steps*self.range.key_step();
+        }
+
+        if gui.is_focused(input_id) {
+            // Up and Down change the value instead of moving the focus.
+            gui.input.take_navigation(Direction::Up);
+            gui.input.take_navigation(Direction::Down);
+
+            let keyboard = &gui.input.keyboard;
+            let steps = keyboard.presses(Key::Up) as f64 - keyboard.presses(Key::Down) as f64;
+            let pages = keyboard.presses(Key::PageUp) as f64 - keyboard.presses(Key::PageDown) as f64;
+            value += (steps + pages*PAGE_STEPS)*self.range.key_step();
+        }
+
+        value = self.buttons_input(gui, value);
+
+        if value != self.value {
+            self.set_value_by_user(value);
+        }
+
+        let (id, up_id, down_id, valid) = (self.id, self.up_id, self.down_id, self.valid);
+        let up_state = if self.value >= self.range.max { vec![pseudo_class!("disabled")] } else { vec![] };
+        let down_state = if self.value <= self.range.min { vec![pseudo_class!("disabled")] } else { vec![] };
+        let input = &mut self.input;
+        let mut input_place = None;
+
+        {
+            let mut build = |gui: &mut Gui| {
+                gui.element(id, element_kind!("SpinBox"), |gui| {
+                    input_place = Some(input.appear(gui));
+                    gui.element_in_state(up_id, element_kind!("SpinBoxUp"), &up_state, |_gui| {});
+                    gui.element_in_state(down_id, element_kind!("SpinBoxDown"), &down_state, |_gui| {});
+                });
+            };
+
+            if valid {
+                build(gui);
+            } else {
+                gui.styled(style_variant!("error"), |gui| build(gui));
+            }
+        }
+
+        self.handle_text(gui);
+
+        let (input_place, up, down) = (input_place.unwrap(), Rect::from(up_id), Rect::from(down_id));
+        add_constraints!(gui.layout, [
+            (input_place.left) == place.left,
+            (input_place.top) == place.top,
+            (input_place.bottom) == place.bottom,
+            (up.left) == input_place.right,
+            (down.left) == input_place.right,
+            (up.right) == place.right,
+            (down.right) == place.right,
+            (up.right - up.left) == BUTTON_WIDTH,
+            (up.top) == place.top,
+            (down.bottom) == place.bottom,
+            (up.bottom) == down.top,
+            (up.bottom - up.top) == down.bottom - down.top,
+        ]);
+
+        place
+    }
+}
+
+impl SpinBox {
+    pub fn new(range: SliderRange) -> Self {
+        let mut spin_box = SpinBox {
+            id: Id::unique(),
+            up_id: Id::unique(),
+            down_id: Id::unique(),
+            input: TextInput::new(),
+
+            value: range.min,
+            valid: true,
+            changed: false,
+            was_focused: false,
+            hold: None,
+
+            range,
+            precision: 0,
+            unit: String::new(),
+        };
+
+        spin_box.show_value();
+        spin_box
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn input_id(&self) -> Id {
+        self.input.id()
+    }
+
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Set the value, it is clamped to the range and rounded to the step and the precision.
+    pub fn set_value(&mut self, value: f64) {
+        self.value = self.round(value);
+        self.show_value();
+    }
+
+    /// Typed text is a number in the range.
+    pub fn is_valid(&self) -> bool {
+        self.valid
+    }
+
+    pub fn text(&self) -> &str {
+        self.input.text()
+    }
+
+    /// Value was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+
+    /// Text of the value with the unit.
+    pub fn format(&self, value: f64) -> String {
+        format!("{:.*}{}", self.precision, value, self.unit)
+    }
+
+    /// Number in the text if it is in the range. The unit after the number is optional.
+    pub fn parse(&self, text: &str) -> Option<f64> {
+        let text = text.trim();
+        let unit = self.unit.trim();
+        let number = if !unit.is_empty() && text.ends_with(unit) { &text[..text.len() - unit.len()] } else { text };
+
+        number.trim().parse::<f64>().ok()
+            .and_then(|value| if value >= self.range.min && value <= self.range.max { Some(value) } else { None })
+    }
+
+    fn round(&self, value: f64) -> f64 {
+        let value = self.range.snap(value);
+        let scale = 10f64.powi(self.precision as i32);
+        (value*scale).round()/scale
+    }
+
+    fn set_value_by_user(&mut self, value: f64) {
+        let value = self.round(value);
+        if value != self.value {
+            self.value = value;
+            self.changed = true;
+        }
+        self.show_value();
+    }
+
+    fn show_value(&mut self) {
+        let text = self.format(self.value);
+        self.input.set_text(text);
+        self.valid = true;
+    }
+
+    /// Pressing a button makes one step, dragging the pointer up or down while it is held makes a step per `DRAG_STEP`.
+    fn buttons_input(&mut self, gui: &mut Gui, mut value: f64) -> f64 {
+        let step = self.range.key_step();
+
+        for &(id, direction) in &[(self.up_id, 1.0), (self.down_id, -1.0)] {
+            let button = gui.layout.prev_value_rect(Rect::from(id));
+
+            if let Some(mouse) = gui.input.mouse_grabbed_by(id) {
+                if !mouse.primary_button.is_pressed() {
+                    self.hold = None;
+                    continue;
+                }
+
+                gui.input.grab_mouse(id);
+
+                if let Some((held, start_y, start_value, dragging)) = self.hold {
+                    let distance = start_y - mouse.position.y;
+                    if held == id && (dragging || distance.abs() >= DRAG_THRESHOLD) {
+                        self.hold = Some((id, start_y, start_value, true));
+                        value = start_value + (distance/DRAG_STEP).round()*step;
+                    }
+                }
+            } else if let Some(mouse) = gui.input.get_mouse(|pos| button.contains(pos)) {
+                if mouse.primary_button == ButtonState::JustPressed {
+                    gui.input.grab_mouse(id);
+                    gui.focus(self.input.id());
+
+                    value = self.round(value + direction*step);
+                    self.hold = Some((id, mouse.position.y, value, false));
+                }
+            }
+        }
+
+        value
+    }
+
+    /// Parse the text edited by the user, restore it on Escape and when the focus leaves.
+    fn handle_text(&mut self, gui: &Gui) {
+        let focused = gui.is_focused(self.input.id());
+
+        if self.input.changed() {
+            let parsed = self.parse(self.input.text());
+            self.valid = parsed.is_some();
+
+            if let Some(value) = parsed {
+                let value = self.round(value);
+                if value != self.value {
+                    self.value = value;
+                    self.changed = true;
+                }
+            }
+        }
+
+        let keyboard = &gui.input.keyboard;
+        let commit = focused && self.valid && keyboard.key_state(Key::Return) == ButtonState::JustPressed;
+        let cancel = focused && keyboard.key_state(Key::Escape) == ButtonState::JustPressed;
+
+        if commit || cancel || (self.was_focused && !focused) {
+            self.show_value();
+        }
+
+        self.was_focused = focused;
+    }
+}
+
+#[test]
+fn test_spin_box() {
+    use std::sync::Arc;
+    use {Theme, Vec2};
+    use theme::ElementStyle;
+    use super::text::MonospaceFont;
+    use gui::input::{Event, MouseButton, KeyEvent, ScanCode, WheelDelta};
+    use gui::scene::Command;
+
+    let mut theme = Theme::empty();
+    theme.element_styles.insert((Default::default(), element_kind!("TextInput")), Arc::new(ElementStyle {
+        font: Arc::new(MonospaceFont::default()),
+        ..ElementStyle::default()
+    }));
+    theme.element_styles.insert((style_variant!("error"), element_kind!("SpinBox")), Arc::new(ElementStyle {
+        opacity: 0.5,
+        ..ElementStyle::default()
+    }));
+
+    let mut gui = Gui::new(theme);
+    let mut spin_box = SpinBox::new(SliderRange::new(0.0, 10.0, 0.5));
+    spin_box.precision = 1;
+    spin_box.unit = " px".to_owned();
+    spin_box.set_value(2.0);
+    assert_eq!(spin_box.text(), "2.0 px");
+
+    // Returns whether the spin box is drawn with the error style.
+    let frame = |gui: &mut Gui, spin_box: &mut SpinBox, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = spin_box.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 100.0,
+        ]);
+
+        let error = gui.scene.commands().iter().any(|command| match *command {
+            Command::CloseElement(ref element) => element.id == spin_box.id() && element.style.opacity == 0.5,
+            _ => false,
+        });
+
+        gui.advance();
+        error
+    };
+
+    let press = |x, y| vec![Event::MouseMoved(Vec2::new(x, y)), Event::MouseButton(MouseButton::Primary, true)];
+    let release = || vec![Event::MouseButton(MouseButton::Primary, false)];
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+
+    frame(&mut gui, &mut spin_box, &[Event::MouseEntered]);
+    frame(&mut gui, &mut spin_box, &[]);
+
+    // Buttons step the value.
+    frame(&mut gui, &mut spin_box, &press(95.0, 5.0));
+    frame(&mut gui, &mut spin_box, &release());
+    assert_eq!(spin_box.value(), 2.5);
+    assert_eq!(spin_box.text(), "2.5 px");
+    assert!(spin_box.changed());
+
+    frame(&mut gui, &mut spin_box, &press(95.0, 15.0));
+    frame(&mut gui, &mut spin_box, &release());
+    assert_eq!(spin_box.value(), 2.0);
+
+    // Dragging from a held button adjusts the value continuously.
+    frame(&mut gui, &mut spin_box, &press(95.0, 5.0));
+    frame(&mut gui, &mut spin_box, &[Event::MouseMoved(Vec2::new(95.0, -15.0))]);
+    frame(&mut gui, &mut spin_box, &release());
+    assert_eq!(spin_box.value(), 4.5);
+
+    // The wheel and the keys step the value, which stays in the range.
+    frame(&mut gui, &mut spin_box, &[Event::MouseMoved(Vec2::new(50.0, 10.0)), Event::MouseWheel(WheelDelta::Pixels(Vec2::new(0.0, 40.0)))]);
+    assert_eq!(spin_box.value(), 5.5);
+    frame(&mut gui, &mut spin_box, &[key(Key::PageUp, true)]);
+    frame(&mut gui, &mut spin_box, &[key(Key::PageUp, false)]);
+    assert_eq!(spin_box.value(), 10.0);
+    frame(&mut gui, &mut spin_box, &[key(Key::Down, true)]);
+    frame(&mut gui, &mut spin_box, &[key(Key::Down, false)]);
+    assert_eq!(spin_box.value(), 9.5);
+
+    // Typed text is taken while it is valid and the error variant is used while it is not.
+    let text_len = spin_box.text().len();
+    spin_box.input.set_selection(0..text_len);
+    gui.input.ime_commit("7.2");
+    assert!(!frame(&mut gui, &mut spin_box, &[]));
+    assert!(spin_box.is_valid());
+    assert_eq!(spin_box.value(), 7.0);
+
+    frame(&mut gui, &mut spin_box, &[Event::Char('x')]);
+    assert!(!spin_box.is_valid());
+    assert!(frame(&mut gui, &mut spin_box, &[]));
+    assert_eq!(spin_box.value(), 7.0);
+
+    frame(&mut gui, &mut spin_box, &[key(Key::Escape, true)]);
+    assert!(!frame(&mut gui, &mut spin_box, &[key(Key::Escape, false)]));
+    assert!(spin_box.is_valid());
+    assert_eq!(spin_box.text(), "7.0 px");
+}
@@ -0,0 +1,456 @@
+use std::time::Duration;
+use {Id, Gui, Rect, Var, Vec2};
+use gui::input::{ButtonState, Key, Direction};
+use solver::Strength;
+use super::{Widget, Orientation};
+
+/// Distance in pixels a focused divider is moved by one press of an arrow key.
+const KEY_STEP: f64 = 10.0;
+
+/// Longest time in milliseconds between two presses on a divider which form a double-click.
+const DOUBLE_CLICK_MS: u64 = 500;
+
+/// Panes placed side by side with dividers between them which can be dragged to resize the panes.
+///
+/// With the `Vertical` orientation the panes are stacked from the top to the bottom. Each pane has a weight which is its
+/// share of the space, the weights are updated when the user moves a divider so `ratios` can be saved and restored with
+/// `set_ratios`. Panes are kept between their minimum and maximum sizes. A double-click on a divider or Enter while it
+/// is focused collapses the adjacent pane or restores the collapsed one; arrow keys move a focused divider.
+///
+/// The `Splitter` element contains `SplitterPane` elements with the pane widgets and `SplitterDivider`s in the
+/// `horizontal` or `vertical` state.
+pub struct Splitter {
+    id: Id,
+    panes: Vec<Pane>,
+    divider_ids: Vec<Id>,
+
+    /// Divider which is being dragged and the offset of the grabbed point from its start.
+    drag: Option<(usize, f64)>,
+    /// Divider pressed last and the time of the press to detect double-clicks.
+    last_press: Option<(usize, Duration)>,
+    /// Weights should be updated from the pane sizes of the previous frame.
+    sync_weights: bool,
+    changed: bool,
+
+    pub orientation: Orientation,
+    pub divider_size: f64,
+}
+
+pub struct Pane {
+    id: Id,
+    weight: f64,
+    collapsed: bool,
+
+    pub widget: Box<Widget>,
+    pub min_size: f64,
+    pub max_size: Option<f64>,
+    pub collapsible: bool,
+}
+
+impl Widget for Splitter {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+
+        if self.sync_weights {
+            self.sync_weights = self.drag.is_some();
+            self.update_weights(gui);
+        }
+
+        let target = self.handle_input(gui);
+
+        let orientation = self.orientation;
+        let divider_size = self.divider_size;
+        let state = [match orientation {
+            Orientation::Horizontal => pseudo_class!("horizontal"),
+            Orientation::Vertical => pseudo_class!("vertical"),
+        }];
+
+        let total_weight: f64 = self.panes.iter().filter(|pane| !pane.collapsed).map(|pane| pane.weight).sum();
+        let (place_start, place_end, place_cross_start, place_cross_end) = axes(place, orientation);
+        let available = place_end - place_start - divider_size*self.divider_ids.len() as f64;
+
+        let panes = &mut self.panes;
+        let divider_ids = &self.divider_ids;
+
+        gui.element(self.id, element_kind!("Splitter"), |gui| {
+            let mut previous_end = place_start;
+
+            for (i, pane) in panes.iter_mut().enumerate() {
+                let pane_place = Rect::from(pane.id);
+                let (start, end, cross_start, cross_end) = axes(pane_place, orientation);
+                let (widget, collapsed) = (&mut pane.widget, pane.collapsed);
+
+                gui.element(pane.id, element_kind!("SplitterPane"), |gui| {
+                    // Collapsed panes add nothing to the layout.
+                    if !collapsed {
+                        let widget_place = widget.appear(gui);
+                        ::layout::equal(gui, pane_place, widget_place);
+                    }
+                });
+
+                add_constraints!(gui.layout, [
+                    (start) == previous_end,
+                    (cross_start) == place_cross_start,
+                    (cross_end) == place_cross_end,
+                ]);
+
+                gui.layout.with_strength(Strength::Strong, |layout| {
+                    if collapsed {
+                        add_constraints!(layout, [(end - start) == 0.0]);
+                    } else {
+                        add_constraints!(layout, [(end - start) >= pane.min_size.max(0.0)]);
+                        if let Some(max_size) = pane.max_size {
+                            add_constraints!(layout, [(end - start) <= max_size]);
+                        }
+                    }
+                });
+
+                if !collapsed && total_weight > 0.0 {
+                    let share = pane.weight/total_weight;
+                    gui.layout.with_strength(Strength::Weak, |layout| {
+                        add_constraints!(layout, [(end - start) == available*share]);
+                    });
+                }
+
+                match divider_ids.get(i) {
+                    Some(&divider_id) => {
+                        gui.focusable(divider_id);
+                        gui.element_in_state(divider_id, element_kind!("SplitterDivider"), &state, |_gui| {});
+
+                        let (divider_start, divider_end, divider_cross_start, divider_cross_end) = axes(Rect::from(divider_id), orientation);
+                        add_constraints!(gui.layout, [
+                            (divider_start) == end,
+                            (divider_end - divider_start) == divider_size,
+                            (divider_cross_start) == place_cross_start,
+                            (divider_cross_end) == place_cross_end,
+                        ]);
+
+                        if let Some((moved, position)) = target {
+                            if moved == i {
+                                add_constraints!(gui.layout, [(divider_start) == position]);
+                            }
+                        }
+
+                        previous_end = divider_end;
+                    }
+                    None => { add_constraints!(gui.layout, [(place_end) == end]); }
+                }
+            }
+        });
+
+        place
+    }
+}
+
+impl Splitter {
+    pub fn new(orientation: Orientation) -> Self {
+        Splitter {
+            id: Id::unique(),
+            panes: Vec::new(),
+            divider_ids: Vec::new(),
+
+            drag: None,
+            last_press: None,
+            sync_weights: false,
+            changed: false,
+
+            orientation,
+            divider_size: 5.0,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Add a pane at the end, returns its index. Panes added together share the space equally.
+    pub fn add<W: Widget + 'static>(&mut self, widget: W) -> usize {
+        if !self.panes.is_empty() {
+            self.divider_ids.push(Id::unique());
+        }
+
+        self.panes.push(Pane {
+            id: Id::unique(),
+            weight: 1.0,
+            collapsed: false,
+
+            widget: Box::new(widget),
+            min_size: 0.0,
+            max_size: None,
+            collapsible: true,
+        });
+
+        self.panes.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.panes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.panes.is_empty()
+    }
+
+    pub fn pane(&self, index: usize) -> &Pane {
+        &self.panes[index]
+    }
+
+    pub fn pane_mut(&mut self, index: usize) -> &mut Pane {
+        &mut self.panes[index]
+    }
+
+    /// Id of the divider after the pane `index`.
+    pub fn divider_id(&self, index: usize) -> Id {
+        self.divider_ids[index]
+    }
+
+    /// Shares of the space of the panes in order, they sum to one. Collapsed panes keep the share they had before.
+    pub fn ratios(&self) -> Vec<f64> {
+        let total: f64 = self.panes.iter().map(|pane| pane.weight).sum();
+        self.panes.iter().map(|pane| if total > 0.0 { pane.weight/total } else { 0.0 }).collect()
+    }
+
+    /// Restore the shares saved with `ratios`. Missing values are left unchanged.
+    pub fn set_ratios(&mut self, ratios: &[f64]) {
+        for (pane, &ratio) in self.panes.iter_mut().zip(ratios) {
+            pane.weight = ratio.max(0.0);
+        }
+    }
+
+    pub fn is_collapsed(&self, index: usize) -> bool {
+        self.panes[index].collapsed
+    }
+
+    pub fn set_collapsed(&mut self, index: usize, collapsed: bool) {
+        self.panes[index].collapsed = collapsed;
+    }
+
+    /// Panes were resized, collapsed or restored by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+
+    /// Handle dragging, double-clicks and the keyboard. Returns the divider moved by the user and its new start.
+    fn handle_input(&mut self, gui: &mut Gui) -> Option<(usize, f64)> {
+        let orientation = self.orientation;
+        let mut target = None;
+
+        for i in 0..self.divider_ids.len() {
+            let divider_id = self.divider_ids[i];
+            let divider = gui.layout.prev_value_rect(Rect::from(divider_id));
+            let divider_start = axes(divider, orientation).0;
+
+            if let Some(mouse) = gui.input.mouse_grabbed_by(divider_id) {
+                if mouse.primary_button.is_pressed() {
+                    gui.input.grab_mouse(divider_id);
+                    if let Some((dragged, offset)) = self.drag {
+                        if dragged == i {
+                            target = Some((i, along(orientation, mouse.position) - offset));
+                        }
+                    }
+                } else {
+                    self.drag = None;
+                }
+            } else if let Some(mouse) = gui.input.get_mouse(|pos| divider.contains(pos)) {
+                if mouse.primary_button == ButtonState::JustPressed {
+                    gui.focus(divider_id);
+
+                    let time = gui.input.time();
+                    let double_click = match self.last_press {
+                        Some((pressed, pressed_at)) => pressed == i && time.checked_sub(pressed_at).map(|elapsed| elapsed <= Duration::from_millis(DOUBLE_CLICK_MS)) == Some(true),
+                        None => false,
+                    };
+
+                    if double_click {
+                        self.last_press = None;
+                        self.toggle_collapsed(i);
+                    } else {
+                        self.last_press = Some((i, time));
+                        self.drag = Some((i, along(orientation, mouse.position) - divider_start));
+                        gui.input.grab_mouse(divider_id);
+                    }
+                }
+            }
+
+            if gui.is_focused(divider_id) {
+                let (decrease, increase) = match orientation {
+                    Orientation::Horizontal => (Direction::Left, Direction::Right),
+                    Orientation::Vertical => (Direction::Up, Direction::Down),
+                };
+
+                // Arrows along the splitter move the divider instead of the focus.
+                gui.input.take_navigation(decrease);
+                gui.input.take_navigation(increase);
+
+                let (decrease, increase) = match orientation {
+                    Orientation::Horizontal => (Key::Left, Key::Right),
+                    Orientation::Vertical => (Key::Up, Key::Down),
+                };
+                let keyboard = &gui.input.keyboard;
+                let steps = keyboard.presses(increase) as f64 - keyboard.presses(decrease) as f64;
+
+                if steps != 0.0 {
+                    target = Some((i, divider_start + steps*KEY_STEP));
+                } else if keyboard.key_state(Key::Return) == ButtonState::JustPressed {
+                    self.toggle_collapsed(i);
+                }
+            }
+        }
+
+        if target.is_some() {
+            self.sync_weights = true;
+        }
+
+        target
+    }
+
+    /// Restore a collapsed pane next to the divider `index` or collapse the pane before it or else the one after it.
+    fn toggle_collapsed(&mut self, index: usize) {
+        let collapsed = (index..index + 2).find(|&i| self.panes[i].collapsed);
+        let collapsible = (index..index + 2).find(|&i| self.panes[i].collapsible);
+
+        match (collapsed, collapsible) {
+            (Some(i), _) => self.panes[i].collapsed = false,
+            (None, Some(i)) => self.panes[i].collapsed = true,
+            (None, None) => return,
+        }
+
+        self.changed = true;
+    }
+
+    /// Make the weights of the shown panes proportional to their sizes keeping the total weight of these panes.
+    fn update_weights(&mut self, gui: &Gui) {
+        let orientation = self.orientation;
+        let sizes: Vec<f64> = self.panes.iter().map(|pane| {
+            let (start, end, _, _) = axes(gui.layout.prev_value_rect(Rect::from(pane.id)), orientation);
+            end - start
+        }).collect();
+
+        let shown = || self.panes.iter().zip(&sizes).filter(|&(pane, _)| !pane.collapsed);
+        let total_size: f64 = shown().map(|(_, &size)| size).sum();
+        let total_weight: f64 = shown().map(|(pane, _)| pane.weight).sum();
+
+        if total_size <= 0.0 {
+            return;
+        }
+
+        for (pane, size) in self.panes.iter_mut().zip(sizes) {
+            if !pane.collapsed && (pane.weight - size/total_size*total_weight).abs() > 1e-9 {
+                pane.weight = size/total_size*total_weight;
+                self.changed = true;
+            }
+        }
+    }
+}
+
+impl Pane {
+    pub fn id(&self) -> Id {
+        self.id
+    }
+}
+
+/// Start and end of the rect along the orientation followed by its start and end across it.
+fn axes<T>(rect: Rect<T>, orientation: Orientation) -> (T, T, T, T) {
+    match orientation {
+        Orientation::Horizontal => (rect.left, rect.right, rect.top, rect.bottom),
+        Orientation::Vertical => (rect.top, rect.bottom, rect.left, rect.right),
+    }
+}
+
+fn along(orientation: Orientation, v: Vec2<f64>) -> f64 {
+    match orientation {
+        Orientation::Horizontal => v.x,
+        Orientation::Vertical => v.y,
+    }
+}
+
+#[test]
+fn test_splitter() {
+    use Theme;
+    use gui::input::{Event, MouseButton, KeyEvent, ScanCode};
+    use super::Dummy;
+
+    let mut gui = Gui::new(Theme::empty());
+    let mut splitter = Splitter::new(Orientation::Horizontal);
+    splitter.add(Dummy::new());
+    splitter.add(Dummy::new());
+    splitter.pane_mut(1).min_size = 40.0;
+
+    let mut time = 0;
+    let mut frame = |gui: &mut Gui, splitter: &mut Splitter, events: &[Event]| {
+        time += 150;
+        gui.input.set_time(Duration::from_millis(time));
+
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = splitter.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 205.0,
+            (place.bottom) == 100.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let press = |x| vec![Event::MouseMoved(Vec2::new(x, 50.0)), Event::MouseButton(MouseButton::Primary, true)];
+    let release = || vec![Event::MouseButton(MouseButton::Primary, false)];
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+    let divider = |gui: &Gui, splitter: &Splitter| gui.layout.prev_value(Rect::from(splitter.divider_id(0)).left).round();
+
+    frame(&mut gui, &mut splitter, &[Event::MouseEntered]);
+    frame(&mut gui, &mut splitter, &[]);
+    assert_eq!(divider(&gui, &splitter), 100.0);
+
+    // Dragging keeps the grabbed point of the divider under the pointer.
+    frame(&mut gui, &mut splitter, &press(102.0));
+    frame(&mut gui, &mut splitter, &[Event::MouseMoved(Vec2::new(52.0, 50.0))]);
+    frame(&mut gui, &mut splitter, &release());
+    frame(&mut gui, &mut splitter, &[]);
+    assert_eq!(divider(&gui, &splitter), 50.0);
+    assert!(splitter.changed());
+    let ratios = splitter.ratios();
+    assert!((ratios[0] - 0.25).abs() < 1e-6);
+
+    // The second pane is kept at its minimum size.
+    frame(&mut gui, &mut splitter, &press(52.0));
+    frame(&mut gui, &mut splitter, &[Event::MouseMoved(Vec2::new(202.0, 50.0))]);
+    frame(&mut gui, &mut splitter, &release());
+    frame(&mut gui, &mut splitter, &[]);
+    assert_eq!(divider(&gui, &splitter), 160.0);
+
+    // Arrow keys move the focused divider.
+    frame(&mut gui, &mut splitter, &[key(Key::Left, true)]);
+    frame(&mut gui, &mut splitter, &[key(Key::Left, false)]);
+    assert_eq!(divider(&gui, &splitter), 150.0);
+
+    // Double-click collapses the first pane and restores it with the same ratio.
+    frame(&mut gui, &mut splitter, &press(152.0));
+    frame(&mut gui, &mut splitter, &release());
+    frame(&mut gui, &mut splitter, &press(152.0));
+    frame(&mut gui, &mut splitter, &release());
+    frame(&mut gui, &mut splitter, &[]);
+    assert!(splitter.is_collapsed(0));
+    assert_eq!(divider(&gui, &splitter), 0.0);
+
+    frame(&mut gui, &mut splitter, &[key(Key::Return, true)]);
+    frame(&mut gui, &mut splitter, &[key(Key::Return, false)]);
+    assert!(!splitter.is_collapsed(0));
+    assert_eq!(divider(&gui, &splitter), 150.0);
+
+    // Saved ratios can be restored.
+    splitter.set_ratios(&ratios);
+    frame(&mut gui, &mut splitter, &[]);
+    frame(&mut gui, &mut splitter, &[]);
+    assert_eq!(divider(&gui, &splitter), 50.0);
+
+    splitter.pane_mut(0).max_size = Some(30.0);
+    frame(&mut gui, &mut splitter, &[]);
+    frame(&mut gui, &mut splitter, &[]);
+    assert_eq!(divider(&gui, &splitter), 30.0);
+}
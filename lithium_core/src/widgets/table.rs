@@ -0,0 +1,632 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use {Id, Gui, Rect, Var};
+use gui::input::{ButtonState, Key, Direction};
+use solver::Strength;
+use theme::PseudoClass;
+use layout;
+use super::{Widget, VirtualList, ListModel, RowHeight};
+
+/// Distance from the right border of a header cell within which dragging resizes the column.
+const RESIZE_MARGIN: f64 = 4.0;
+
+/// Pointer has to move this far while dragging a header cell to move the column, otherwise it is a click.
+const DRAG_THRESHOLD: f64 = 4.0;
+
+/// Rows shown by a `Table`.
+pub trait TableModel {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Key of the row which stays the same when other rows are inserted or removed, see `ListModel::key`.
+    fn key(&self, row: usize) -> u64 {
+        row as u64
+    }
+
+    /// Create the widget showing the cell of the `row` in the `column`.
+    ///
+    /// Cell widgets are kept while their rows are visible and are created again when the rows become visible after
+    /// scrolling or after `Table::refresh`.
+    fn cell(&mut self, row: usize, column: usize) -> Box<Widget>;
+
+    /// Order of the rows `a` and `b` when the table is sorted by the `column`.
+    fn compare(&self, _column: usize, _a: usize, _b: usize) -> Ordering {
+        Ordering::Equal
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+pub struct Column {
+    id: Id,
+    sort_indicator_id: Id,
+
+    pub title: Box<Widget>,
+
+    /// Preferred width. Width of the column is a variable of the layout, so the preferred width gives way to the
+    /// minimum and maximum widths as well as to any other constraints of `Table::column_place`.
+    pub width: f64,
+    pub min_width: f64,
+    pub max_width: Option<f64>,
+
+    pub sortable: bool,
+    pub resizable: bool,
+}
+
+/// Table with a header which stays in place while the rows scroll.
+///
+/// Rows are shown by a `VirtualList` so only the visible cells appear. Clicking a header cell sorts the rows by the
+/// column, clicking it again reverses the order. Columns are resized by dragging the right border of their header cell
+/// and moved by dragging the rest of it. A cell is selected in the row with the cursor and is moved between the
+/// columns by the Left and Right keys or by clicking.
+///
+/// The table is the `Table` element containing the `TableHeader` with `TableHeaderCell`s and the rows which are
+/// `TableRow`s with `TableCell`s. The header cell of the sorted column has the `ascending` or `descending` pseudo-class
+/// and contains the `TableSortIndicator` in the same state. The selected cell has the `selected` pseudo-class.
+pub struct Table<M: TableModel> {
+    id: Id,
+    header_id: Id,
+    columns: Vec<Column>,
+
+    /// Indices of the columns in the order they are shown.
+    column_order: Vec<usize>,
+
+    list: VirtualList<TableRows<M>>,
+    sort: Option<(usize, SortOrder)>,
+    drag: Option<HeaderDrag>,
+
+    pub header_height: f64,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum HeaderDrag {
+    Resize { column: usize, start_x: f64, start_width: f64 },
+    Move { column: usize, start_x: f64, moved: bool },
+}
+
+struct TableRows<M: TableModel> {
+    model: M,
+
+    /// Rows of the model in the order they are shown.
+    rows: Vec<usize>,
+
+    /// Columns in the order they are shown and the ids of their header cells.
+    columns: Vec<(usize, Id)>,
+    cells: HashMap<(u64, usize), Box<Widget>>,
+
+    /// Selected cell is in the row with the cursor.
+    cursor: Option<usize>,
+    cell_column: Option<usize>,
+}
+
+impl<M: TableModel> Widget for Table<M> {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let header = Rect::from(self.header_id);
+
+        self.handle_header_mouse(gui);
+
+        if gui.is_focused(self.list.id()) {
+            self.handle_keyboard(gui);
+        }
+
+        if self.list.model.rows.len() != self.list.model.model.len() {
+            self.sort_rows();
+        }
+
+        let columns = &mut self.columns;
+        self.list.model.columns = self.column_order.iter().map(|&column| (column, columns[column].id)).collect();
+        self.list.model.cursor = self.list.cursor();
+
+        let (id, header_id, sort) = (self.id, self.header_id, self.sort);
+        let order = &self.column_order;
+        let list = &mut self.list;
+
+        gui.element(id, element_kind!("Table"), |gui| {
+            gui.element(header_id, element_kind!("TableHeader"), |gui| {
+                let mut previous: Option<Rect<Var>> = None;
+
+                for &column_index in order {
+                    let column = &mut columns[column_index];
+                    let cell = Rect::from(column.id);
+                    let sorted = sort.and_then(|(sorted, order)| if sorted == column_index { Some(order) } else { None });
+                    let pseudo_classes = sort_pseudo_classes(sorted);
+                    let sort_indicator_id = column.sort_indicator_id;
+                    let title = &mut column.title;
+
+                    gui.element_in_state(column.id, element_kind!("TableHeaderCell"), &pseudo_classes, |gui| {
+                        let title_place = title.appear(gui);
+                        add_constraints!(gui.layout, [(title_place.left) == cell.left]);
+                        layout::center_horizontal(gui, cell, title_place);
+
+                        if sorted.is_some() {
+                            let indicator = Rect::from(sort_indicator_id);
+                            gui.element_in_state(sort_indicator_id, element_kind!("TableSortIndicator"), &pseudo_classes, |_gui| {});
+
+                            add_constraints!(gui.layout, [
+                                (indicator.right) == cell.right,
+                                (indicator.right - indicator.left) == indicator.bottom - indicator.top,
+                            ]);
+                            layout::equal_horizontal(gui, cell, indicator);
+                        }
+                    });
+
+                    let left = previous.map_or(header.left, |previous| previous.right);
+                    add_constraints!(gui.layout, [
+                        (cell.left) == left,
+                        (cell.right - cell.left) >= column.min_width,
+                    ]);
+                    if let Some(max_width) = column.max_width {
+                        add_constraints!(gui.layout, [(cell.right - cell.left) <= max_width]);
+                    }
+
+                    let width = column.width;
+                    gui.layout.with_strength(Strength::Weak, |layout| {
+                        add_constraints!(layout, [(cell.right - cell.left) == width]);
+                    });
+
+                    layout::equal_horizontal(gui, header, cell);
+                    previous = Some(cell);
+                }
+            });
+
+            let list_place = list.appear(gui);
+            add_constraints!(gui.layout, [
+                (list_place.top) == header.bottom,
+                (list_place.bottom) == place.bottom,
+            ]);
+            layout::equal_vertical(gui, place, list_place);
+        });
+
+        add_constraints!(gui.layout, [
+            (header.top) == place.top,
+            (header.bottom) == place.top + self.header_height,
+        ]);
+        layout::equal_vertical(gui, place, header);
+
+        self.handle_cell_click(gui);
+
+        // Cells are kept only for the visible rows.
+        let visible: HashSet<u64> = self.list.visible_rows().iter().map(|&(_, key)| key).collect();
+        self.list.model.cells.retain(|&(key, _), _| visible.contains(&key));
+
+        place
+    }
+}
+
+impl<M: TableModel> Table<M> {
+    pub fn new(model: M, columns: Vec<Column>, row_height: f64) -> Self {
+        let mut list = VirtualList::new(TableRows {
+            model,
+            rows: Vec::new(),
+            columns: Vec::new(),
+            cells: HashMap::new(),
+            cursor: None,
+            cell_column: None,
+        }, RowHeight::Fixed(row_height));
+
+        list.kind = element_kind!("TableBody");
+        list.row_kind = element_kind!("TableRow");
+
+        Table {
+            id: Id::unique(),
+            header_id: Id::unique(),
+            column_order: (0..columns.len()).collect(),
+            columns,
+
+            list,
+            sort: None,
+            drag: None,
+
+            header_height: row_height,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn model(&self) -> &M {
+        &self.list.model.model
+    }
+
+    pub fn model_mut(&mut self) -> &mut M {
+        &mut self.list.model.model
+    }
+
+    pub fn column(&self, column: usize) -> &Column {
+        &self.columns[column]
+    }
+
+    pub fn column_mut(&mut self, column: usize) -> &mut Column {
+        &mut self.columns[column]
+    }
+
+    /// Place of the header cell of the `column`. Cells of the column have the same left and right edges.
+    pub fn column_place(&self, column: usize) -> Rect<Var> {
+        Rect::from(self.columns[column].id)
+    }
+
+    /// Indices of the columns in the order they are shown.
+    pub fn column_order(&self) -> &[usize] {
+        &self.column_order
+    }
+
+    pub fn set_column_order(&mut self, column_order: Vec<usize>) {
+        let mut sorted = column_order.clone();
+        sorted.sort();
+        assert!(sorted == (0..self.columns.len()).collect::<Vec<_>>(), "column order must contain each column once");
+        self.column_order = column_order;
+    }
+
+    pub fn sort(&self) -> Option<(usize, SortOrder)> {
+        self.sort
+    }
+
+    pub fn set_sort(&mut self, sort: Option<(usize, SortOrder)>) {
+        self.sort = sort;
+        self.sort_rows();
+    }
+
+    /// Row of the model shown at the `index` from the top.
+    pub fn row_at(&self, index: usize) -> usize {
+        self.list.model.rows[index]
+    }
+
+    /// Row of the model and the column of the selected cell.
+    pub fn selected_cell(&self) -> Option<(usize, usize)> {
+        match (self.list.cursor(), self.list.model.cell_column) {
+            (Some(index), Some(column)) => self.list.model.rows.get(index).map(|&row| (row, column)),
+            _ => None,
+        }
+    }
+
+    /// Scroll during the next frame so that the `row` of the model is visible.
+    pub fn scroll_to_row(&mut self, row: usize) {
+        if let Some(index) = self.list.model.rows.iter().position(|&shown| shown == row) {
+            self.list.scroll_to_index(index);
+        }
+    }
+
+    /// Sort the rows again and create all cell widgets again after the data of the model has changed.
+    pub fn refresh(&mut self) {
+        self.list.model.cells.clear();
+        self.sort_rows();
+    }
+
+    /// Selection of the rows was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        self.list.changed()
+    }
+
+    fn sort_rows(&mut self) {
+        let cursor_row = self.list.cursor().and_then(|index| self.list.model.rows.get(index).cloned());
+
+        let mut rows: Vec<usize> = (0..self.list.model.model.len()).collect();
+        if let Some((column, order)) = self.sort {
+            let model = &self.list.model.model;
+            rows.sort_by(|&a, &b| match order {
+                SortOrder::Ascending => model.compare(column, a, b),
+                SortOrder::Descending => model.compare(column, a, b).reverse(),
+            });
+        }
+        self.list.model.rows = rows;
+
+        // Cursor stays at the same row of the model.
+        if let Some(cursor_row) = cursor_row {
+            let index = self.list.model.rows.iter().position(|&row| row == cursor_row);
+            self.list.set_cursor(index);
+        }
+    }
+
+    fn toggle_sort(&mut self, column: usize) {
+        if !self.columns[column].sortable {
+            return;
+        }
+
+        let order = match self.sort {
+            Some((sorted, SortOrder::Ascending)) if sorted == column => SortOrder::Descending,
+            _ => SortOrder::Ascending,
+        };
+
+        self.set_sort(Some((column, order)));
+    }
+
+    fn handle_header_mouse(&mut self, gui: &mut Gui) {
+        if let Some(drag) = self.drag {
+            let mouse = match gui.input.mouse_grabbed_by(self.header_id) {
+                Some(mouse) => mouse,
+                None => {
+                    self.drag = None;
+                    return;
+                }
+            };
+
+            let x = mouse.position.x;
+            let released = !mouse.primary_button.is_pressed();
+
+            self.drag = match drag {
+                HeaderDrag::Resize { column, start_x, start_width } => {
+                    let column = &mut self.columns[column];
+                    column.width = (start_width + x - start_x).max(column.min_width);
+                    if let Some(max_width) = column.max_width {
+                        column.width = column.width.min(max_width);
+                    }
+                    Some(drag)
+                }
+                HeaderDrag::Move { column, start_x, moved } => {
+                    let moved = moved || (x - start_x).abs() >= DRAG_THRESHOLD;
+                    if moved {
+                        self.move_column_to(gui, column, x);
+                    } else if released {
+                        self.toggle_sort(column);
+                    }
+                    Some(HeaderDrag::Move { column, start_x, moved })
+                }
+            };
+
+            if released {
+                self.drag = None;
+            } else {
+                gui.input.grab_mouse(self.header_id);
+            }
+
+            return;
+        }
+
+        let header = gui.layout.prev_value_rect(Rect::from(self.header_id));
+        let mouse = match gui.input.get_mouse(|pos| header.contains(pos)) {
+            Some(mouse) => mouse,
+            None => return,
+        };
+
+        if mouse.primary_button != ButtonState::JustPressed {
+            return;
+        }
+
+        let x = mouse.position.x;
+        let cells: Vec<(usize, Rect<f64>)> = self.column_order.iter()
+            .map(|&column| (column, gui.layout.prev_value_rect(Rect::from(self.columns[column].id))))
+            .collect();
+
+        let resized = cells.iter().find(|&&(column, cell)| self.columns[column].resizable && (cell.right - x).abs() <= RESIZE_MARGIN);
+        let pressed = cells.iter().find(|&&(_, cell)| cell.left <= x && x < cell.right);
+
+        self.drag = if let Some(&(column, cell)) = resized {
+            Some(HeaderDrag::Resize { column, start_x: x, start_width: cell.width() })
+        } else if let Some(&(column, _)) = pressed {
+            Some(HeaderDrag::Move { column, start_x: x, moved: false })
+        } else {
+            None
+        };
+
+        if self.drag.is_some() {
+            gui.input.grab_mouse(self.header_id);
+        }
+    }
+
+    /// Move the dragged `column` in place of the one under the pointer once the pointer passes its center.
+    fn move_column_to(&mut self, gui: &Gui, column: usize, x: f64) {
+        let from = match self.column_order.iter().position(|&shown| shown == column) {
+            Some(from) => from,
+            None => return,
+        };
+
+        let target = self.column_order.iter().enumerate().find(|&(to, &shown)| {
+            let cell = gui.layout.prev_value_rect(Rect::from(self.columns[shown].id));
+            (to > from && x > cell.center().x && x < cell.right) || (to < from && x < cell.center().x && x >= cell.left)
+        }).map(|(to, _)| to);
+
+        if let Some(to) = target {
+            self.column_order.remove(from);
+            self.column_order.insert(to, column);
+        }
+    }
+
+    fn handle_keyboard(&mut self, gui: &mut Gui) {
+        // Left and Right move the selected cell instead of the focus.
+        gui.input.take_navigation(Direction::Left);
+        gui.input.take_navigation(Direction::Right);
+
+        if self.column_order.is_empty() {
+            return;
+        }
+
+        let keyboard = &gui.input.keyboard;
+        let steps = keyboard.presses(Key::Right) as isize - keyboard.presses(Key::Left) as isize;
+        if steps == 0 {
+            return;
+        }
+
+        let current = self.list.model.cell_column.and_then(|column| self.column_order.iter().position(|&shown| shown == column));
+        let position = match current {
+            Some(current) => (current as isize + steps).max(0).min(self.column_order.len() as isize - 1) as usize,
+            None => 0,
+        };
+
+        self.list.model.cell_column = Some(self.column_order[position]);
+    }
+
+    /// Clicked cell is found after the rows appeared, so the mouse is observed without taking it from them.
+    fn handle_cell_click(&mut self, gui: &mut Gui) {
+        let mouse = match gui.input.peek_mouse() {
+            Some(mouse) => mouse,
+            None => return,
+        };
+
+        let body = gui.layout.prev_value_rect(Rect::from(self.list.id()));
+        if mouse.primary_button != ButtonState::JustPressed || !body.contains(mouse.position) {
+            return;
+        }
+
+        let x = mouse.position.x;
+        let clicked = self.column_order.iter().cloned().find(|&column| {
+            let cell = gui.layout.prev_value_rect(Rect::from(self.columns[column].id));
+            cell.left <= x && x < cell.right
+        });
+
+        if clicked.is_some() {
+            self.list.model.cell_column = clicked;
+        }
+    }
+}
+
+impl Column {
+    pub fn new<T: Widget + 'static>(title: T, width: f64) -> Self {
+        Column {
+            id: Id::unique(),
+            sort_indicator_id: Id::unique(),
+
+            title: Box::new(title),
+
+            width,
+            min_width: 0.0,
+            max_width: None,
+
+            sortable: true,
+            resizable: true,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+}
+
+impl<M: TableModel> ListModel for TableRows<M> {
+    fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn key(&self, index: usize) -> u64 {
+        self.model.key(self.rows[index])
+    }
+
+    fn row(&mut self, gui: &mut Gui, index: usize, id: Id) {
+        let row = self.rows[index];
+        let key = self.model.key(row);
+        let place = Rect::from(id);
+
+        let (model, cells) = (&mut self.model, &mut self.cells);
+
+        for &(column, header_cell_id) in &self.columns {
+            let cell_id = id.child(column as u64);
+            let (cell, header_cell) = (Rect::from(cell_id), Rect::from(header_cell_id));
+
+            let selected = self.cursor == Some(index) && self.cell_column == Some(column);
+            let pseudo_classes = if selected { vec![pseudo_class!("selected")] } else { vec![] };
+            let widget = cells.entry((key, column)).or_insert_with(|| model.cell(row, column));
+
+            gui.element_in_state(cell_id, element_kind!("TableCell"), &pseudo_classes, |gui| {
+                let content = widget.appear(gui);
+                add_constraints!(gui.layout, [(content.left) == cell.left]);
+                layout::center_horizontal(gui, cell, content);
+            });
+
+            add_constraints!(gui.layout, [
+                (cell.left) == header_cell.left,
+                (cell.right) == header_cell.right,
+            ]);
+            layout::equal_horizontal(gui, place, cell);
+        }
+    }
+}
+
+fn sort_pseudo_classes(order: Option<SortOrder>) -> Vec<PseudoClass> {
+    match order {
+        Some(SortOrder::Ascending) => vec![pseudo_class!("ascending")],
+        Some(SortOrder::Descending) => vec![pseudo_class!("descending")],
+        None => vec![],
+    }
+}
+
+#[test]
+fn test_table() {
+    use {Theme, Vec2};
+    use gui::input::{Event, MouseButton};
+    use widgets::Dummy;
+
+    struct Numbers {
+        values: Vec<i32>,
+    }
+
+    impl TableModel for Numbers {
+        fn len(&self) -> usize {
+            self.values.len()
+        }
+
+        fn cell(&mut self, _row: usize, _column: usize) -> Box<Widget> {
+            Box::new(Dummy::new())
+        }
+
+        fn compare(&self, _column: usize, a: usize, b: usize) -> Ordering {
+            self.values[a].cmp(&self.values[b])
+        }
+    }
+
+    let mut gui = Gui::new(Theme::empty());
+    let mut table = Table::new(Numbers { values: vec![3, 1, 2] }, vec![Column::new(Dummy::new(), 50.0), Column::new(Dummy::new(), 50.0)], 20.0);
+    table.column_mut(0).max_width = Some(70.0);
+
+    let frame = |gui: &mut Gui, table: &mut Table<Numbers>, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = table.appear(gui);
+        let second_column = table.column_place(1);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 200.0,
+            (place.bottom) == 200.0,
+            (second_column.right - second_column.left) <= 40.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let press = |x, y| vec![Event::MouseMoved(Vec2::new(x, y)), Event::MouseButton(MouseButton::Primary, true)];
+    let release = || vec![Event::MouseButton(MouseButton::Primary, false)];
+
+    frame(&mut gui, &mut table, &[Event::MouseEntered]);
+    frame(&mut gui, &mut table, &[]);
+    let column_place = |gui: &Gui, table: &Table<Numbers>, column| gui.layout.prev_value_rect(table.column_place(column));
+    assert_eq!((column_place(&gui, &table, 1).left, column_place(&gui, &table, 1).right), (50.0, 90.0));
+
+    frame(&mut gui, &mut table, &press(25.0, 10.0));
+    frame(&mut gui, &mut table, &release());
+    assert_eq!(table.sort(), Some((0, SortOrder::Ascending)));
+    assert_eq!((table.row_at(0), table.row_at(2)), (1, 0));
+
+    frame(&mut gui, &mut table, &press(25.0, 10.0));
+    frame(&mut gui, &mut table, &release());
+    assert_eq!(table.sort(), Some((0, SortOrder::Descending)));
+    assert_eq!(table.row_at(0), 0);
+
+    // Border of the first column is dragged beyond its maximum width.
+    frame(&mut gui, &mut table, &press(49.0, 10.0));
+    frame(&mut gui, &mut table, &[Event::MouseMoved(Vec2::new(89.0, 10.0))]);
+    frame(&mut gui, &mut table, &release());
+    assert_eq!(table.column(0).width, 70.0);
+    assert_eq!(column_place(&gui, &table, 1).left, 70.0);
+
+    // Second column is dragged to the left past the center of the first one.
+    frame(&mut gui, &mut table, &press(90.0, 10.0));
+    frame(&mut gui, &mut table, &[Event::MouseMoved(Vec2::new(20.0, 10.0))]);
+    frame(&mut gui, &mut table, &release());
+    assert_eq!(table.column_order(), &[1, 0]);
+    assert_eq!(table.sort(), Some((0, SortOrder::Descending)));
+
+    // First column is now at 40..110, the click selects its cell in the first row.
+    frame(&mut gui, &mut table, &press(60.0, 30.0));
+    frame(&mut gui, &mut table, &release());
+    assert_eq!(table.selected_cell(), Some((0, 0)));
+}
@@ -0,0 +1,445 @@
+use {Id, Gui, Rect, Var};
+use gui::input::Key;
+use layout;
+use super::{Widget, ClickArea, activated_by_keyboard, modifiers};
+
+/// Distance the tab strip scrolls by for each line of the mouse wheel.
+const WHEEL_LINE: f64 = 40.0;
+
+/// Pages with a strip of tabs to switch between them.
+///
+/// Only the page of the active tab appears so hidden pages add nothing to the layout, but all of the pages keep their
+/// state. The `Tabs` element contains the `TabStrip` with `Tab`s followed by the `TabPage` showing the active page. The
+/// active tab is styled with the `active` style variant (`Tab.active` in CSS) and closable tabs contain a `TabClose`
+/// button. When the tabs don't fit the strip it scrolls with the mouse wheel and to show the active tab, the theme
+/// should give `TabStrip` `overflow: hidden`.
+///
+/// Tabs form a focus scope, while something inside of it is focused Ctrl+Tab and Ctrl+Shift+Tab switch to the next and
+/// previous tab.
+pub struct Tabs {
+    id: Id,
+    strip_id: Id,
+    page_id: Id,
+
+    tabs: Vec<Tab>,
+    active: Option<usize>,
+    closed: Vec<Tab>,
+    changed: bool,
+
+    /// Distance the strip is scrolled by from its start.
+    scroll: f64,
+    reveal_active: bool,
+
+    pub strip_height: f64,
+}
+
+pub struct Tab {
+    id: Id,
+    close_id: Id,
+    click_area: ClickArea,
+    close_area: ClickArea,
+
+    pub title: Box<Widget>,
+    pub page: Box<Widget>,
+    pub closable: bool,
+}
+
+impl Widget for Tabs {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let (id, strip_id, page_id) = (self.id, self.strip_id, self.page_id);
+        let (place, strip, page) = (Rect::from(id), Rect::from(strip_id), Rect::from(page_id));
+
+        self.scroll_strip(gui);
+
+        let active = self.active;
+        let scroll = self.scroll;
+        let tabs = &mut self.tabs;
+        let mut activated = None;
+        let mut closed = None;
+
+        gui.element(id, element_kind!("Tabs"), |gui| {
+            gui.focus_scope(id, |gui| {
+                gui.element(strip_id, element_kind!("TabStrip"), |gui| {
+                    let mut previous: Option<Rect<Var>> = None;
+
+                    for (i, tab) in tabs.iter_mut().enumerate() {
+                        let tab_place = tab.appear(gui, active == Some(i));
+
+                        if tab.close_area.clicked() {
+                            closed = Some(i);
+                        } else if tab.click_area.clicked() {
+                            gui.focus(tab.id);
+                            activated = Some(i);
+                        } else if activated_by_keyboard(gui, tab.id) {
+                            activated = Some(i);
+                        }
+
+                        match previous {
+                            Some(previous) => { add_constraints!(gui.layout, [(tab_place.left) == previous.right]); }
+                            None => { add_constraints!(gui.layout, [(tab_place.left) == strip.left - scroll]); }
+                        }
+                        add_constraints!(gui.layout, [
+                            (tab_place.top) == strip.top,
+                            (tab_place.bottom) == strip.bottom,
+                        ]);
+                        previous = Some(tab_place);
+                    }
+                });
+
+                gui.element(page_id, element_kind!("TabPage"), |gui| {
+                    if let Some(active) = active {
+                        let page_place = tabs[active].page.appear(gui);
+                        layout::equal(gui, page, page_place);
+                    }
+                });
+
+                // The focused element is known only after the active page appeared.
+                if gui.is_focused_within(id) && !tabs.is_empty() {
+                    let (shift, control) = modifiers(gui);
+                    let presses = gui.input.keyboard.presses(Key::Tab);
+
+                    if control && presses > 0 {
+                        let len = tabs.len();
+                        let current = active.unwrap_or(0);
+                        let next = if shift {
+                            (current + len - presses % len) % len
+                        } else {
+                            (current + presses) % len
+                        };
+
+                        gui.focus(tabs[next].id);
+                        activated = Some(next);
+                    }
+                }
+            });
+        });
+
+        add_constraints!(gui.layout, [
+            (strip.top) == place.top,
+            (strip.bottom) == place.top + self.strip_height,
+            (page.top) == strip.bottom,
+            (page.bottom) == place.bottom,
+        ]);
+        layout::equal_vertical(gui, place, strip);
+        layout::equal_vertical(gui, place, page);
+
+        if let Some(activated) = activated {
+            if self.active != Some(activated) {
+                self.active = Some(activated);
+                self.changed = true;
+                self.reveal_active = true;
+            }
+        }
+
+        if let Some(closed) = closed {
+            self.close(closed);
+        }
+
+        place
+    }
+}
+
+impl Tab {
+    fn appear(&mut self, gui: &mut Gui, active: bool) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let close_place = Rect::from(self.close_id);
+
+        gui.focusable(self.id);
+
+        let (id, close_id, closable) = (self.id, self.close_id, self.closable);
+        let click_area = &mut self.click_area;
+        let close_area = &mut self.close_area;
+        let title = &mut self.title;
+
+        let mut show = |gui: &mut Gui| {
+            gui.element(id, element_kind!("Tab"), |gui| {
+                // The close button is over the tab, so it gets the mouse first.
+                if closable {
+                    gui.element(close_id, element_kind!("TabClose"), |gui| {
+                        let close_area_place = close_area.appear(gui);
+                        layout::equal(gui, close_place, close_area_place);
+                    });
+                }
+
+                let click_area_place = click_area.appear(gui);
+                let title_place = title.appear(gui);
+
+                layout::equal(gui, place, click_area_place);
+                add_constraints!(gui.layout, [(title_place.left) == place.left]);
+                layout::center_horizontal(gui, place, title_place);
+
+                if closable {
+                    add_constraints!(gui.layout, [
+                        (close_place.left) == title_place.right,
+                        (close_place.right) == place.right,
+                        (close_place.right - close_place.left) == close_place.bottom - close_place.top,
+                    ]);
+                    layout::equal_horizontal(gui, place, close_place);
+                } else {
+                    add_constraints!(gui.layout, [(place.right) == title_place.right]);
+                }
+            });
+        };
+
+        if active {
+            gui.styled(style_variant!("active"), show);
+        } else {
+            show(gui);
+        }
+
+        place
+    }
+}
+
+impl Tabs {
+    pub fn new() -> Self {
+        Tabs {
+            id: Id::unique(),
+            strip_id: Id::unique(),
+            page_id: Id::unique(),
+
+            tabs: Vec::new(),
+            active: None,
+            closed: Vec::new(),
+            changed: false,
+
+            scroll: 0.0,
+            reveal_active: false,
+
+            strip_height: 30.0,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Add a tab at the end of the strip and return its index. The first added tab becomes active.
+    pub fn add<T: Widget + 'static, P: Widget + 'static>(&mut self, title: T, page: P) -> usize {
+        self.tabs.push(Tab {
+            id: Id::unique(),
+            close_id: Id::unique(),
+            click_area: ClickArea::new(),
+            close_area: ClickArea::new(),
+
+            title: Box::new(title),
+            page: Box::new(page),
+            closable: false,
+        });
+
+        if self.active.is_none() {
+            self.active = Some(0);
+        }
+
+        self.tabs.len() - 1
+    }
+
+    /// Remove the tab at the `index`. If it was active the following tab becomes active.
+    pub fn remove(&mut self, index: usize) -> Tab {
+        let tab = self.tabs.remove(index);
+
+        self.active = match self.active {
+            Some(active) if active > index => Some(active - 1),
+            Some(active) if active == index => {
+                self.reveal_active = true;
+                if self.tabs.is_empty() { None } else { Some(index.min(self.tabs.len() - 1)) }
+            }
+            active => active,
+        };
+
+        tab
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tabs.is_empty()
+    }
+
+    pub fn tab(&self, index: usize) -> &Tab {
+        &self.tabs[index]
+    }
+
+    pub fn tab_mut(&mut self, index: usize) -> &mut Tab {
+        &mut self.tabs[index]
+    }
+
+    /// Identifier of the tab element which can be used to focus it.
+    pub fn tab_id(&self, index: usize) -> Id {
+        self.tabs[index].id
+    }
+
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    pub fn set_active(&mut self, index: usize) {
+        assert!(index < self.tabs.len());
+        self.active = Some(index);
+        self.reveal_active = true;
+    }
+
+    /// Active tab was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+
+    /// Tabs closed by the user since the last call.
+    pub fn take_closed(&mut self) -> Vec<Tab> {
+        self.closed.drain(..).collect()
+    }
+
+    fn close(&mut self, index: usize) {
+        let was_active = self.active == Some(index);
+        let tab = self.remove(index);
+        self.closed.push(tab);
+
+        if was_active {
+            self.changed = true;
+        }
+    }
+
+    /// Scroll the strip by the mouse wheel or to show the active tab and keep the tabs filling it.
+    fn scroll_strip(&mut self, gui: &mut Gui) {
+        let strip = gui.layout.prev_value_rect(Rect::from(self.strip_id));
+
+        if let Some(wheel) = gui.input.take_wheel(WHEEL_LINE, |pos| strip.contains(pos)) {
+            self.scroll += wheel.x - wheel.y;
+        }
+
+        let (first, last) = match (self.tabs.first(), self.tabs.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => {
+                self.scroll = 0.0;
+                return;
+            }
+        };
+
+        if self.reveal_active {
+            if let Some(active) = self.active {
+                let tab = gui.layout.prev_value_rect(Rect::from(self.tabs[active].id));
+
+                // Newly added tab has no place yet, it is revealed after it appears.
+                if tab.width() > 0.0 {
+                    if tab.left < strip.left {
+                        self.scroll -= strip.left - tab.left;
+                    } else if tab.right > strip.right {
+                        self.scroll += (tab.right - strip.right).min(tab.left - strip.left);
+                    }
+                    self.reveal_active = false;
+                }
+            } else {
+                self.reveal_active = false;
+            }
+        }
+
+        let content_width = gui.layout.prev_value(Rect::from(last.id).right - Rect::from(first.id).left);
+        let max_scroll = (content_width - strip.width()).max(0.0);
+        self.scroll = self.scroll.max(0.0).min(max_scroll);
+    }
+}
+
+impl Default for Tabs {
+    fn default() -> Self {
+        Tabs::new()
+    }
+}
+
+#[test]
+fn test_tabs() {
+    use std::rc::Rc;
+    use std::cell::Cell;
+    use {Theme, Vec2};
+    use gui::input::{Event, MouseButton, KeyEvent, ScanCode};
+
+    /// Widget counting how many times it appeared, optionally with a fixed size.
+    struct Counted {
+        id: Id,
+        width: Option<f64>,
+        appeared: Rc<Cell<u32>>,
+    }
+
+    impl Widget for Counted {
+        fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+            let place = Rect::from(self.id);
+            self.appeared.set(self.appeared.get() + 1);
+            if let Some(width) = self.width {
+                add_constraints!(gui.layout, [
+                    (place.right - place.left) == width,
+                    (place.bottom - place.top) == 20.0,
+                ]);
+            }
+            place
+        }
+    }
+
+    let fixed = |width| Counted { id: Id::unique(), width: Some(width), appeared: Rc::new(Cell::new(0)) };
+    let counted = |counter: &Rc<Cell<u32>>| Counted { id: Id::unique(), width: None, appeared: counter.clone() };
+
+    let mut gui = Gui::new(Theme::empty());
+    let mut tabs = Tabs::new();
+    tabs.strip_height = 20.0;
+
+    let pages: Vec<Rc<Cell<u32>>> = (0..3).map(|_| Rc::new(Cell::new(0))).collect();
+    for page in &pages {
+        let index = tabs.add(fixed(60.0), counted(page));
+        tabs.tab_mut(index).closable = true;
+    }
+
+    let frame = |gui: &mut Gui, tabs: &mut Tabs, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = tabs.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 100.0,
+            (place.bottom) == 100.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let click = |x, y| vec![Event::MouseMoved(Vec2::new(x, y)), Event::MouseButton(MouseButton::Primary, true)];
+    let release = || vec![Event::MouseButton(MouseButton::Primary, false)];
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+    let tab_left = |gui: &Gui, tabs: &Tabs, index| gui.layout.prev_value(Rect::from(tabs.tab_id(index)).left);
+
+    frame(&mut gui, &mut tabs, &[Event::MouseEntered]);
+    frame(&mut gui, &mut tabs, &[]);
+    assert_eq!(tabs.active(), Some(0));
+    assert_eq!(tab_left(&gui, &tabs, 1), 80.0);
+
+    // The second tab is partially visible, after the click the strip scrolls to show it.
+    frame(&mut gui, &mut tabs, &click(85.0, 10.0));
+    frame(&mut gui, &mut tabs, &release());
+    frame(&mut gui, &mut tabs, &[]);
+    assert_eq!(tabs.active(), Some(1));
+    assert!(tabs.changed());
+    assert_eq!(tab_left(&gui, &tabs, 1), 20.0);
+
+    frame(&mut gui, &mut tabs, &[key(Key::LControl, true), key(Key::Tab, true)]);
+    frame(&mut gui, &mut tabs, &[key(Key::LControl, false), key(Key::Tab, false)]);
+    frame(&mut gui, &mut tabs, &[]);
+    assert_eq!(tabs.active(), Some(2));
+    assert_eq!(tab_left(&gui, &tabs, 2), 20.0);
+
+    // Close button of the last tab is at the right end of the strip.
+    let appeared = pages[1].get();
+    frame(&mut gui, &mut tabs, &click(90.0, 10.0));
+    frame(&mut gui, &mut tabs, &release());
+    assert_eq!(tabs.len(), 2);
+    assert_eq!(tabs.active(), Some(1));
+    assert_eq!(tabs.take_closed().len(), 1);
+
+    frame(&mut gui, &mut tabs, &[]);
+    assert_eq!(pages[1].get(), appeared + 1);
+    // The first page appeared only until the frame in which the second tab was clicked.
+    assert_eq!(pages[0].get(), 4);
+}
@@ -0,0 +1,149 @@
+//! Helpers shared by the text editing widgets.
+//!
+//! All offsets are byte offsets into the text which lie on character boundaries.
+
+use font::Segment;
+#[cfg(test)]
+use std::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(test)]
+use Font;
+
+pub fn prev_char(text: &str, offset: usize) -> usize {
+    text[..offset].char_indices().next_back().map_or(0, |(i, _)| i)
+}
+
+pub fn next_char(text: &str, offset: usize) -> usize {
+    text[offset..].chars().next().map_or(offset, |char| offset + char.len_utf8())
+}
+
+fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+/// Start of the word before the `offset`, skipping whitespace and punctuation.
+pub fn prev_word(text: &str, offset: usize) -> usize {
+    let mut chars = text[..offset].char_indices().rev().skip_while(|&(_, char)| !is_word_char(char)).peekable();
+    let mut start = chars.peek().map_or(0, |&(i, _)| i);
+
+    for (i, char) in chars {
+        if !is_word_char(char) {
+            break;
+        }
+        start = i;
+    }
+
+    start
+}
+
+/// End of the word after the `offset`, skipping whitespace and punctuation.
+pub fn next_word(text: &str, offset: usize) -> usize {
+    let mut chars = text[offset..].char_indices().skip_while(|&(_, char)| !is_word_char(char));
+
+    for (i, char) in &mut chars {
+        if !is_word_char(char) {
+            return offset + i;
+        }
+    }
+
+    text.len()
+}
+
+/// Horizontal position of the caret placed at the `offset`.
+///
+/// Offsets inside of a segment (for example a ligature) are interpolated.
+pub fn caret_position(segments: &[Segment], offset: usize) -> f64 {
+    for segment in segments {
+        if offset >= segment.byte_start && offset < segment.byte_end {
+            let t = (offset - segment.byte_start) as f64/(segment.byte_end - segment.byte_start) as f64;
+            return segment.position_start + (segment.position_end - segment.position_start)*t;
+        }
+    }
+
+    width(segments)
+}
+
+/// Offset of the segment boundary which is closest to the horizontal `position`.
+pub fn offset_at(segments: &[Segment], position: f64) -> usize {
+    let mut best: Option<(usize, f64)> = None;
+
+    for segment in segments {
+        for &(offset, x) in &[(segment.byte_start, segment.position_start), (segment.byte_end, segment.position_end)] {
+            let distance = (x - position).abs();
+            if best.map_or(true, |(_, best_distance)| distance < best_distance) {
+                best = Some((offset, distance));
+            }
+        }
+    }
+
+    best.map_or(0, |(offset, _)| offset)
+}
+
+pub fn width(segments: &[Segment]) -> f64 {
+    segments.iter().fold(0.0, |width: f64, segment| width.max(segment.position_end))
+}
+
+#[cfg(test)]
+pub fn monospace_segments(text: &str, advance: f64) -> Vec<Segment> {
+    use Vec2;
+
+    text.char_indices().enumerate().map(|(i, (byte, char))| Segment {
+        byte_start: byte,
+        byte_end: byte + char.len_utf8(),
+        position_start: i as f64*advance,
+        position_end: (i + 1) as f64*advance,
+        glyph_index: char as u32,
+        position: Vec2::new(i as f64*advance, 0.0),
+    }).collect()
+}
+
+/// Font for the tests whose glyphs are 10 pixels wide and lines 20 pixels high. It counts how many times it shaped text.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MonospaceFont {
+    pub shaped: AtomicUsize,
+}
+
+#[cfg(test)]
+impl Font for MonospaceFont {
+    fn shape(&self, text: &str, out: &mut Vec<Segment>) {
+        self.shaped.fetch_add(1, Ordering::SeqCst);
+        out.extend(monospace_segments(text, 10.0));
+    }
+
+    fn line_height(&self) -> f64 {
+        20.0
+    }
+}
+
+#[test]
+fn test_char_and_word_boundaries() {
+    let text = "héllo, wörld  foo_bar";
+
+    assert_eq!(prev_char(text, 3), 1);
+    assert_eq!(next_char(text, 1), 3);
+    assert_eq!(prev_char(text, 0), 0);
+    assert_eq!(next_char(text, text.len()), text.len());
+
+    assert_eq!(next_word(text, 0), 6);
+    assert_eq!(next_word(text, 6), 14);
+    assert_eq!(next_word(text, 14), text.len());
+    assert_eq!(prev_word(text, text.len()), 16);
+    assert_eq!(prev_word(text, 16), 8);
+    assert_eq!(prev_word(text, 8), 0);
+    assert_eq!(prev_word(text, 0), 0);
+}
+
+#[test]
+fn test_caret_positions() {
+    let segments = monospace_segments("aéb", 10.0);
+
+    assert_eq!(caret_position(&segments, 0), 0.0);
+    assert_eq!(caret_position(&segments, 3), 20.0);
+    assert_eq!(caret_position(&segments, 4), 30.0);
+    assert_eq!(caret_position(&[], 0), 0.0);
+
+    assert_eq!(offset_at(&segments, -5.0), 0);
+    assert_eq!(offset_at(&segments, 14.0), 1);
+    assert_eq!(offset_at(&segments, 16.0), 3);
+    assert_eq!(offset_at(&segments, 100.0), 4);
+}
@@ -0,0 +1,142 @@
+/// Single replacement of the text.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Edit {
+    pub offset: usize,
+    pub removed: String,
+    pub inserted: String,
+
+    /// Anchor and caret before the edit.
+    pub selection_before: (usize, usize),
+}
+
+/// Kind of the edit which decides whether it can be merged with the previous one.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum EditKind {
+    Typing,
+    Deleting,
+    Other,
+}
+
+/// Undo and redo stacks.
+///
+/// Consecutive typing or deleting is merged into a single edit so that undo reverts the whole word or sentence rather than
+/// a single character. Merging stops when the caret is moved, see `History::break_merging`.
+#[derive(Clone, Default, Debug)]
+pub struct History {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    last_kind: Option<EditKind>,
+}
+
+impl History {
+    pub fn record(&mut self, kind: EditKind, edit: Edit) {
+        self.redo.clear();
+
+        let merged = match (self.last_kind, self.undo.last_mut()) {
+            (Some(last_kind), Some(last)) if last_kind == kind => merge(kind, last, &edit),
+            _ => false,
+        };
+
+        if !merged {
+            self.undo.push(edit);
+        }
+
+        // Line breaks end the merged typing.
+        self.last_kind = if kind == EditKind::Other || self.undo.last().map_or(false, |edit| edit.inserted.ends_with('\n')) {
+            None
+        } else {
+            Some(kind)
+        };
+    }
+
+    /// Next edit will not be merged with the previous one.
+    pub fn break_merging(&mut self) {
+        self.last_kind = None;
+    }
+
+    /// Edit which should be reverted. It is moved to the redo stack.
+    pub fn undo(&mut self) -> Option<Edit> {
+        self.last_kind = None;
+        let edit = self.undo.pop()?;
+        self.redo.push(edit.clone());
+        Some(edit)
+    }
+
+    /// Edit which should be applied again. It is moved to the undo stack.
+    pub fn redo(&mut self) -> Option<Edit> {
+        self.last_kind = None;
+        let edit = self.redo.pop()?;
+        self.undo.push(edit.clone());
+        Some(edit)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_kind = None;
+    }
+}
+
+fn merge(kind: EditKind, last: &mut Edit, edit: &Edit) -> bool {
+    match kind {
+        EditKind::Typing if edit.removed.is_empty() && edit.offset == last.offset + last.inserted.len() => {
+            last.inserted += &edit.inserted;
+            true
+        }
+        EditKind::Deleting if edit.inserted.is_empty() && last.inserted.is_empty() => {
+            if edit.offset + edit.removed.len() == last.offset {
+                // Backspace.
+                last.offset = edit.offset;
+                last.removed = edit.removed.clone() + &last.removed;
+                true
+            } else if edit.offset == last.offset {
+                // Delete.
+                last.removed += &edit.removed;
+                true
+            } else {
+                false
+            }
+        }
+        _ => false,
+    }
+}
+
+#[test]
+fn test_history_merging() {
+    let edit = |offset, removed: &str, inserted: &str| Edit {
+        offset,
+        removed: removed.to_owned(),
+        inserted: inserted.to_owned(),
+        selection_before: (offset, offset),
+    };
+
+    let mut history = History::default();
+    history.record(EditKind::Typing, edit(0, "", "a"));
+    history.record(EditKind::Typing, edit(1, "", "b"));
+    history.record(EditKind::Typing, edit(2, "", "\n"));
+    history.record(EditKind::Typing, edit(3, "", "c"));
+    history.break_merging();
+    history.record(EditKind::Typing, edit(4, "", "d"));
+    history.record(EditKind::Deleting, edit(4, "d", ""));
+    history.record(EditKind::Deleting, edit(3, "c", ""));
+
+    assert_eq!(history.undo(), Some(Edit { selection_before: (4, 4), ..edit(3, "cd", "") }));
+    assert_eq!(history.undo(), Some(edit(4, "", "d")));
+    assert_eq!(history.undo(), Some(edit(3, "", "c")));
+    assert_eq!(history.redo(), Some(edit(3, "", "c")));
+    assert_eq!(history.undo(), Some(edit(3, "", "c")));
+    assert_eq!(history.undo(), Some(edit(0, "", "ab\n")));
+    assert_eq!(history.undo(), None);
+    assert!(history.can_redo());
+
+    history.record(EditKind::Other, edit(0, "", "x"));
+    assert!(!history.can_redo());
+}
@@ -0,0 +1,875 @@
+use std::cmp::{min, max};
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::cell::RefCell;
+use {Id, Gui, Rect, Var, Vec2, Font};
+use font::{Segment, ErrorFont};
+use gui::input::{ButtonState, Key, Direction};
+use gui::scene::{Text, Glyph};
+use layout;
+use super::Widget;
+use super::text;
+
+pub use self::piece_table::PieceTable;
+pub use self::history::{History, Edit, EditKind};
+
+mod piece_table;
+mod history;
+
+/// Multi-line text editor.
+///
+/// Lines are shaped when they become visible and only the visible rows are rendered, so documents of several megabytes
+/// stay responsive. Lines which were never shown are assumed to occupy a single row.
+///
+/// Like `TextInput` the editor consists of several elements: `TextEditorText` contains glyphs, `TextEditorSelection` is
+/// placed behind each selected row, `TextEditorPreedit` behind the text composed by the input method which is shown at
+/// the caret and `TextEditorCaret` is shown while the editor is focused.
+pub struct TextEditor {
+    id: Id,
+    text_id: Id,
+    caret_id: Id,
+    preedit_id: Id,
+    selection_ids: Vec<Id>,
+
+    buffer: PieceTable,
+
+    /// Selection is the range between the anchor and the caret. Both are byte offsets into the text.
+    caret: usize,
+    anchor: usize,
+
+    /// Horizontal position which the caret tries to keep when moving between rows.
+    goal_x: Option<f64>,
+
+    /// Vertical offset of the text.
+    scroll: f64,
+    history: History,
+    changed: bool,
+
+    /// Layout of each line or `None` if it was not shaped yet.
+    lines: Vec<Option<LineLayout>>,
+
+    /// First row of each line followed by the total number of rows. Only a prefix may be computed, see `row_starts`.
+    row_starts: Vec<usize>,
+    font: Arc<Font>,
+    wrap_width: Option<f64>,
+
+    /// Wrap lines which don't fit into the width of the editor.
+    pub wrap: bool,
+}
+
+struct LineLayout {
+    segments: Vec<Segment>,
+    rows: Vec<Row>,
+}
+
+/// Part of the line displayed on a single row when it is wrapped.
+struct Row {
+    segments: Range<usize>,
+
+    /// Byte range relative to the start of the line.
+    bytes: Range<usize>,
+
+    /// Position of the row start inside of the line.
+    x: f64,
+}
+
+impl Widget for TextEditor {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let place_value = gui.layout.prev_value_rect(place);
+        let font = gui.scene.element_style(element_kind!("TextEditor")).font.clone();
+        let line_height = font.line_height();
+
+        let wrap_width = if self.wrap { Some(place_value.width()) } else { None };
+        if !Arc::ptr_eq(&font, &self.font) || wrap_width != self.wrap_width {
+            self.font = font;
+            self.wrap_width = wrap_width;
+            self.invalidate(0..self.lines.len());
+        }
+
+        gui.focusable(self.id);
+        self.handle_mouse(gui, place_value, line_height);
+
+        let focused = gui.is_focused(self.id);
+        if focused {
+            let page = if line_height > 0.0 { max((place_value.height()/line_height) as usize, 1) } else { 1 };
+            self.handle_keyboard(gui, page);
+        }
+
+        let (caret_row, caret_x) = self.position_of(self.caret);
+        self.scroll_to(caret_row, line_height, place_value.height());
+        let caret_y = caret_row as f64*line_height - self.scroll;
+
+        // Text composed by the input method is shaped separately and shown at the caret, the rest of the row moves after
+        // it until the text is committed.
+        let preedit = if focused { gui.input.keyboard.composition().cloned() } else { None };
+        let preedit_segments = preedit.as_ref().map(|preedit| {
+            let mut segments = Vec::new();
+            self.font.shape(&preedit.text, &mut segments);
+            segments
+        });
+        let preedit_width = preedit_segments.as_ref().map(|segments| text::width(segments));
+
+        let mut glyphs = Vec::new();
+        let mut selection_rows = Vec::new();
+        let shift = preedit_width.map(|width| (self.caret, width));
+        self.visible_rows(line_height, place_value.height(), shift, &mut glyphs, &mut selection_rows);
+
+        while self.selection_ids.len() < selection_rows.len() {
+            self.selection_ids.push(Id::unique());
+        }
+
+        let preedit_x = preedit_width.map(|width| (caret_x, caret_x + width));
+        let (caret_x, show_caret) = match (preedit, preedit_segments.as_ref()) {
+            (Some(preedit), Some(segments)) => {
+                glyphs.extend(segments.iter().map(|segment| Glyph {
+                    position: segment.position + Vec2::new(caret_x, caret_y),
+                    scale: Vec2::new(1.0, 1.0),
+                    glyph_id: segment.glyph_index,
+                }));
+
+                let cursor = preedit.cursor.map_or(preedit.text.len(), |(start, _)| start);
+                (caret_x + text::caret_position(segments, cursor), preedit.cursor.is_some())
+            }
+            _ => (caret_x, focused),
+        };
+        let (text_id, caret_id, preedit_id) = (self.text_id, self.caret_id, self.preedit_id);
+        let selection_ids = &self.selection_ids;
+
+        gui.element(self.id, element_kind!("TextEditor"), |gui| {
+            for (&(left, right, y), &id) in selection_rows.iter().zip(selection_ids) {
+                let selection_place = Rect::from(id);
+                gui.element(id, element_kind!("TextEditorSelection"), |_gui| {});
+
+                add_constraints!(gui.layout, [
+                    (selection_place.left) == place.left + left,
+                    (selection_place.right) == place.left + right,
+                    (selection_place.top) == place.top + y,
+                    (selection_place.bottom) == place.top + y + line_height,
+                ]);
+            }
+
+            if let Some((preedit_start, preedit_end)) = preedit_x {
+                let preedit_place = Rect::from(preedit_id);
+                gui.element(preedit_id, element_kind!("TextEditorPreedit"), |_gui| {});
+
+                add_constraints!(gui.layout, [
+                    (preedit_place.left) == place.left + preedit_start,
+                    (preedit_place.right) == place.left + preedit_end,
+                    (preedit_place.top) == place.top + caret_y,
+                    (preedit_place.bottom) == place.top + caret_y + line_height,
+                ]);
+            }
+
+            let text_place = Rect::from(text_id);
+            gui.element(text_id, element_kind!("TextEditorText"), |gui| {
+                gui.scene.text(Text {
+                    id: text_id,
+                    glyphs: Rc::new(RefCell::new(glyphs)),
+                });
+            });
+            layout::equal(gui, place, text_place);
+
+            if show_caret {
+                let caret_place = Rect::from(caret_id);
+                gui.element(caret_id, element_kind!("TextEditorCaret"), |_gui| {});
+
+                add_constraints!(gui.layout, [
+                    (caret_place.left) == place.left + caret_x,
+                    (caret_place.right - caret_place.left) == 1.0,
+                    (caret_place.top) == place.top + caret_y,
+                    (caret_place.bottom - caret_place.top) == line_height,
+                ]);
+            }
+        });
+
+        if focused {
+            gui.input.set_ime_caret(Rect {
+                left: place_value.left + caret_x,
+                right: place_value.left + caret_x + 1.0,
+                top: place_value.top + caret_y,
+                bottom: place_value.top + caret_y + line_height,
+            });
+        }
+
+        place
+    }
+}
+
+impl TextEditor {
+    pub fn new() -> Self {
+        TextEditor {
+            id: Id::unique(),
+            text_id: Id::unique(),
+            caret_id: Id::unique(),
+            preedit_id: Id::unique(),
+            selection_ids: Vec::new(),
+
+            buffer: PieceTable::new(""),
+            caret: 0,
+            anchor: 0,
+            goal_x: None,
+            scroll: 0.0,
+            history: History::default(),
+            changed: false,
+
+            lines: vec![None],
+            row_starts: Vec::new(),
+            font: Arc::new(ErrorFont),
+            wrap_width: None,
+
+            wrap: true,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn text(&self) -> String {
+        self.buffer.to_string()
+    }
+
+    pub fn buffer(&self) -> &PieceTable {
+        &self.buffer
+    }
+
+    /// Replace the whole document, clear the history and move the caret to the start.
+    pub fn set_text<S: Into<String>>(&mut self, text: S) {
+        self.buffer = PieceTable::new(text);
+        self.lines = (0..self.buffer.line_count()).map(|_| None).collect();
+        self.row_starts.clear();
+        self.history.clear();
+        self.caret = 0;
+        self.anchor = 0;
+        self.goal_x = None;
+        self.scroll = 0.0;
+    }
+
+    /// Text was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+
+    /// Selected byte range of the text. It is empty if nothing is selected.
+    pub fn selection(&self) -> Range<usize> {
+        min(self.caret, self.anchor)..max(self.caret, self.anchor)
+    }
+
+    /// Select the byte range, the caret is placed at its end.
+    pub fn set_selection(&mut self, selection: Range<usize>) {
+        assert!(selection.end <= self.buffer.len());
+        self.anchor = selection.start;
+        self.caret = selection.end;
+        self.goal_x = None;
+        self.history.break_merging();
+    }
+
+    pub fn selected_text(&self) -> String {
+        self.buffer.slice(self.selection())
+    }
+
+    /// Revert the last edit. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        if let Some(edit) = self.history.undo() {
+            self.apply(edit.offset..edit.offset + edit.inserted.len(), &edit.removed);
+            self.anchor = edit.selection_before.0;
+            self.caret = edit.selection_before.1;
+            self.changed = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Apply the last reverted edit again. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        if let Some(edit) = self.history.redo() {
+            self.apply(edit.offset..edit.offset + edit.removed.len(), &edit.inserted);
+            self.caret = edit.offset + edit.inserted.len();
+            self.anchor = self.caret;
+            self.changed = true;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn handle_mouse(&mut self, gui: &mut Gui, place: Rect<f64>, line_height: f64) {
+        let shift = gui.input.keyboard.is_pressed(Key::LShift) || gui.input.keyboard.is_pressed(Key::RShift);
+        let row_at = |y: f64, scroll: f64| if line_height > 0.0 { ((y - place.top + scroll)/line_height).max(0.0) as usize } else { 0 };
+
+        if let Some(mouse) = gui.input.mouse_grabbed_by(self.id) {
+            if mouse.primary_button.is_pressed() {
+                gui.input.grab_mouse(self.id);
+
+                let row = row_at(mouse.position.y, self.scroll);
+                self.caret = self.offset_at(row, mouse.position.x - place.left);
+                self.goal_x = None;
+            }
+        } else if let Some(mouse) = gui.input.get_mouse(|pos| place.contains(pos)) {
+            if mouse.primary_button == ButtonState::JustPressed {
+                gui.input.grab_mouse(self.id);
+                gui.focus(self.id);
+
+                let row = row_at(mouse.position.y, self.scroll);
+                let caret = self.offset_at(row, mouse.position.x - place.left);
+                self.move_caret(caret, shift);
+            }
+        }
+    }
+
+    fn handle_keyboard(&mut self, gui: &mut Gui, page: usize) {
+        // Arrow keys move the caret instead of the focus.
+        for &direction in &[Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+            gui.input.take_navigation(direction);
+        }
+
+        // Copy of the keyboard state so that the clipboard can be used while handling keys.
+        let keyboard = gui.input.keyboard.clone();
+        let shift = keyboard.is_pressed(Key::LShift) || keyboard.is_pressed(Key::RShift);
+        let control = keyboard.is_pressed(Key::LControl) || keyboard.is_pressed(Key::RControl);
+        let presses = |key| keyboard.presses(key);
+
+        for _ in 0..presses(Key::Left) {
+            let selection = self.selection();
+            let caret = if !shift && !selection.is_empty() { selection.start } else { self.prev_boundary(self.caret, control) };
+            self.move_caret(caret, shift);
+        }
+
+        for _ in 0..presses(Key::Right) {
+            let selection = self.selection();
+            let caret = if !shift && !selection.is_empty() { selection.end } else { self.next_boundary(self.caret, control) };
+            self.move_caret(caret, shift);
+        }
+
+        for _ in 0..presses(Key::Up) {
+            self.move_vertically(-1, shift);
+        }
+
+        for _ in 0..presses(Key::Down) {
+            self.move_vertically(1, shift);
+        }
+
+        for _ in 0..presses(Key::PageUp) {
+            self.move_vertically(-(page as isize), shift);
+        }
+
+        for _ in 0..presses(Key::PageDown) {
+            self.move_vertically(page as isize, shift);
+        }
+
+        if presses(Key::Home) > 0 {
+            let caret = if control { 0 } else { self.row_edge(self.caret, false) };
+            self.move_caret(caret, shift);
+        }
+
+        if presses(Key::End) > 0 {
+            let caret = if control { self.buffer.len() } else { self.row_edge(self.caret, true) };
+            self.move_caret(caret, shift);
+        }
+
+        for _ in 0..presses(Key::Backspace) {
+            let selection = self.selection();
+            let range = if selection.is_empty() { self.prev_boundary(self.caret, control)..self.caret } else { selection };
+            self.replace(range, "", EditKind::Deleting);
+        }
+
+        for _ in 0..presses(Key::Delete) {
+            let selection = self.selection();
+            let range = if selection.is_empty() { self.caret..self.next_boundary(self.caret, control) } else { selection };
+            self.replace(range, "", EditKind::Deleting);
+        }
+
+        for _ in 0..presses(Key::Return) + presses(Key::NumpadEnter) {
+            self.replace_selection("\n", EditKind::Typing);
+        }
+
+        if control {
+            let just_pressed = |key| keyboard.key_state(key) == ButtonState::JustPressed;
+
+            if just_pressed(Key::A) {
+                self.anchor = 0;
+                self.caret = self.buffer.len();
+            }
+
+            if (just_pressed(Key::C) || just_pressed(Key::X)) && !self.selection().is_empty() {
+                gui.clipboard.set(&self.selected_text());
+            }
+
+            if just_pressed(Key::X) {
+                self.replace_selection("", EditKind::Other);
+            }
+
+            if just_pressed(Key::V) {
+                if let Some(text) = gui.clipboard.get() {
+                    let text: String = text.replace("\r\n", "\n").chars()
+                        .filter(|&char| char == '\n' || char == '\t' || !char.is_control())
+                        .collect();
+                    self.replace_selection(&text, EditKind::Other);
+                }
+            }
+
+            for _ in 0..presses(Key::Z) {
+                if shift { self.redo() } else { self.undo() };
+            }
+
+            for _ in 0..presses(Key::Y) {
+                self.redo();
+            }
+        }
+
+        let input: String = keyboard.input().chars().filter(|char| !char.is_control()).collect();
+        if !input.is_empty() {
+            self.replace_selection(&input, EditKind::Typing);
+        }
+    }
+
+    fn move_caret(&mut self, caret: usize, select: bool) {
+        self.caret = caret;
+        if !select {
+            self.anchor = caret;
+        }
+        self.goal_x = None;
+        self.history.break_merging();
+    }
+
+    /// Move the caret by the number of rows keeping its horizontal position.
+    fn move_vertically(&mut self, rows: isize, select: bool) {
+        let (row, x) = self.position_of(self.caret);
+        let goal_x = *self.goal_x.get_or_insert(x);
+        let target = row as isize + rows;
+
+        self.caret = if target < 0 {
+            0
+        } else if target as usize >= self.total_rows() {
+            self.buffer.len()
+        } else {
+            self.offset_at(target as usize, goal_x)
+        };
+
+        if !select {
+            self.anchor = self.caret;
+        }
+        self.history.break_merging();
+    }
+
+    fn replace_selection(&mut self, text: &str, kind: EditKind) {
+        let selection = self.selection();
+        self.replace(selection, text, kind);
+    }
+
+    /// Replace the `range` and record it in the history along with the current selection which is restored by undo.
+    fn replace(&mut self, range: Range<usize>, text: &str, kind: EditKind) {
+        if range.is_empty() && text.is_empty() {
+            return;
+        }
+
+        let removed = self.apply(range.clone(), text);
+        self.history.record(kind, Edit {
+            offset: range.start,
+            removed: removed,
+            inserted: text.to_owned(),
+            selection_before: (self.anchor, self.caret),
+        });
+
+        self.caret = range.start + text.len();
+        self.anchor = self.caret;
+        self.goal_x = None;
+        self.changed = true;
+    }
+
+    /// Replace the `range` without recording it in the history and return the removed text.
+    fn apply(&mut self, range: Range<usize>, text: &str) -> String {
+        let first_line = self.buffer.line_at(range.start);
+        let old_last_line = self.buffer.line_at(range.end);
+
+        let removed = self.buffer.remove(range.clone());
+        self.buffer.insert(range.start, text);
+
+        let new_last_line = self.buffer.line_at(range.start + text.len());
+        self.lines.splice(first_line..old_last_line + 1, (first_line..new_last_line + 1).map(|_| None));
+        self.row_starts.truncate(first_line + 1);
+
+        removed
+    }
+
+    fn invalidate(&mut self, lines: Range<usize>) {
+        self.row_starts.truncate(lines.start + 1);
+        for line in &mut self.lines[lines] {
+            *line = None;
+        }
+    }
+
+    fn prev_boundary(&self, offset: usize, word: bool) -> usize {
+        if offset == 0 {
+            return 0;
+        }
+
+        let line = self.buffer.line_at(offset);
+        let range = self.buffer.line_range(line);
+        if offset == range.start {
+            return offset - 1;
+        }
+
+        let text = self.buffer.line(line);
+        let offset = offset - range.start;
+        range.start + if word { text::prev_word(&text, offset) } else { text::prev_char(&text, offset) }
+    }
+
+    fn next_boundary(&self, offset: usize, word: bool) -> usize {
+        let line = self.buffer.line_at(offset);
+        let range = self.buffer.line_range(line);
+        if offset == range.end {
+            return min(offset + 1, self.buffer.len());
+        }
+
+        let text = self.buffer.line(line);
+        let offset = offset - range.start;
+        range.start + if word { text::next_word(&text, offset) } else { text::next_char(&text, offset) }
+    }
+
+    /// First row of each line followed by the total number of rows.
+    ///
+    /// Changes of a line drop the entries after it and they are summed again here only when rows are needed.
+    fn row_starts(&mut self) -> &[usize] {
+        if self.row_starts.is_empty() {
+            self.row_starts.push(0);
+        }
+
+        while self.row_starts.len() <= self.lines.len() {
+            let line = self.row_starts.len() - 1;
+            let rows = self.lines[line].as_ref().map_or(1, |layout| layout.rows.len());
+            let next = self.row_starts[line] + rows;
+            self.row_starts.push(next);
+        }
+
+        &self.row_starts
+    }
+
+    fn total_rows(&mut self) -> usize {
+        let row_starts = self.row_starts();
+        row_starts[row_starts.len() - 1]
+    }
+
+    fn first_row_of(&mut self, line: usize) -> usize {
+        self.row_starts()[line]
+    }
+
+    /// Line and the row inside of it. Rows past the end are clamped to the last row.
+    fn row_at(&mut self, row: usize) -> (usize, usize) {
+        let row_starts = self.row_starts();
+        let row = min(row, row_starts[row_starts.len() - 1] - 1);
+
+        // Every line has at least one row so the starts are strictly increasing.
+        let line = match row_starts.binary_search(&row) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+
+        (line, row - row_starts[line])
+    }
+
+    fn layout_line(&mut self, line: usize) -> &LineLayout {
+        if self.lines[line].is_none() {
+            let text = self.buffer.line(line);
+            let mut segments = Vec::new();
+            self.font.shape(&text, &mut segments);
+
+            let rows = wrap(&text, &segments, self.wrap_width);
+            if rows.len() != 1 {
+                // Rows of the following lines were counted assuming that this one has a single row.
+                self.row_starts.truncate(line + 1);
+            }
+            self.lines[line] = Some(LineLayout { segments, rows });
+        }
+
+        self.lines[line].as_ref().unwrap()
+    }
+
+    /// Row and horizontal position of the caret placed at the `offset`.
+    fn position_of(&mut self, offset: usize) -> (usize, f64) {
+        let line = self.buffer.line_at(offset);
+        let line_start = self.buffer.line_range(line).start;
+        let first_row = self.first_row_of(line);
+        let layout = self.layout_line(line);
+
+        let offset = offset - line_start;
+        let index = layout.rows.iter().rposition(|row| row.bytes.start <= offset).unwrap_or(0);
+        let row = &layout.rows[index];
+
+        (first_row + index, text::caret_position(&layout.segments[row.segments.clone()], offset) - row.x)
+    }
+
+    /// Offset closest to the position `x` on the `row`.
+    fn offset_at(&mut self, row: usize, x: f64) -> usize {
+        let (line, index) = self.row_at(row);
+        let line_start = self.buffer.line_range(line).start;
+        let layout = self.layout_line(line);
+
+        let index = min(index, layout.rows.len() - 1);
+        let row = &layout.rows[index];
+        let segments = &layout.segments[row.segments.clone()];
+
+        if segments.is_empty() {
+            return line_start + row.bytes.start;
+        }
+
+        let mut offset = text::offset_at(segments, x + row.x);
+
+        // The end of a wrapped row is the start of the next one so stay before the last segment to remain on this row.
+        if offset == row.bytes.end && index + 1 < layout.rows.len() {
+            offset = segments[segments.len() - 1].byte_start;
+        }
+
+        line_start + offset
+    }
+
+    /// Start or end of the row containing the `offset`.
+    fn row_edge(&mut self, offset: usize, end: bool) -> usize {
+        let (row, _) = self.position_of(offset);
+        let (line, index) = self.row_at(row);
+        let line_start = self.buffer.line_range(line).start;
+        let layout = self.layout_line(line);
+        let row = &layout.rows[index];
+
+        line_start + if !end {
+            row.bytes.start
+        } else if index + 1 < layout.rows.len() {
+            // Stay before the last segment of a wrapped row, see `offset_at`.
+            layout.segments[row.segments.end - 1].byte_start
+        } else {
+            row.bytes.end
+        }
+    }
+
+    fn scroll_to(&mut self, row: usize, line_height: f64, height: f64) {
+        let top = row as f64*line_height;
+
+        if top + line_height > self.scroll + height {
+            self.scroll = top + line_height - height;
+        }
+        if top < self.scroll {
+            self.scroll = top;
+        }
+
+        let max_scroll = self.total_rows() as f64*line_height - height;
+        self.scroll = self.scroll.min(max_scroll).max(0.0);
+    }
+
+    /// Collect glyphs of the visible rows and horizontal extents of the selection on each of them.
+    ///
+    /// `shift` is an offset and a width by which the glyphs after the offset on its row are moved to make space for the
+    /// text composed by the input method.
+    fn visible_rows(&mut self, line_height: f64, height: f64, shift: Option<(usize, f64)>, glyphs: &mut Vec<Glyph>, selection_rows: &mut Vec<(f64, f64, f64)>) {
+        if line_height <= 0.0 {
+            return;
+        }
+
+        let first_row = (self.scroll/line_height) as usize;
+        let end_row = ((self.scroll + height)/line_height).ceil() as usize;
+        let (mut line, mut index) = self.row_at(first_row);
+        let mut row_number = first_row;
+        let selection = self.selection();
+        let scroll = self.scroll;
+
+        while row_number < end_row && line < self.lines.len() {
+            let line_range = self.buffer.line_range(line);
+            let is_last_line = line + 1 == self.lines.len();
+            let layout = self.layout_line(line);
+
+            while index < layout.rows.len() && row_number < end_row {
+                let row = &layout.rows[index];
+                let segments = &layout.segments[row.segments.clone()];
+                let y = row_number as f64*line_height - scroll;
+                let row_start = line_range.start + row.bytes.start;
+                let row_end = line_range.start + row.bytes.end;
+
+                glyphs.extend(segments.iter().map(|segment| {
+                    let offset = line_range.start + segment.byte_start;
+                    let x = match shift {
+                        Some((start, width)) if start >= row_start && offset >= start => width - row.x,
+                        _ => -row.x,
+                    };
+
+                    Glyph {
+                        position: segment.position + Vec2::new(x, y),
+                        scale: Vec2::new(1.0, 1.0),
+                        glyph_id: segment.glyph_index,
+                    }
+                }));
+
+                let is_last_row = index + 1 == layout.rows.len();
+                let selects_line_break = is_last_row && !is_last_line && selection.start <= row_end && selection.end > row_end;
+
+                if (selection.start < row_end && selection.end > row_start) || selects_line_break {
+                    let start = max(selection.start, row_start) - line_range.start;
+                    let end = min(selection.end, row_end) - line_range.start;
+                    let left = text::caret_position(segments, start) - row.x;
+                    let mut right = text::caret_position(segments, end) - row.x;
+
+                    if selects_line_break {
+                        right += line_height*0.25;
+                    }
+
+                    selection_rows.push((left, right, y));
+                }
+
+                index += 1;
+                row_number += 1;
+            }
+
+            line += 1;
+            index = 0;
+        }
+    }
+}
+
+/// Split the line into rows no wider than `width`, preferably after whitespace.
+fn wrap(text: &str, segments: &[Segment], width: Option<f64>) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut start = 0;
+    let mut last_break = None;
+
+    for (i, segment) in segments.iter().enumerate() {
+        if let Some(width) = width {
+            if i > start && segment.position_end - segments[start].position_start > width {
+                let end = match last_break {
+                    Some(end) if end > start => end,
+                    _ => i,
+                };
+
+                rows.push(start..end);
+                start = end;
+                last_break = None;
+            }
+        }
+
+        if text[segment.byte_start..segment.byte_end].chars().all(char::is_whitespace) {
+            last_break = Some(i + 1);
+        }
+    }
+
+    rows.push(start..segments.len());
+
+    rows.into_iter().enumerate().map(|(i, range)| Row {
+        bytes: if i == 0 { 0 } else { segments[range.start].byte_start }
+            ..segments.get(range.end).map_or(text.len(), |segment| segment.byte_start),
+        x: if i == 0 { 0.0 } else { segments[range.start].position_start },
+        segments: range,
+    }).collect()
+}
+
+#[test]
+fn test_text_editor() {
+    use Theme;
+    use theme::ElementStyle;
+    use gui::input::{Event, KeyEvent, ScanCode};
+    use gui::scene::Command;
+
+    let mut theme = Theme::empty();
+    theme.element_styles.insert((Default::default(), element_kind!("TextEditor")), Arc::new(ElementStyle {
+        font: Arc::new(text::MonospaceFont::default()),
+        ..ElementStyle::default()
+    }));
+
+    let mut gui = Gui::new(theme);
+    let mut editor = TextEditor::new();
+    editor.set_text("hello world foo\nbar");
+    gui.focus(editor.id());
+
+    let frame = |gui: &mut Gui, editor: &mut TextEditor, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = editor.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 100.0,
+            (place.bottom) == 40.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+
+    // The first line is wrapped after "hello ".
+    frame(&mut gui, &mut editor, &[]);
+    frame(&mut gui, &mut editor, &[key(Key::Down, true)]);
+    assert_eq!(editor.selection(), 6..6);
+    assert_eq!(editor.total_rows(), 3);
+    assert_eq!((editor.row_at(1), editor.row_at(2), editor.row_at(10)), ((0, 1), (1, 0), (1, 0)));
+
+    frame(&mut gui, &mut editor, &[key(Key::Down, false), key(Key::End, true)]);
+    assert_eq!(editor.selection(), 15..15);
+
+    // Moving through the shorter line keeps the column.
+    frame(&mut gui, &mut editor, &[key(Key::End, false), key(Key::Down, true)]);
+    assert_eq!(editor.selection(), 19..19);
+    assert_eq!(editor.scroll, 20.0);
+    frame(&mut gui, &mut editor, &[key(Key::Down, false), key(Key::Up, true)]);
+    assert_eq!(editor.selection(), 15..15);
+
+    frame(&mut gui, &mut editor, &[key(Key::Up, false), key(Key::Return, true), Event::Char('a'), Event::Char('b')]);
+    assert_eq!(editor.text(), "hello world foo\nab\nbar");
+    let rows: usize = editor.lines.iter().map(|line| line.as_ref().map_or(1, |layout| layout.rows.len())).sum();
+    assert_eq!(editor.total_rows(), rows);
+
+    frame(&mut gui, &mut editor, &[key(Key::Return, false), key(Key::LShift, true), key(Key::Left, true)]);
+    assert_eq!(editor.selected_text(), "b");
+
+    frame(&mut gui, &mut editor, &[key(Key::Left, false), key(Key::LShift, false), Event::Char('c')]);
+    assert_eq!(editor.text(), "hello world foo\nac\nbar");
+
+    frame(&mut gui, &mut editor, &[key(Key::LControl, true), key(Key::Z, true)]);
+    assert_eq!(editor.text(), "hello world foo\nab\nbar");
+    assert_eq!(editor.selected_text(), "b");
+    frame(&mut gui, &mut editor, &[key(Key::Z, false)]);
+    frame(&mut gui, &mut editor, &[key(Key::Z, true)]);
+    assert_eq!(editor.text(), "hello world foo\n\nbar");
+
+    frame(&mut gui, &mut editor, &[key(Key::Z, false), key(Key::Y, true)]);
+    assert_eq!(editor.text(), "hello world foo\nab\nbar");
+    assert!(editor.changed());
+
+    // Undo restores the caret from before the deletion.
+    frame(&mut gui, &mut editor, &[key(Key::Y, false), key(Key::LControl, false)]);
+    editor.set_selection(17..17);
+    frame(&mut gui, &mut editor, &[key(Key::Backspace, true)]);
+    frame(&mut gui, &mut editor, &[key(Key::Backspace, false), key(Key::Delete, true)]);
+    assert_eq!(editor.text(), "hello world foo\n\nbar");
+    frame(&mut gui, &mut editor, &[key(Key::Delete, false), key(Key::LControl, true), key(Key::Z, true)]);
+    assert_eq!(editor.text(), "hello world foo\nab\nbar");
+    assert_eq!(editor.selection(), 17..17);
+
+    frame(&mut gui, &mut editor, &[key(Key::Z, false), key(Key::LControl, false)]);
+    editor.set_selection(16..16);
+    frame(&mut gui, &mut editor, &[key(Key::Delete, true)]);
+    assert_eq!(editor.text(), "hello world foo\nb\nbar");
+    frame(&mut gui, &mut editor, &[key(Key::Delete, false), key(Key::LControl, true), key(Key::Z, true)]);
+    assert_eq!(editor.text(), "hello world foo\nab\nbar");
+    assert_eq!(editor.selection(), 16..16);
+
+    // Text composed by the input method is shown at the caret and the rest of the row moves after it.
+    frame(&mut gui, &mut editor, &[key(Key::Z, false), key(Key::LControl, false)]);
+    editor.set_selection(17..17);
+    gui.input.ime_preedit("xy", Some((1, 1)));
+    frame(&mut gui, &mut editor, &[]);
+    assert_eq!(gui.layout.prev_value_rect(Rect::from(editor.caret_id)).left, 20.0);
+    assert_eq!(gui.layout.prev_value_rect(Rect::from(editor.preedit_id)).right, 30.0);
+
+    editor.appear(&mut gui);
+    let glyphs = gui.scene.commands().iter().filter_map(|command| match *command {
+        Command::Text(ref text) => Some(text.glyphs.borrow().clone()),
+        _ => None,
+    }).next().unwrap();
+    let row: Vec<_> = glyphs.iter().filter(|glyph| glyph.position.y == 0.0).map(|glyph| (glyph.glyph_id, glyph.position.x)).collect();
+    assert_eq!(row, vec![('a' as u32, 0.0), ('b' as u32, 30.0), ('x' as u32, 10.0), ('y' as u32, 20.0)]);
+    assert_eq!(editor.text(), "hello world foo\nab\nbar");
+}
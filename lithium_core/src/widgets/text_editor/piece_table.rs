@@ -0,0 +1,265 @@
+use std::fmt;
+use std::ops::Range;
+
+/// Text buffer which stays fast for large documents.
+///
+/// The original text is never modified, inserted text is appended to a separate buffer and the document is described by
+/// a list of pieces referring to either of them. Byte offsets of the line starts are kept up to date so that lines can be
+/// found without scanning the text.
+///
+/// All offsets are byte offsets which must lie on character boundaries.
+#[derive(Clone, Debug)]
+pub struct PieceTable {
+    original: String,
+    added: String,
+    pieces: Vec<Piece>,
+    len: usize,
+
+    /// Offsets of the line starts, the first line always starts at zero.
+    line_starts: Vec<usize>,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+enum Source {
+    Original,
+    Added,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct Piece {
+    source: Source,
+    start: usize,
+    len: usize,
+}
+
+impl PieceTable {
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        let original = text.into();
+        let len = original.len();
+
+        let mut line_starts = vec![0];
+        line_starts.extend(original.match_indices('\n').map(|(i, _)| i + 1));
+
+        PieceTable {
+            pieces: if len > 0 { vec![Piece { source: Source::Original, start: 0, len }] } else { Vec::new() },
+            original,
+            added: String::new(),
+            len,
+            line_starts,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Byte range of the line without the line break.
+    pub fn line_range(&self, line: usize) -> Range<usize> {
+        let start = self.line_starts[line];
+        let end = self.line_starts.get(line + 1).map_or(self.len, |&next| next - 1);
+        start..end
+    }
+
+    /// Line containing the `offset`. Line break belongs to the line it ends.
+    pub fn line_at(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        }
+    }
+
+    pub fn line(&self, line: usize) -> String {
+        self.slice(self.line_range(line))
+    }
+
+    pub fn slice(&self, range: Range<usize>) -> String {
+        let mut text = String::with_capacity(range.end - range.start);
+        let mut piece_start = 0;
+
+        for piece in &self.pieces {
+            let piece_end = piece_start + piece.len;
+
+            if piece_end > range.start && piece_start < range.end {
+                let from = range.start.max(piece_start) - piece_start + piece.start;
+                let to = range.end.min(piece_end) - piece_start + piece.start;
+                text += &self.source(piece.source)[from..to];
+            }
+
+            if piece_end >= range.end {
+                break;
+            }
+            piece_start = piece_end;
+        }
+
+        text
+    }
+
+    pub fn insert(&mut self, offset: usize, text: &str) {
+        assert!(offset <= self.len);
+
+        if text.is_empty() {
+            return;
+        }
+
+        let added_start = self.added.len();
+        self.added += text;
+
+        let new_piece = Piece { source: Source::Added, start: added_start, len: text.len() };
+        let mut piece_start = 0;
+        let mut index = self.pieces.len();
+
+        for (i, piece) in self.pieces.iter().enumerate() {
+            let piece_end = piece_start + piece.len;
+
+            if offset == piece_start {
+                index = i;
+                break;
+            }
+
+            if offset < piece_end {
+                let split = offset - piece_start;
+                let (left, right) = (
+                    Piece { len: split, ..*piece },
+                    Piece { start: piece.start + split, len: piece.len - split, ..*piece },
+                );
+
+                self.pieces.splice(i..i + 1, vec![left, new_piece, right]);
+                self.insert_line_starts(offset, text);
+                return;
+            }
+
+            piece_start = piece_end;
+        }
+
+        // Typing usually continues right after the previously inserted text so the previous piece can be extended.
+        let extends_previous = index > 0 && {
+            let previous = self.pieces[index - 1];
+            previous.source == Source::Added && previous.start + previous.len == added_start
+        };
+
+        if extends_previous {
+            self.pieces[index - 1].len += text.len();
+        } else {
+            self.pieces.insert(index, new_piece);
+        }
+
+        self.insert_line_starts(offset, text);
+    }
+
+    /// Remove the `range` from the text and return the removed text.
+    pub fn remove(&mut self, range: Range<usize>) -> String {
+        assert!(range.start <= range.end && range.end <= self.len);
+
+        let removed = self.slice(range.clone());
+        if removed.is_empty() {
+            return removed;
+        }
+
+        let mut pieces = Vec::with_capacity(self.pieces.len() + 1);
+        let mut piece_start = 0;
+
+        for piece in &self.pieces {
+            let piece_end = piece_start + piece.len;
+
+            if piece_end <= range.start || piece_start >= range.end {
+                pieces.push(*piece);
+            } else {
+                if piece_start < range.start {
+                    pieces.push(Piece { len: range.start - piece_start, ..*piece });
+                }
+                if piece_end > range.end {
+                    let skip = range.end - piece_start;
+                    pieces.push(Piece { start: piece.start + skip, len: piece_end - range.end, ..*piece });
+                }
+            }
+
+            piece_start = piece_end;
+        }
+
+        self.pieces = pieces;
+        self.len -= removed.len();
+
+        let removed_len = removed.len();
+        self.line_starts.retain(|&start| start <= range.start || start > range.end);
+        for start in &mut self.line_starts {
+            if *start > range.start {
+                *start -= removed_len;
+            }
+        }
+
+        removed
+    }
+
+    fn insert_line_starts(&mut self, offset: usize, text: &str) {
+        self.len += text.len();
+
+        let index = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line + 1,
+            Err(line) => line,
+        };
+
+        for start in &mut self.line_starts[index..] {
+            *start += text.len();
+        }
+
+        let new_starts: Vec<usize> = text.match_indices('\n').map(|(i, _)| offset + i + 1).collect();
+        self.line_starts.splice(index..index, new_starts);
+    }
+
+    fn source(&self, source: Source) -> &str {
+        match source {
+            Source::Original => &self.original,
+            Source::Added => &self.added,
+        }
+    }
+}
+
+impl fmt::Display for PieceTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for piece in &self.pieces {
+            f.write_str(&self.source(piece.source)[piece.start..piece.start + piece.len])?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_piece_table_edits() {
+    let mut table = PieceTable::new("first\nsecond\nthird");
+    assert_eq!(table.line_count(), 3);
+    assert_eq!(table.line(1), "second");
+
+    table.insert(6, "new ");
+    table.insert(10, "line\n");
+    assert_eq!(table.to_string(), "first\nnew line\nsecond\nthird");
+    assert_eq!(table.line_count(), 4);
+    assert_eq!(table.line_range(1), 6..14);
+    assert_eq!(table.line_at(14), 1);
+    assert_eq!(table.line_at(15), 2);
+    assert_eq!(table.pieces.len(), 3);
+
+    assert_eq!(table.remove(3..17), "st\nnew line\nse");
+    assert_eq!(table.to_string(), "fircond\nthird");
+    assert_eq!(table.line_count(), 2);
+    assert_eq!(table.line(1), "third");
+    assert_eq!(table.slice(1..9), "ircond\nt");
+
+    table.insert(table.len(), "\n");
+    assert_eq!(table.line_count(), 3);
+    assert_eq!(table.line(2), "");
+
+    let mut empty = PieceTable::new("");
+    assert_eq!(empty.line_count(), 1);
+    empty.insert(0, "ä\n");
+    assert_eq!(empty.remove(0..2), "ä");
+    assert_eq!(empty.line_range(0), 0..0);
+    assert_eq!(empty.line_range(1), 1..1);
+}
@@ -0,0 +1,549 @@
+use std::cmp::{min, max};
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::cell::RefCell;
+use {Id, Gui, Rect, Var, Vec2, Font};
+use font::Segment;
+use gui::input::{ButtonState, Key, Direction};
+use gui::scene::{Text, Glyph};
+use layout;
+use super::Widget;
+use super::text;
+
+/// Character displayed instead of each character of the text in the password mode.
+const MASK: char = '•';
+
+/// Single line text field.
+///
+/// Text is edited only while the field is focused, it receives focus when clicked. Parts of the field are separate
+/// elements which can be styled by the theme: `TextInputText` or `TextInputPlaceholder` contains glyphs,
+/// `TextInputSelection` is placed behind the selected text, `TextInputPreedit` behind the text composed by the input
+/// method which is shown at the caret and `TextInputCaret` is shown while the field is focused. The theme should set
+/// `overflow: hidden` for `TextInput` since the text scrolls horizontally when it does not fit.
+pub struct TextInput {
+    id: Id,
+    text_id: Id,
+    selection_id: Id,
+    caret_id: Id,
+    preedit_id: Id,
+
+    text: String,
+
+    /// Selection is the range between the anchor and the caret. Both are byte offsets into the text.
+    caret: usize,
+    anchor: usize,
+
+    /// Horizontal offset of the text.
+    scroll: f64,
+    changed: bool,
+    shaped: Option<Shaped>,
+
+    /// Text displayed while the field is empty.
+    pub placeholder: String,
+
+    /// Maximum number of characters.
+    pub max_length: Option<usize>,
+
+    /// Display each character as `•` and disallow copying.
+    pub password: bool,
+}
+
+struct Shaped {
+    font: Arc<Font>,
+    text: String,
+    scroll: f64,
+    segments: Vec<Segment>,
+    glyphs: Rc<RefCell<Vec<Glyph>>>,
+
+    /// Byte range of the shaped text which was composed by the input method.
+    preedit: Option<Range<usize>>,
+}
+
+impl Widget for TextInput {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let place_value = gui.layout.prev_value_rect(place);
+        let font = gui.scene.element_style(element_kind!("TextInput")).font.clone();
+
+        gui.focusable(self.id);
+        self.handle_mouse(gui, place_value);
+
+        let focused = gui.is_focused(self.id);
+        if focused {
+            self.handle_keyboard(gui);
+        }
+
+        // Text composed by the input method is shown at the caret until it is committed.
+        let preedit = if focused && !self.password { gui.input.keyboard.composition().cloned() } else { None };
+        let preedit_range = preedit.as_ref().map(|preedit| self.caret..self.caret + preedit.text.len());
+
+        let show_placeholder = self.text.is_empty() && preedit.is_none() && !self.placeholder.is_empty();
+        let display_text = if show_placeholder {
+            self.placeholder.clone()
+        } else if self.password {
+            self.text.chars().map(|_| MASK).collect()
+        } else if let Some(ref preedit) = preedit {
+            format!("{}{}{}", &self.text[..self.caret], preedit.text, &self.text[self.caret..])
+        } else {
+            self.text.clone()
+        };
+
+        self.shape(&display_text, preedit_range.clone(), font.clone());
+
+        let caret_x = match preedit {
+            Some(ref preedit) => self.shaped_position(self.caret + preedit.cursor.map_or(preedit.text.len(), |(start, _)| start)),
+            None => self.position_of(self.caret),
+        };
+        let show_caret = focused && preedit.as_ref().map_or(true, |preedit| preedit.cursor.is_some());
+        let preedit_x = preedit_range.map(|range| (self.shaped_position(range.start), self.shaped_position(range.end)));
+        let text_width = self.shaped.as_ref().map_or(0.0, |shaped| text::width(&shaped.segments));
+        self.scroll_to(caret_x, text_width, place_value.width());
+        let glyphs = self.update_glyphs();
+
+        let text_kind = if show_placeholder { element_kind!("TextInputPlaceholder") } else { element_kind!("TextInputText") };
+        let selection = self.selection();
+        let (selection_start, selection_end) = (self.position_of(selection.start), self.position_of(selection.end));
+        let scroll = self.scroll;
+        let (text_id, selection_id, caret_id, preedit_id) = (self.text_id, self.selection_id, self.caret_id, self.preedit_id);
+
+        gui.element(self.id, element_kind!("TextInput"), |gui| {
+            if !selection.is_empty() {
+                let selection_place = Rect::from(selection_id);
+                gui.element(selection_id, element_kind!("TextInputSelection"), |_gui| {});
+
+                layout::equal_horizontal(gui, place, selection_place);
+                add_constraints!(gui.layout, [
+                    (selection_place.left) == place.left + (selection_start - scroll),
+                    (selection_place.right) == place.left + (selection_end - scroll),
+                ]);
+            }
+
+            if let Some((preedit_start, preedit_end)) = preedit_x {
+                let preedit_place = Rect::from(preedit_id);
+                gui.element(preedit_id, element_kind!("TextInputPreedit"), |_gui| {});
+
+                layout::equal_horizontal(gui, place, preedit_place);
+                add_constraints!(gui.layout, [
+                    (preedit_place.left) == place.left + (preedit_start - scroll),
+                    (preedit_place.right) == place.left + (preedit_end - scroll),
+                ]);
+            }
+
+            let text_place = Rect::from(text_id);
+            gui.element(text_id, text_kind, |gui| {
+                gui.scene.text(Text {
+                    id: text_id,
+                    glyphs: glyphs,
+                });
+            });
+            layout::equal(gui, place, text_place);
+
+            if show_caret {
+                let caret_place = Rect::from(caret_id);
+                gui.element(caret_id, element_kind!("TextInputCaret"), |_gui| {});
+
+                layout::equal_horizontal(gui, place, caret_place);
+                add_constraints!(gui.layout, [
+                    (caret_place.left) == place.left + (caret_x - scroll),
+                    (caret_place.right - caret_place.left) == 1.0,
+                ]);
+            }
+        });
+
+        if focused {
+            gui.input.set_ime_caret(Rect {
+                left: place_value.left + caret_x - scroll,
+                right: place_value.left + caret_x - scroll + 1.0,
+                top: place_value.top,
+                bottom: place_value.bottom,
+            });
+        }
+
+        add_constraints!(gui.layout, [
+            (place.bottom - place.top) == font.line_height(),
+        ]);
+
+        place
+    }
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        TextInput {
+            id: Id::unique(),
+            text_id: Id::unique(),
+            selection_id: Id::unique(),
+            caret_id: Id::unique(),
+            preedit_id: Id::unique(),
+
+            text: String::new(),
+            caret: 0,
+            anchor: 0,
+            scroll: 0.0,
+            changed: false,
+            shaped: None,
+
+            placeholder: String::new(),
+            max_length: None,
+            password: false,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Replace the text and move the caret to its end. This does not count as a change made by the user.
+    pub fn set_text<S: Into<String>>(&mut self, text: S) {
+        self.text = text.into();
+        self.caret = self.text.len();
+        self.anchor = self.caret;
+    }
+
+    /// Text was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+
+    /// Selected byte range of the text. It is empty if nothing is selected.
+    pub fn selection(&self) -> Range<usize> {
+        min(self.caret, self.anchor)..max(self.caret, self.anchor)
+    }
+
+    /// Select the byte range, the caret is placed at its end.
+    ///
+    /// Panics if the range is out of bounds or not on character boundaries.
+    pub fn set_selection(&mut self, selection: Range<usize>) {
+        assert!(self.text.is_char_boundary(selection.start) && self.text.is_char_boundary(selection.end));
+        self.anchor = selection.start;
+        self.caret = selection.end;
+    }
+
+    pub fn selected_text(&self) -> &str {
+        &self.text[self.selection()]
+    }
+
+    fn handle_mouse(&mut self, gui: &mut Gui, place: Rect<f64>) {
+        let shift = gui.input.keyboard.is_pressed(Key::LShift) || gui.input.keyboard.is_pressed(Key::RShift);
+
+        if let Some(mouse) = gui.input.mouse_grabbed_by(self.id) {
+            if mouse.primary_button.is_pressed() {
+                gui.input.grab_mouse(self.id);
+                self.caret = self.offset_at(mouse.position.x - place.left + self.scroll);
+            }
+        } else if let Some(mouse) = gui.input.get_mouse(|pos| place.contains(pos)) {
+            if mouse.primary_button == ButtonState::JustPressed {
+                gui.input.grab_mouse(self.id);
+                gui.focus(self.id);
+
+                self.caret = self.offset_at(mouse.position.x - place.left + self.scroll);
+                if !shift {
+                    self.anchor = self.caret;
+                }
+            }
+        }
+    }
+
+    fn handle_keyboard(&mut self, gui: &mut Gui) {
+        // Arrow keys move the caret instead of the focus.
+        gui.input.take_navigation(Direction::Left);
+        gui.input.take_navigation(Direction::Right);
+
+        let (shift, control) = {
+            let keyboard = &gui.input.keyboard;
+            (keyboard.is_pressed(Key::LShift) || keyboard.is_pressed(Key::RShift),
+             keyboard.is_pressed(Key::LControl) || keyboard.is_pressed(Key::RControl))
+        };
+
+        for _ in 0..gui.input.keyboard.presses(Key::Left) {
+            let selection = self.selection();
+            let caret = if !shift && !selection.is_empty() {
+                selection.start
+            } else if control {
+                text::prev_word(&self.text, self.caret)
+            } else {
+                text::prev_char(&self.text, self.caret)
+            };
+            self.move_caret(caret, shift);
+        }
+
+        for _ in 0..gui.input.keyboard.presses(Key::Right) {
+            let selection = self.selection();
+            let caret = if !shift && !selection.is_empty() {
+                selection.end
+            } else if control {
+                text::next_word(&self.text, self.caret)
+            } else {
+                text::next_char(&self.text, self.caret)
+            };
+            self.move_caret(caret, shift);
+        }
+
+        if gui.input.keyboard.presses(Key::Home) > 0 {
+            self.move_caret(0, shift);
+        }
+
+        if gui.input.keyboard.presses(Key::End) > 0 {
+            let end = self.text.len();
+            self.move_caret(end, shift);
+        }
+
+        for _ in 0..gui.input.keyboard.presses(Key::Backspace) {
+            if self.selection().is_empty() {
+                self.anchor = if control { text::prev_word(&self.text, self.caret) } else { text::prev_char(&self.text, self.caret) };
+            }
+            self.replace_selection("");
+        }
+
+        for _ in 0..gui.input.keyboard.presses(Key::Delete) {
+            if self.selection().is_empty() {
+                self.anchor = if control { text::next_word(&self.text, self.caret) } else { text::next_char(&self.text, self.caret) };
+            }
+            self.replace_selection("");
+        }
+
+        if control {
+            let just_pressed = |key| gui.input.keyboard.key_state(key) == ButtonState::JustPressed;
+            let (select_all, copy, cut, paste) = (just_pressed(Key::A), just_pressed(Key::C), just_pressed(Key::X), just_pressed(Key::V));
+
+            if select_all {
+                self.anchor = 0;
+                self.caret = self.text.len();
+            }
+
+            if (copy || cut) && !self.password && !self.selection().is_empty() {
+                gui.clipboard.set(self.selected_text());
+            }
+
+            if cut && !self.password {
+                self.replace_selection("");
+            }
+
+            if paste {
+                if let Some(text) = gui.clipboard.get() {
+                    // Line breaks are not allowed in a single line field.
+                    let text: String = text.chars().filter(|char| !char.is_control()).collect();
+                    self.replace_selection(&text);
+                }
+            }
+        }
+
+        let input: String = gui.input.keyboard.input().chars().filter(|char| !char.is_control()).collect();
+        if !input.is_empty() {
+            self.replace_selection(&input);
+        }
+    }
+
+    fn move_caret(&mut self, caret: usize, select: bool) {
+        self.caret = caret;
+        if !select {
+            self.anchor = caret;
+        }
+    }
+
+    /// Replace the selected text and place the caret after the inserted text. Insertion is truncated to the `max_length`.
+    fn replace_selection(&mut self, text: &str) {
+        let selection = self.selection();
+
+        let text = if let Some(max_length) = self.max_length {
+            let remaining_length = self.text.chars().count() - self.text[selection.clone()].chars().count();
+            let allowed = max_length.saturating_sub(remaining_length);
+            text.char_indices().nth(allowed).map_or(text, |(i, _)| &text[..i])
+        } else {
+            text
+        };
+
+        if selection.is_empty() && text.is_empty() {
+            return;
+        }
+
+        self.text = format!("{}{}{}", &self.text[..selection.start], text, &self.text[selection.end..]);
+        self.caret = selection.start + text.len();
+        self.anchor = self.caret;
+        self.changed = true;
+    }
+
+    fn shape(&mut self, display_text: &str, preedit: Option<Range<usize>>, font: Arc<Font>) {
+        let reshape = match self.shaped {
+            Some(ref shaped) => shaped.text != display_text || !Arc::ptr_eq(&shaped.font, &font),
+            None => true,
+        };
+
+        if reshape {
+            let mut segments = Vec::new();
+            font.shape(display_text, &mut segments);
+
+            self.shaped = Some(Shaped {
+                font: font,
+                text: display_text.to_owned(),
+                scroll: ::std::f64::NAN,
+                segments: segments,
+                glyphs: Rc::new(RefCell::new(Vec::new())),
+                preedit: None,
+            });
+        }
+
+        self.shaped.as_mut().unwrap().preedit = preedit;
+    }
+
+    /// Position of the caret at the byte `offset` of the text relative to the start of the text.
+    fn position_of(&self, offset: usize) -> f64 {
+        let preedit = self.shaped.as_ref().and_then(|shaped| shaped.preedit.clone());
+        if self.text.is_empty() && preedit.is_none() {
+            return 0.0;
+        }
+
+        let offset = if self.password {
+            self.text[..offset].chars().count()*MASK.len_utf8()
+        } else {
+            match preedit {
+                Some(ref preedit) if offset > preedit.start => offset + preedit.len(),
+                _ => offset,
+            }
+        };
+
+        self.shaped_position(offset)
+    }
+
+    /// Position of the caret at the byte `offset` of the shaped text which may differ from the text, see `Shaped::preedit`.
+    fn shaped_position(&self, offset: usize) -> f64 {
+        self.shaped.as_ref().map_or(0.0, |shaped| text::caret_position(&shaped.segments, offset))
+    }
+
+    /// Byte offset of the text closest to the `position` relative to the start of the text.
+    fn offset_at(&self, position: f64) -> usize {
+        if self.text.is_empty() {
+            return 0;
+        }
+
+        let offset = self.shaped.as_ref().map_or(0, |shaped| text::offset_at(&shaped.segments, position));
+        let preedit = self.shaped.as_ref().and_then(|shaped| shaped.preedit.clone());
+
+        if self.password {
+            self.text.char_indices().nth(offset/MASK.len_utf8()).map_or(self.text.len(), |(i, _)| i)
+        } else {
+            match preedit {
+                Some(ref preedit) if offset >= preedit.end => offset - preedit.len(),
+                Some(ref preedit) => min(offset, preedit.start),
+                None => offset,
+            }
+        }
+    }
+
+    /// Scroll the text so that the caret is visible and there is no empty space after the text if it does not fit.
+    fn scroll_to(&mut self, caret_x: f64, text_width: f64, width: f64) {
+        if caret_x - self.scroll > width {
+            self.scroll = caret_x - width;
+        }
+        if caret_x - self.scroll < 0.0 {
+            self.scroll = caret_x;
+        }
+
+        self.scroll = self.scroll.min(text_width - width).max(0.0);
+    }
+
+    fn update_glyphs(&mut self) -> Rc<RefCell<Vec<Glyph>>> {
+        let scroll = self.scroll;
+        let shaped = self.shaped.as_mut().unwrap();
+
+        if shaped.scroll != scroll {
+            shaped.scroll = scroll;
+            *shaped.glyphs.borrow_mut() = shaped.segments.iter().map(|segment| Glyph {
+                position: segment.position - Vec2::new(scroll, 0.0),
+                scale: Vec2::new(1.0, 1.0),
+                glyph_id: segment.glyph_index,
+            }).collect();
+        }
+
+        shaped.glyphs.clone()
+    }
+}
+
+#[test]
+fn test_text_input_editing() {
+    use Theme;
+    use theme::ElementStyle;
+    use gui::input::{Event, KeyEvent, ScanCode};
+
+    let mut theme = Theme::empty();
+    theme.element_styles.insert((Default::default(), element_kind!("TextInput")), Arc::new(ElementStyle {
+        font: Arc::new(text::MonospaceFont::default()),
+        ..ElementStyle::default()
+    }));
+
+    let mut gui = Gui::new(theme);
+    let mut input = TextInput::new();
+    input.max_length = Some(12);
+    gui.focus(input.id());
+
+    let frame = |gui: &mut Gui, input: &mut TextInput, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+        input.appear(gui);
+        gui.advance();
+    };
+
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+
+    frame(&mut gui, &mut input, &[Event::Char('a'), Event::Char('b'), Event::Char('\u{8}')]);
+    gui.input.ime_commit("cd ef");
+    frame(&mut gui, &mut input, &[]);
+    assert_eq!(input.text(), "abcd ef");
+    assert!(input.changed());
+
+    // Text composed by the input method is shown at the caret without changing the text.
+    input.set_selection(2..2);
+    gui.input.ime_preedit("xy", Some((1, 1)));
+    frame(&mut gui, &mut input, &[]);
+    assert_eq!(input.shaped.as_ref().unwrap().text, "abxycd ef");
+    assert_eq!(input.position_of(4), 60.0);
+    let left = gui.layout.prev_value_rect(Rect::from(input.id())).left - input.scroll;
+    assert_eq!(gui.layout.prev_value_rect(Rect::from(input.caret_id)).left - left, 30.0);
+    assert_eq!(gui.layout.prev_value_rect(Rect::from(input.preedit_id)).right - left, 40.0);
+    assert_eq!(input.text(), "abcd ef");
+
+    gui.input.ime_preedit("", None);
+    input.set_selection(7..7);
+    frame(&mut gui, &mut input, &[]);
+    assert_eq!(input.shaped.as_ref().unwrap().text, "abcd ef");
+
+    frame(&mut gui, &mut input, &[key(Key::LShift, true), key(Key::LControl, true), key(Key::Left, true)]);
+    assert_eq!(input.selected_text(), "ef");
+
+    frame(&mut gui, &mut input, &[key(Key::Left, false), key(Key::X, true)]);
+    assert_eq!(input.text(), "abcd ");
+
+    frame(&mut gui, &mut input, &[key(Key::X, false), key(Key::LShift, false), key(Key::Home, true), key(Key::V, true)]);
+    assert_eq!(input.text(), "efabcd ");
+    assert_eq!(input.selection(), 2..2);
+
+    frame(&mut gui, &mut input, &[key(Key::V, false), key(Key::Home, false), key(Key::LControl, false)]);
+    frame(&mut gui, &mut input, &[key(Key::Delete, true)]);
+    frame(&mut gui, &mut input, &[key(Key::Delete, false), key(Key::End, true)]);
+    gui.input.ime_commit("0123456789");
+    frame(&mut gui, &mut input, &[key(Key::End, false)]);
+    assert_eq!(input.text(), "efbcd 012345");
+
+    frame(&mut gui, &mut input, &[key(Key::Backspace, true)]);
+    assert_eq!(input.text(), "efbcd 01234");
+
+    let mut placeholder = TextInput::new();
+    placeholder.placeholder = "Search".to_owned();
+    placeholder.appear(&mut gui);
+    assert_eq!(placeholder.shaped.as_ref().unwrap().text, "Search");
+
+    input.password = true;
+    input.set_text("pass");
+    frame(&mut gui, &mut input, &[]);
+    assert_eq!(input.shaped.as_ref().unwrap().text, "••••");
+    assert_eq!(input.position_of(2), 20.0);
+    assert_eq!(input.offset_at(31.0), 3);
+}
@@ -0,0 +1,142 @@
+use {Id, Gui, Rect, Var};
+use layout;
+use super::{Widget, ClickArea, activated_by_keyboard};
+
+/// Switch which is either on or off.
+///
+/// Consists of the `Toggle` element which is twice as wide as it is high and the square thumb placed at its left side
+/// when off and at its right side when on. The thumb is a `Toggle` element styled with the `thumb` style variant
+/// (`Toggle.thumb` in CSS) which rules for the whole `Toggle` don't apply to. Both get the `checked` pseudo-class when
+/// on.
+pub struct Toggle {
+    id: Id,
+    thumb_id: Id,
+    click_area: ClickArea,
+    on: bool,
+    changed: bool,
+}
+
+impl Widget for Toggle {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let thumb_place = Rect::from(self.thumb_id);
+        let pseudo_classes = if self.on { vec![pseudo_class!("checked")] } else { vec![] };
+
+        gui.focusable(self.id);
+
+        let (thumb_id, on) = (self.thumb_id, self.on);
+        let click_area = &mut self.click_area;
+
+        gui.element_in_state(self.id, element_kind!("Toggle"), &pseudo_classes, |gui| {
+            let click_area_place = click_area.appear(gui);
+            gui.part(thumb_id, element_kind!("Toggle"), style_variant!("thumb"), &pseudo_classes, |_gui| {});
+
+            layout::equal(gui, place, click_area_place);
+            add_constraints!(gui.layout, [
+                (place.right - place.left) == (place.bottom - place.top)*2.0,
+                (thumb_place.top) == place.top,
+                (thumb_place.bottom) == place.bottom,
+                (thumb_place.right - thumb_place.left) == thumb_place.bottom - thumb_place.top,
+            ]);
+
+            if on {
+                add_constraints!(gui.layout, [(thumb_place.right) == place.right]);
+            } else {
+                add_constraints!(gui.layout, [(thumb_place.left) == place.left]);
+            }
+        });
+
+        let clicked = self.click_area.clicked();
+        if clicked {
+            gui.focus(self.id);
+        }
+
+        if clicked || activated_by_keyboard(gui, self.id) {
+            self.on = !self.on;
+            self.changed = true;
+        }
+
+        place
+    }
+}
+
+impl Toggle {
+    pub fn new() -> Self {
+        Toggle {
+            id: Id::unique(),
+            thumb_id: Id::unique(),
+            click_area: ClickArea::new(),
+            on: false,
+            changed: false,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    pub fn set_on(&mut self, on: bool) {
+        self.on = on;
+    }
+
+    /// State was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+}
+
+#[test]
+fn test_toggle_keyboard() {
+    use std::sync::Arc;
+    use Theme;
+    use theme::ElementStyle;
+    use gui::input::{Event, KeyEvent, ScanCode, Key};
+    use gui::scene::Command;
+
+    let mut theme = Theme::empty();
+    theme.element_styles.insert((style_variant!("thumb"), element_kind!("Toggle")), Arc::new(ElementStyle {
+        opacity: 0.5,
+        ..ElementStyle::default()
+    }));
+    theme.element_styles.insert((Default::default(), element_kind!("Toggle")), Arc::new(ElementStyle {
+        opacity: 0.75,
+        ..ElementStyle::default()
+    }));
+
+    let mut gui = Gui::new(theme);
+    let mut toggle = Toggle::new();
+
+    let space = |pressed| Event::Key(KeyEvent { key: Some(Key::Space), scan_code: ScanCode(0), pressed });
+    let mut states = Vec::new();
+
+    for i in 0..8 {
+        // Focus is given on the second frame, pressing Space before that does nothing.
+        if i == 2 {
+            gui.focus(toggle.id());
+        }
+
+        gui.input.event(&space(i % 2 == 0));
+        toggle.appear(&mut gui);
+
+        if toggle.changed() {
+            states.push(toggle.is_on());
+        }
+
+        // Only the thumb is styled as `Toggle.thumb` and only the whole toggle as `Toggle`.
+        let opacities: Vec<_> = gui.scene.commands().iter().filter_map(|command| match *command {
+            Command::CloseElement(ref element) => Some((element.id, element.style.opacity)),
+            _ => None,
+        }).collect();
+        assert_eq!(opacities, vec![(toggle.thumb_id, 0.5), (toggle.id(), 0.75)]);
+
+        gui.advance();
+    }
+
+    assert_eq!(states, vec![true, false, true]);
+}
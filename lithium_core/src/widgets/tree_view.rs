@@ -0,0 +1,603 @@
+use std::collections::{HashMap, HashSet};
+use {Id, Gui, Rect, Var, Vec2};
+use gui::input::{ButtonState, Key, Direction};
+use theme::PseudoClass;
+use super::{Widget, ClickArea, VirtualList, ListModel, RowHeight};
+
+/// Keys of the parts of a row derived from the row id.
+const HANDLE: u64 = 0;
+const EXPANDER: u64 = 1;
+const LABEL: u64 = 2;
+
+/// Nodes shown by a `TreeView`. Each node is identified by a key which is unique in the whole tree.
+pub trait TreeModel {
+    /// Children of the `node` or the roots of the tree if it is `None`.
+    ///
+    /// This is called when the node is expanded for the first time, so children can be loaded lazily. The tree keeps
+    /// them until `TreeView::reload` is called.
+    fn children(&mut self, node: Option<u64>) -> Vec<u64>;
+
+    /// Node may have children and shows the expander. Children are not loaded until the node is expanded.
+    fn has_children(&self, node: u64) -> bool;
+
+    /// Show the node inside of the label `id`. Place of the label is the rest of the row after the indentation.
+    fn label(&mut self, gui: &mut Gui, node: u64, id: Id);
+
+    /// Move the `node` so that it becomes the child of the `parent` at `index`. Index is counted among the children
+    /// without the moved node.
+    ///
+    /// This is called when a node is dropped after dragging its handle. Returning `false` rejects the move.
+    fn move_node(&mut self, _node: u64, _parent: Option<u64>, _index: usize) -> bool {
+        false
+    }
+}
+
+/// Hierarchical list of nodes which can be expanded and collapsed.
+///
+/// Built on `VirtualList`, so only the visible rows appear and the selection works the same way. Additionally the Right
+/// key expands the node with the cursor or moves to its first child and the Left key collapses it or moves to its
+/// parent.
+///
+/// The tree is the `TreeView` element and each row is a `TreeView` element styled with the `row` style variant
+/// (`TreeView.row` in CSS) with the `selected`, `current` and `expanded` pseudo-classes. A row starts with the
+/// `TreeViewHandle` which can be dragged to move the node, followed by the indentation, the `TreeViewExpander` (only
+/// for nodes which may have children) and the label. While dragging the place where the node would be dropped is shown
+/// by the `TreeViewDropIndicator`.
+pub struct TreeView<T: TreeModel> {
+    list: VirtualList<TreeRows<T>>,
+    drop_indicator_id: Id,
+
+    /// Node which is being dragged and where it would be dropped.
+    drag: Option<u64>,
+    drop: Option<DropTarget>,
+}
+
+struct TreeRows<T: TreeModel> {
+    model: T,
+    indent: f64,
+
+    rows: Vec<TreeRow>,
+    children: HashMap<Option<u64>, Vec<u64>>,
+    parents: HashMap<u64, Option<u64>>,
+    expanded: HashSet<u64>,
+    expanders: HashMap<u64, ClickArea>,
+
+    /// Grabs the mouse while a node is dragged.
+    drag_id: Id,
+    drag_started: Option<u64>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct TreeRow {
+    node: u64,
+    parent: Option<u64>,
+    depth: usize,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct DropTarget {
+    parent: Option<u64>,
+    index: usize,
+
+    /// Row at whose top or bottom edge the indicator is shown.
+    row: u64,
+    above: bool,
+}
+
+impl<T: TreeModel> Widget for TreeView<T> {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let toggled: Vec<u64> = self.list.model.expanders.iter_mut()
+            .filter_map(|(&node, click_area)| if click_area.clicked() { Some(node) } else { None })
+            .collect();
+        for node in toggled {
+            self.toggle(node);
+        }
+
+        if gui.is_focused(self.list.id()) {
+            self.handle_keyboard(gui);
+        }
+
+        self.handle_drag(gui);
+
+        let cursor_node = self.cursor_node();
+        self.list.model.flatten();
+
+        // Rows above the cursor may have been expanded or collapsed.
+        if let Some(cursor_node) = cursor_node {
+            let index = self.list.model.rows.iter().position(|row| row.node == cursor_node);
+            if index.is_some() && index != self.list.cursor() {
+                self.list.set_cursor(index);
+            }
+        }
+
+        let place = self.list.appear(gui);
+
+        if let Some(node) = self.list.model.drag_started.take() {
+            self.drag = Some(node);
+            self.drop = None;
+        }
+
+        if let (Some(_), Some(drop)) = (self.drag, self.drop) {
+            let row = gui.layout.prev_value_rect(Rect::from(self.list.row_id(drop.row)));
+            let y = if drop.above { row.top } else { row.bottom };
+            let indicator = Rect::from(self.drop_indicator_id);
+
+            gui.element(self.drop_indicator_id, element_kind!("TreeViewDropIndicator"), |_gui| {});
+            add_constraints!(gui.layout, [
+                (indicator.left) == place.left,
+                (indicator.right) == place.right,
+                (indicator.top) == y - 1.0,
+                (indicator.bottom) == y + 1.0,
+            ]);
+        }
+
+        place
+    }
+}
+
+impl<T: TreeModel> TreeView<T> {
+    pub fn new(model: T, row_height: f64) -> Self {
+        let mut list = VirtualList::new(TreeRows {
+            model,
+            indent: 16.0,
+
+            rows: Vec::new(),
+            children: HashMap::new(),
+            parents: HashMap::new(),
+            expanded: HashSet::new(),
+            expanders: HashMap::new(),
+
+            drag_id: Id::unique(),
+            drag_started: None,
+        }, RowHeight::Fixed(row_height));
+
+        list.kind = element_kind!("TreeView");
+        list.row_kind = element_kind!("TreeView");
+        list.row_variant = Some(style_variant!("row"));
+
+        TreeView {
+            list,
+            drop_indicator_id: Id::unique(),
+            drag: None,
+            drop: None,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.list.id()
+    }
+
+    pub fn model(&self) -> &T {
+        &self.list.model.model
+    }
+
+    pub fn model_mut(&mut self) -> &mut T {
+        &mut self.list.model.model
+    }
+
+    /// Indentation of each level of the tree.
+    pub fn set_indent(&mut self, indent: f64) {
+        self.list.model.indent = indent;
+    }
+
+    pub fn set_multi_select(&mut self, multi_select: bool) {
+        self.list.multi_select = multi_select;
+    }
+
+    pub fn is_expanded(&self, node: u64) -> bool {
+        self.list.model.expanded.contains(&node)
+    }
+
+    /// Expand the node loading its children if they were not loaded yet.
+    pub fn expand(&mut self, node: u64) {
+        self.list.model.load(Some(node));
+        self.list.model.expanded.insert(node);
+    }
+
+    pub fn collapse(&mut self, node: u64) {
+        self.list.model.expanded.remove(&node);
+    }
+
+    /// Forget the loaded children of the `node` (or of the whole tree if `None`) so they are loaded again when needed.
+    pub fn reload(&mut self, node: Option<u64>) {
+        match node {
+            Some(node) => { self.list.model.children.remove(&Some(node)); }
+            None => self.list.model.children.clear(),
+        }
+    }
+
+    /// Node with the keyboard cursor.
+    pub fn cursor_node(&self) -> Option<u64> {
+        self.list.cursor().and_then(|index| self.list.model.rows.get(index)).map(|row| row.node)
+    }
+
+    pub fn is_selected(&self, node: u64) -> bool {
+        self.list.selected_keys().contains(&node)
+    }
+
+    pub fn selected_nodes(&self) -> Vec<u64> {
+        self.list.selected_keys().iter().cloned().collect()
+    }
+
+    /// Selection was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        self.list.changed()
+    }
+
+    fn toggle(&mut self, node: u64) {
+        if self.is_expanded(node) {
+            self.collapse(node);
+        } else {
+            self.expand(node);
+        }
+    }
+
+    /// Select the row like moving the cursor with the Up and Down keys does.
+    fn move_cursor(&mut self, index: usize) {
+        self.list.set_cursor(Some(index));
+        self.list.select(index);
+        self.list.scroll_to_index(index);
+    }
+
+    fn handle_keyboard(&mut self, gui: &mut Gui) {
+        // Left and Right expand and collapse instead of moving the focus.
+        gui.input.take_navigation(Direction::Left);
+        gui.input.take_navigation(Direction::Right);
+
+        let (index, row) = match self.list.cursor().and_then(|index| self.list.model.rows.get(index).map(|&row| (index, row))) {
+            Some(cursor) => cursor,
+            None => return,
+        };
+
+        if gui.input.keyboard.key_state(Key::Right) == ButtonState::JustPressed && self.list.model.model.has_children(row.node) {
+            if !self.is_expanded(row.node) {
+                self.expand(row.node);
+            } else if !self.list.model.children.get(&Some(row.node)).map(Vec::is_empty).unwrap_or(true) {
+                self.move_cursor(index + 1);
+            }
+        }
+
+        if gui.input.keyboard.key_state(Key::Left) == ButtonState::JustPressed {
+            if self.is_expanded(row.node) {
+                self.collapse(row.node);
+            } else if let Some(parent) = row.parent {
+                if let Some(parent_index) = self.list.model.rows.iter().position(|row| row.node == parent) {
+                    self.move_cursor(parent_index);
+                }
+            }
+        }
+    }
+
+    fn handle_drag(&mut self, gui: &mut Gui) {
+        let node = match self.drag {
+            Some(node) => node,
+            None => return,
+        };
+
+        let drag_id = self.list.model.drag_id;
+        let mouse = match gui.input.mouse_grabbed_by(drag_id) {
+            Some(mouse) => mouse,
+            None => {
+                self.drag = None;
+                self.drop = None;
+                return;
+            }
+        };
+
+        self.drop = self.drop_at(gui, node, mouse.position);
+
+        if mouse.primary_button.is_pressed() {
+            gui.input.grab_mouse(drag_id);
+            return;
+        }
+
+        if let Some(drop) = self.drop {
+            if self.list.model.model.move_node(node, drop.parent, drop.index) {
+                self.list.model.move_child(node, drop.parent, drop.index);
+            }
+        }
+
+        self.drag = None;
+        self.drop = None;
+    }
+
+    /// Place between the visible rows where the dragged `node` would be dropped.
+    fn drop_at(&self, gui: &Gui, node: u64, position: Vec2<f64>) -> Option<DropTarget> {
+        let rows = &self.list.model.rows;
+
+        let (index, place) = self.list.visible_rows().iter()
+            .map(|&(index, key)| (index, gui.layout.prev_value_rect(Rect::from(self.list.row_id(key)))))
+            .find(|&(_, place)| place.top <= position.y && position.y < place.bottom)?;
+
+        let row = rows[index];
+        let above = position.y < place.center().y;
+
+        // Node cannot be moved inside of itself.
+        let mut ancestor = Some(row.node);
+        while let Some(current) = ancestor {
+            if current == node {
+                return None;
+            }
+            ancestor = self.list.model.parents.get(&current).cloned().unwrap_or(None);
+        }
+
+        let siblings = self.list.model.children.get(&row.parent)?;
+        let mut sibling_index = siblings.iter().filter(|&&sibling| sibling != node).position(|&sibling| sibling == row.node)?;
+        if !above {
+            sibling_index += 1;
+        }
+
+        Some(DropTarget {
+            parent: row.parent,
+            index: sibling_index,
+            row: row.node,
+            above,
+        })
+    }
+}
+
+impl<T: TreeModel> TreeRows<T> {
+    fn load(&mut self, node: Option<u64>) {
+        if !self.children.contains_key(&node) {
+            let children = self.model.children(node);
+            self.children.insert(node, children);
+        }
+    }
+
+    /// Rows of the roots and of the children of the expanded nodes in the order they are shown.
+    fn flatten(&mut self) {
+        self.load(None);
+
+        let mut rows = Vec::new();
+        let mut stack: Vec<(Option<u64>, usize, usize)> = vec![(None, 0, 0)];
+
+        while let Some((parent, depth, next)) = stack.pop() {
+            let node = match self.children.get(&parent).and_then(|children| children.get(next)) {
+                Some(&node) => node,
+                None => continue,
+            };
+
+            stack.push((parent, depth, next + 1));
+            rows.push(TreeRow { node, parent, depth });
+
+            if self.expanded.contains(&node) {
+                self.load(Some(node));
+                stack.push((Some(node), depth + 1, 0));
+            }
+        }
+
+        self.parents = rows.iter().map(|row| (row.node, row.parent)).collect();
+        self.rows = rows;
+    }
+
+    fn move_child(&mut self, node: u64, parent: Option<u64>, index: usize) {
+        for children in self.children.values_mut() {
+            children.retain(|&child| child != node);
+        }
+
+        if let Some(children) = self.children.get_mut(&parent) {
+            let index = index.min(children.len());
+            children.insert(index, node);
+        }
+    }
+}
+
+impl<T: TreeModel> ListModel for TreeRows<T> {
+    fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn key(&self, index: usize) -> u64 {
+        self.rows[index].node
+    }
+
+    fn row_state(&self, index: usize, pseudo_classes: &mut Vec<PseudoClass>) {
+        if self.expanded.contains(&self.rows[index].node) {
+            pseudo_classes.push(pseudo_class!("expanded"));
+        }
+    }
+
+    fn row(&mut self, gui: &mut Gui, index: usize, id: Id) {
+        let row = self.rows[index];
+        let place = Rect::from(id);
+        let (handle_id, expander_id, label_id) = (id.child(HANDLE), id.child(EXPANDER), id.child(LABEL));
+        let (handle, expander, label) = (Rect::from(handle_id), Rect::from(expander_id), Rect::from(label_id));
+
+        let handle_value = gui.layout.prev_value_rect(handle);
+        if let Some(mouse) = gui.input.get_mouse(|pos| handle_value.contains(pos)) {
+            if mouse.primary_button == ButtonState::JustPressed {
+                gui.input.grab_mouse(self.drag_id);
+                self.drag_started = Some(row.node);
+            }
+        }
+        gui.element(handle_id, element_kind!("TreeViewHandle"), |_gui| {});
+
+        if self.model.has_children(row.node) {
+            let pseudo_classes = if self.expanded.contains(&row.node) { vec![pseudo_class!("expanded")] } else { vec![] };
+            let click_area = self.expanders.entry(row.node).or_insert_with(ClickArea::new);
+
+            gui.element_in_state(expander_id, element_kind!("TreeViewExpander"), &pseudo_classes, |gui| {
+                let click_area_place = click_area.appear(gui);
+                ::layout::equal(gui, expander, click_area_place);
+            });
+        }
+
+        let model = &mut self.model;
+        gui.element(label_id, element_kind!("TreeViewLabel"), |gui| {
+            model.label(gui, row.node, label_id);
+        });
+
+        let indent = self.indent*row.depth as f64;
+        add_constraints!(gui.layout, [
+            (handle.left) == place.left,
+            (handle.right - handle.left) == handle.bottom - handle.top,
+            (expander.left) == handle.right + indent,
+            (expander.right - expander.left) == expander.bottom - expander.top,
+            (label.left) == expander.right,
+            (label.right) == place.right,
+        ]);
+
+        ::layout::equal_horizontal(gui, place, handle);
+        ::layout::equal_horizontal(gui, place, expander);
+        ::layout::equal_horizontal(gui, place, label);
+    }
+}
+
+#[test]
+fn test_tree_view() {
+    use Theme;
+    use gui::input::{Event, KeyEvent, ScanCode, MouseButton};
+
+    #[derive(Default)]
+    struct Files {
+        loaded: Vec<Option<u64>>,
+        moved: Vec<(u64, Option<u64>, usize)>,
+    }
+
+    impl TreeModel for Files {
+        fn children(&mut self, node: Option<u64>) -> Vec<u64> {
+            self.loaded.push(node);
+            match node {
+                None => vec![1, 2],
+                Some(1) => vec![10, 11],
+                Some(10) => vec![100],
+                _ => vec![],
+            }
+        }
+
+        fn has_children(&self, node: u64) -> bool {
+            node == 1 || node == 10
+        }
+
+        fn label(&mut self, _gui: &mut Gui, _node: u64, _id: Id) {}
+
+        fn move_node(&mut self, node: u64, parent: Option<u64>, index: usize) -> bool {
+            self.moved.push((node, parent, index));
+            true
+        }
+    }
+
+    let mut gui = Gui::new(Theme::empty());
+    let mut tree = TreeView::new(Files::default(), 20.0);
+
+    let frame = |gui: &mut Gui, tree: &mut TreeView<Files>, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = tree.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 100.0,
+            (place.bottom) == 100.0,
+        ]);
+
+        gui.advance();
+    };
+
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+    let nodes = |tree: &TreeView<Files>| tree.list.model.rows.iter().map(|row| row.node).collect::<Vec<_>>();
+
+    frame(&mut gui, &mut tree, &[]);
+    frame(&mut gui, &mut tree, &[]);
+    assert_eq!(nodes(&tree), vec![1, 2]);
+
+    gui.focus(tree.id());
+    frame(&mut gui, &mut tree, &[key(Key::Down, true)]);
+    frame(&mut gui, &mut tree, &[key(Key::Down, false), key(Key::Right, true)]);
+    assert_eq!(nodes(&tree), vec![1, 10, 11, 2]);
+    assert_eq!(tree.model().loaded, vec![None, Some(1)]);
+
+    frame(&mut gui, &mut tree, &[key(Key::Right, false)]);
+    frame(&mut gui, &mut tree, &[key(Key::Right, true)]);
+    assert_eq!(tree.cursor_node(), Some(10));
+    assert_eq!(tree.selected_nodes(), vec![10]);
+
+    frame(&mut gui, &mut tree, &[key(Key::Right, false)]);
+    frame(&mut gui, &mut tree, &[key(Key::Left, true)]);
+    assert_eq!(tree.cursor_node(), Some(1));
+    frame(&mut gui, &mut tree, &[key(Key::Left, false)]);
+    frame(&mut gui, &mut tree, &[key(Key::Left, true)]);
+    assert_eq!(nodes(&tree), vec![1, 2]);
+
+    // Expander of the first row is after the 20 pixel handle.
+    frame(&mut gui, &mut tree, &[key(Key::Left, false), Event::MouseEntered, Event::MouseMoved(Vec2::new(30.0, 10.0)), Event::MouseButton(MouseButton::Primary, true)]);
+    frame(&mut gui, &mut tree, &[Event::MouseButton(MouseButton::Primary, false)]);
+    frame(&mut gui, &mut tree, &[]);
+    assert!(tree.is_expanded(1));
+    assert_eq!(nodes(&tree), vec![1, 10, 11, 2]);
+    assert_eq!(tree.model().loaded.len(), 2);
+
+    // Handle of the node 11 is dragged to the upper half of the node 10.
+    frame(&mut gui, &mut tree, &[Event::MouseMoved(Vec2::new(10.0, 50.0)), Event::MouseButton(MouseButton::Primary, true)]);
+    frame(&mut gui, &mut tree, &[Event::MouseMoved(Vec2::new(10.0, 25.0))]);
+    assert!(tree.drop.is_some());
+    frame(&mut gui, &mut tree, &[Event::MouseButton(MouseButton::Primary, false)]);
+    assert_eq!(tree.model().moved, vec![(11, Some(1), 0)]);
+    assert_eq!(nodes(&tree), vec![1, 11, 10, 2]);
+}
+
+#[test]
+fn test_tree_view_row_style() {
+    use std::sync::Arc;
+    use Theme;
+    use theme::ElementStyle;
+    use gui::scene::Command;
+
+    struct Nodes;
+
+    impl TreeModel for Nodes {
+        fn children(&mut self, node: Option<u64>) -> Vec<u64> {
+            if node.is_none() { vec![1, 2, 3] } else { vec![] }
+        }
+
+        fn has_children(&self, node: u64) -> bool {
+            node != 3
+        }
+
+        fn label(&mut self, _gui: &mut Gui, _node: u64, _id: Id) {}
+    }
+
+    let row = element_kind!("TreeView");
+    let (selected, expanded) = (pseudo_class!("selected"), pseudo_class!("expanded"));
+
+    let mut theme = Theme::empty();
+    let mut style = |variant, kind, opacity| {
+        theme.element_styles.insert((variant, kind), Arc::new(ElementStyle { opacity, ..ElementStyle::default() }));
+    };
+    style(style_variant!("default"), row, 0.75);
+    style(style_variant!("row"), row, 0.5);
+    style(style_variant!("row"), row.with_pseudo_class(selected), 0.25);
+    style(style_variant!("row"), row.with_pseudo_class(expanded), 0.125);
+    style(style_variant!("row"), row.with_pseudo_class(selected).with_pseudo_class(expanded), 0.0625);
+
+    let mut gui = Gui::new(theme);
+    let mut tree = TreeView::new(Nodes, 20.0);
+
+    let opacities = |gui: &mut Gui, tree: &mut TreeView<Nodes>| {
+        let place = tree.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 100.0,
+            (place.bottom) == 100.0,
+        ]);
+
+        let row_ids: Vec<_> = (1..4).map(|node| tree.list.row_id(node)).collect();
+        let opacities: Vec<_> = gui.scene.commands().iter().filter_map(|command| match *command {
+            Command::CloseElement(ref element) if row_ids.contains(&element.id) => Some(element.style.opacity),
+            _ => None,
+        }).collect();
+        gui.advance();
+        opacities
+    };
+
+    opacities(&mut gui, &mut tree);
+    tree.expand(1);
+    tree.expand(2);
+    tree.list.select(1);
+
+    // Every row is styled as `TreeView.row` in its state, the rule for the whole tree doesn't apply to the rows.
+    assert_eq!(opacities(&mut gui, &mut tree), vec![0.125, 0.0625, 0.5]);
+}
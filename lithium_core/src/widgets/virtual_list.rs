@@ -0,0 +1,532 @@
+use std::collections::{HashMap, HashSet};
+use {Id, Gui, Rect, Var};
+use gui::input::{ButtonState, Key, Direction};
+use theme::{ElementKind, StyleVariant, PseudoClass};
+use super::{Widget, modifiers};
+
+/// Rows scrolled by one line of the mouse wheel.
+const WHEEL_ROWS: f64 = 3.0;
+
+/// Items shown by a `VirtualList`.
+pub trait ListModel {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Key of the item which stays the same when other items are inserted or removed.
+    ///
+    /// Rows get their `Id`s and remember their selection and measured height by the key.
+    fn key(&self, index: usize) -> u64 {
+        index as u64
+    }
+
+    /// Add pseudo-classes describing the state of the row at `index` in addition to `selected` and `current`.
+    fn row_state(&self, _index: usize, _pseudo_classes: &mut Vec<PseudoClass>) {}
+
+    /// Show the item at `index` inside of the row `id`.
+    ///
+    /// Position and width of `Rect::from(id)` are set by the list. Height is set by the list as well if it is
+    /// `RowHeight::Fixed`, otherwise it must be constrained here.
+    fn row(&mut self, gui: &mut Gui, index: usize, id: Id);
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum RowHeight {
+    /// All rows have this height.
+    Fixed(f64),
+
+    /// Rows choose their own height. This is used for the rows which have not been shown yet.
+    Estimated(f64),
+}
+
+/// Vertical list which shows only the rows in its view so it stays fast with a huge number of items.
+///
+/// Scroll position is kept as the first row in the view and the part of it which is scrolled away, so nothing is ever
+/// computed for the rows outside of the view. The list is the `VirtualList` element which should have `overflow: hidden`
+/// and the rows are `VirtualListRow` elements with the `selected` pseudo-class and `current` for the row with the cursor.
+///
+/// While focused the cursor is moved by the Up and Down keys, PageUp, PageDown, Home and End. Moving the cursor selects
+/// the row, with Shift the range from the previously selected row and with Control only the cursor moves. Space selects
+/// the row with the cursor or toggles it with Control and Control+A selects all rows. Clicks select rows the same way.
+pub struct VirtualList<M: ListModel> {
+    id: Id,
+
+    /// First row in the view and how much of it is scrolled above the top.
+    first: usize,
+    first_offset: f64,
+
+    /// Rows shown during the last frame.
+    shown: Vec<(usize, u64)>,
+    heights: HashMap<u64, f64>,
+    reveal: Option<usize>,
+
+    selected: HashSet<u64>,
+    cursor: Option<usize>,
+    anchor: Option<usize>,
+    changed: bool,
+
+    pub model: M,
+    pub row_height: RowHeight,
+
+    /// Element kinds of the list and of the rows, widgets built on the list use their own.
+    pub kind: ElementKind,
+    pub row_kind: ElementKind,
+
+    /// Style variant of the rows, by default they use the variant of the list. Rows with a variant are parts of the list,
+    /// see `Gui::part`.
+    pub row_variant: Option<StyleVariant>,
+
+    /// More than one row can be selected.
+    pub multi_select: bool,
+}
+
+impl<M: ListModel> Widget for VirtualList<M> {
+    fn appear(&mut self, gui: &mut Gui) -> Rect<Var> {
+        let place = Rect::from(self.id);
+        let view = gui.layout.prev_value_rect(place);
+        let len = self.model.len();
+
+        gui.focusable(self.id);
+
+        if let RowHeight::Estimated(_) = self.row_height {
+            for &(_, key) in &self.shown {
+                let height = gui.layout.prev_value_rect(Rect::from(self.id.child(key))).height();
+                if height > 0.0 {
+                    self.heights.insert(key, height);
+                }
+            }
+        }
+
+        if gui.is_focused(self.id) {
+            self.handle_keyboard(gui, len);
+        }
+
+        // Rows are rarely scrollable themselves, so the list takes the wheel before them to show the scrolled rows in
+        // the same frame.
+        let wheel_line = WHEEL_ROWS*self.estimated_height();
+        if let Some(wheel) = gui.input.take_wheel(wheel_line, |pos| view.contains(pos)) {
+            self.scroll_by(-wheel.y, len);
+        }
+
+        // Until the list has appeared its view has no height and revealing would scroll past the row, so that waits.
+        if view.height() > 0.0 {
+            if let Some(index) = self.reveal.take() {
+                if len > 0 {
+                    self.reveal_row(index.min(len - 1), view.height(), len);
+                }
+            }
+        }
+        self.clamp_scroll(view.height(), len);
+
+        let mut rows = Vec::new();
+        let mut bottom = -self.first_offset;
+        let mut index = self.first;
+        while index < len && bottom < view.height() {
+            rows.push((index, self.model.key(index)));
+            bottom += self.height(index);
+            index += 1;
+        }
+
+        let (id, cursor, row_height, first_offset) = (self.id, self.cursor, self.row_height, self.first_offset);
+        let (kind, row_kind, row_variant) = (self.kind, self.row_kind, self.row_variant);
+        let selected = &self.selected;
+        let model = &mut self.model;
+
+        gui.element(id, kind, |gui| {
+            let mut previous: Option<Rect<Var>> = None;
+
+            for &(index, key) in &rows {
+                let row_id = id.child(key);
+                let row = Rect::from(row_id);
+
+                let mut pseudo_classes = Vec::new();
+                if selected.contains(&key) {
+                    pseudo_classes.push(pseudo_class!("selected"));
+                }
+                if cursor == Some(index) {
+                    pseudo_classes.push(pseudo_class!("current"));
+                }
+                model.row_state(index, &mut pseudo_classes);
+
+                match row_variant {
+                    Some(row_variant) => {
+                        gui.part(row_id, row_kind, row_variant, &pseudo_classes, |gui| model.row(gui, index, row_id));
+                    }
+                    None => gui.element_in_state(row_id, row_kind, &pseudo_classes, |gui| model.row(gui, index, row_id)),
+                }
+
+                add_constraints!(gui.layout, [
+                    (row.left) == place.left,
+                    (row.right) == place.right,
+                ]);
+
+                match previous {
+                    Some(previous) => { add_constraints!(gui.layout, [(row.top) == previous.bottom]); }
+                    None => { add_constraints!(gui.layout, [(row.top) == place.top - first_offset]); }
+                }
+
+                if let RowHeight::Fixed(height) = row_height {
+                    add_constraints!(gui.layout, [(row.bottom) == row.top + height]);
+                }
+
+                previous = Some(row);
+            }
+        });
+
+        // Row content takes the mouse first, so clicking for example a checkbox in a row does not change the selection.
+        self.handle_mouse(gui, view);
+        self.shown = rows;
+
+        place
+    }
+}
+
+impl<M: ListModel> VirtualList<M> {
+    pub fn new(model: M, row_height: RowHeight) -> Self {
+        VirtualList {
+            id: Id::unique(),
+
+            first: 0,
+            first_offset: 0.0,
+
+            shown: Vec::new(),
+            heights: HashMap::new(),
+            reveal: None,
+
+            selected: HashSet::new(),
+            cursor: None,
+            anchor: None,
+            changed: false,
+
+            model,
+            row_height,
+            kind: element_kind!("VirtualList"),
+            row_kind: element_kind!("VirtualListRow"),
+            row_variant: None,
+            multi_select: true,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Id of the row showing the item with the `key`.
+    pub fn row_id(&self, key: u64) -> Id {
+        self.id.child(key)
+    }
+
+    /// Indices and keys of the rows which appeared during the last frame.
+    pub fn visible_rows(&self) -> &[(usize, u64)] {
+        &self.shown
+    }
+
+    /// Index of the first row in the view.
+    pub fn first_visible(&self) -> usize {
+        self.first
+    }
+
+    /// Scroll during the next frame as little as possible so that the row at `index` is in the view.
+    pub fn scroll_to_index(&mut self, index: usize) {
+        self.reveal = Some(index);
+    }
+
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    pub fn set_cursor(&mut self, cursor: Option<usize>) {
+        self.cursor = cursor;
+        self.anchor = cursor;
+    }
+
+    pub fn is_selected(&self, index: usize) -> bool {
+        self.selected.contains(&self.model.key(index))
+    }
+
+    /// Keys of the selected items.
+    pub fn selected_keys(&self) -> &HashSet<u64> {
+        &self.selected
+    }
+
+    /// Indices of the selected items. This goes through all of the items.
+    pub fn selected(&self) -> Vec<usize> {
+        (0..self.model.len()).filter(|&index| self.is_selected(index)).collect()
+    }
+
+    /// Select only the item at `index`.
+    pub fn select(&mut self, index: usize) {
+        self.selected.clear();
+        self.selected.insert(self.model.key(index));
+        self.anchor = Some(index);
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selected.clear();
+    }
+
+    /// Selection was changed by the user since the last call.
+    pub fn changed(&mut self) -> bool {
+        let prev = self.changed;
+        self.changed = false;
+        prev
+    }
+
+    fn estimated_height(&self) -> f64 {
+        match self.row_height {
+            RowHeight::Fixed(height) | RowHeight::Estimated(height) => height,
+        }
+    }
+
+    /// Measured height of the row or the estimate if it was not shown yet.
+    fn height(&self, index: usize) -> f64 {
+        match self.row_height {
+            RowHeight::Fixed(height) => height,
+            RowHeight::Estimated(height) => self.heights.get(&self.model.key(index)).cloned().unwrap_or(height),
+        }
+    }
+
+    /// Move the view down by `delta` (or up if negative) going through as few rows as needed.
+    fn scroll_by(&mut self, delta: f64, len: usize) {
+        self.first_offset += delta;
+
+        while self.first_offset < 0.0 && self.first > 0 {
+            self.first -= 1;
+            self.first_offset += self.height(self.first);
+        }
+
+        while self.first + 1 < len && self.first_offset >= self.height(self.first) {
+            self.first_offset -= self.height(self.first);
+            self.first += 1;
+        }
+    }
+
+    /// Rows below the top of the view must fill it unless the whole list is shorter than the view.
+    fn clamp_scroll(&mut self, view_height: f64, len: usize) {
+        if len == 0 {
+            self.first = 0;
+            self.first_offset = 0.0;
+            return;
+        }
+
+        self.first = self.first.min(len - 1);
+        self.first_offset = self.first_offset.max(0.0);
+
+        let mut bottom = -self.first_offset;
+        let mut index = self.first;
+        while index < len && bottom < view_height {
+            bottom += self.height(index);
+            index += 1;
+        }
+
+        if bottom < view_height {
+            self.scroll_by(bottom - view_height, len);
+            self.first_offset = self.first_offset.max(0.0);
+        }
+    }
+
+    fn reveal_row(&mut self, index: usize, view_height: f64, len: usize) {
+        if index < self.first || (index == self.first && self.first_offset > 0.0) {
+            self.first = index;
+            self.first_offset = 0.0;
+            return;
+        }
+
+        let mut bottom = -self.first_offset;
+        let mut i = self.first;
+        while i <= index && bottom <= view_height {
+            bottom += self.height(i);
+            i += 1;
+        }
+
+        if i > index && bottom <= view_height {
+            return;
+        }
+
+        // Bottom of the row is aligned with the bottom of the view.
+        self.first = index;
+        self.first_offset = 0.0;
+        let height = self.height(index);
+        self.scroll_by(height - view_height, len);
+    }
+
+    fn handle_keyboard(&mut self, gui: &mut Gui, len: usize) {
+        // Up and Down move the cursor instead of the focus.
+        gui.input.take_navigation(Direction::Up);
+        gui.input.take_navigation(Direction::Down);
+
+        if len == 0 {
+            return;
+        }
+
+        let (shift, control) = modifiers(gui);
+        let keyboard = &gui.input.keyboard;
+        let page = self.shown.len().saturating_sub(1).max(1) as isize;
+
+        let steps = keyboard.presses(Key::Down) as isize - keyboard.presses(Key::Up) as isize +
+            (keyboard.presses(Key::PageDown) as isize - keyboard.presses(Key::PageUp) as isize)*page;
+
+        let current = self.cursor.map(|cursor| cursor.min(len - 1));
+        let moved = if keyboard.presses(Key::Home) > 0 {
+            Some(0)
+        } else if keyboard.presses(Key::End) > 0 {
+            Some(len - 1)
+        } else if steps != 0 {
+            Some(current.map_or(0, |current| (current as isize + steps).max(0).min(len as isize - 1) as usize))
+        } else {
+            None
+        };
+
+        if let Some(cursor) = moved {
+            self.cursor = Some(cursor);
+            self.reveal = Some(cursor);
+
+            if !control {
+                self.select_by_user(cursor, shift, false);
+            }
+        }
+
+        if keyboard.key_state(Key::Space) == ButtonState::JustPressed {
+            if let Some(cursor) = current {
+                self.select_by_user(cursor, false, control);
+            }
+        }
+
+        if control && self.multi_select && keyboard.presses(Key::A) > 0 {
+            for index in 0..len {
+                self.selected.insert(self.model.key(index));
+            }
+            self.changed = true;
+        }
+    }
+
+    /// Rows shown during the previous frame are where the pointer sees them.
+    fn handle_mouse(&mut self, gui: &mut Gui, view: Rect<f64>) {
+        let mouse = match gui.input.get_mouse(|pos| view.contains(pos)) {
+            Some(mouse) => mouse,
+            None => return,
+        };
+
+        if mouse.primary_button != ButtonState::JustPressed {
+            return;
+        }
+
+        gui.focus(self.id);
+
+        let id = self.id;
+        let clicked = self.shown.iter().find(|&&(_, key)| {
+            gui.layout.prev_value_rect(Rect::from(id.child(key))).contains(mouse.position)
+        }).cloned();
+
+        if let Some((index, _)) = clicked {
+            let (shift, control) = modifiers(gui);
+            self.cursor = Some(index);
+            self.select_by_user(index, shift, control);
+        }
+    }
+
+    /// Select the row like a click does: only the row, with `range` the rows from the anchor and with `toggle` add or
+    /// remove the row.
+    fn select_by_user(&mut self, index: usize, range: bool, toggle: bool) {
+        let key = self.model.key(index);
+
+        if range && self.multi_select {
+            let anchor = self.anchor.unwrap_or(index);
+            self.selected.clear();
+            for i in anchor.min(index)..anchor.max(index) + 1 {
+                self.selected.insert(self.model.key(i));
+            }
+        } else if toggle && self.multi_select {
+            if !self.selected.remove(&key) {
+                self.selected.insert(key);
+            }
+            self.anchor = Some(index);
+        } else {
+            self.selected.clear();
+            self.selected.insert(key);
+            self.anchor = Some(index);
+        }
+
+        self.changed = true;
+    }
+}
+
+#[test]
+fn test_virtual_list() {
+    use {Theme, Vec2};
+    use gui::input::{Event, KeyEvent, ScanCode, MouseButton};
+    use gui::scene::Command;
+
+    struct Log {
+        len: usize,
+    }
+
+    impl ListModel for Log {
+        fn len(&self) -> usize {
+            self.len
+        }
+
+        fn row(&mut self, _gui: &mut Gui, _index: usize, _id: Id) {}
+    }
+
+    let mut gui = Gui::new(Theme::empty());
+    let mut list = VirtualList::new(Log { len: 500_000 }, RowHeight::Fixed(20.0));
+
+    // Returns the number of rows which appeared.
+    let frame = |gui: &mut Gui, list: &mut VirtualList<Log>, events: &[Event]| {
+        for event in events {
+            gui.input.event(event);
+        }
+
+        let place = list.appear(gui);
+        add_constraints!(gui.layout, [
+            (place.left) == 0.0,
+            (place.top) == 0.0,
+            (place.right) == 100.0,
+            (place.bottom) == 100.0,
+        ]);
+
+        let rows = gui.scene.commands().iter().filter(|command| match **command {
+            Command::CloseElement(ref element) => element.kind == element_kind!("VirtualListRow"),
+            _ => false,
+        }).count();
+
+        gui.advance();
+        rows
+    };
+
+    let key = |key, pressed| Event::Key(KeyEvent { key: Some(key), scan_code: ScanCode(0), pressed });
+
+    frame(&mut gui, &mut list, &[]);
+    assert_eq!(frame(&mut gui, &mut list, &[]), 5);
+
+    gui.focus(list.id());
+    frame(&mut gui, &mut list, &[key(Key::Down, true)]);
+    frame(&mut gui, &mut list, &[key(Key::Down, false)]);
+    frame(&mut gui, &mut list, &[key(Key::Down, true)]);
+    frame(&mut gui, &mut list, &[key(Key::Down, false), key(Key::LShift, true)]);
+    frame(&mut gui, &mut list, &[key(Key::Down, true)]);
+    assert_eq!(list.cursor(), Some(2));
+    assert_eq!(list.selected_keys().len(), 2);
+    assert!(list.is_selected(1) && list.is_selected(2));
+    assert!(list.changed());
+
+    // Row 1000 is aligned with the bottom of the view.
+    list.scroll_to_index(1000);
+    assert_eq!(frame(&mut gui, &mut list, &[key(Key::Down, false), key(Key::LShift, false)]), 5);
+    assert_eq!(list.first_visible(), 996);
+    assert_eq!(gui.layout.prev_value(Rect::from(list.row_id(1000)).bottom), 100.0);
+
+    frame(&mut gui, &mut list, &[key(Key::End, true)]);
+    assert_eq!(list.cursor(), Some(499_999));
+    frame(&mut gui, &mut list, &[key(Key::End, false)]);
+    assert_eq!(list.first_visible(), 499_995);
+    assert_eq!(list.selected(), vec![499_999]);
+
+    // Clicking the second visible row selects it.
+    frame(&mut gui, &mut list, &[Event::MouseEntered, Event::MouseMoved(Vec2::new(50.0, 30.0)), Event::MouseButton(MouseButton::Primary, true)]);
+    assert_eq!(list.cursor(), Some(499_996));
+    assert!(list.is_selected(499_996) && !list.is_selected(499_999));
+}
@@ -8,6 +8,7 @@ extern crate webrender;
 extern crate webrender_api;
 
 use std::thread;
+use std::time::Instant;
 use gleam::gl;
 use webrender_api::{ColorF, Epoch};
 use webrender_api::{DeviceUintPoint, DeviceUintSize, DeviceUintRect, LayoutPoint, LayoutRect, LayoutSize};
@@ -70,13 +71,19 @@ fn window_thread<W: Widget>(mut widget: W, theme: Theme, window_builder: glutin:
     api.set_root_pipeline(pipeline_id);
 
     let mut gui = lithium_core::Gui::new(theme);
+    let mut images = lithium_webrender::Images::new();
+    let start_time = Instant::now();
 
     let mut resized = false;
-    while process_events(&mut gui, &event_loop, &window, &mut width, &mut height, &mut resized) {
-        let place = widget.appear(&mut gui);
+    let mut wait = false;
+    while process_events(&mut gui, &event_loop, &window, &mut width, &mut height, &mut resized, wait) {
+        gui.input.set_time(start_time.elapsed());
 
         let hidpi_factor = window.hidpi_factor() as f64;
-        
+        gui.set_window_size(Vec2::new(width as f64/hidpi_factor, height as f64/hidpi_factor));
+
+        let place = widget.appear(&mut gui);
+
         add_constraints!(gui.layout, [
             (place.left) == 0.0,
             (place.top) == 0.0,
@@ -91,7 +98,7 @@ fn window_thread<W: Widget>(mut widget: W, theme: Theme, window_builder: glutin:
         }
 
         // render
-        render(&gui, pipeline_id, (width, height), epoch, &api);
+        render(&gui, pipeline_id, (width, height), epoch, &api, &mut images);
         renderer.update();
         renderer.render(DeviceUintSize::new(width, height));
         epoch.0 += 1;
@@ -99,13 +106,17 @@ fn window_thread<W: Widget>(mut widget: W, theme: Theme, window_builder: glutin:
         window.swap_buffers().ok();
 
         gui.advance();
+
+        // Nothing changes until the next event unless something is animating or the layout has not settled yet.
+        wait = !gui.next_frame_requested();
     }
 }
 
-fn process_events(gui: &mut lithium_core::Gui, event_loop: &glutin::EventsLoop, window: &glutin::Window, width: &mut u32, height: &mut u32, resized: &mut bool) -> bool {
+/// Pass the pending events to the `gui`, if `wait` is set then block until at least one event arrives.
+fn process_events(gui: &mut lithium_core::Gui, event_loop: &glutin::EventsLoop, window: &glutin::Window, width: &mut u32, height: &mut u32, resized: &mut bool, wait: bool) -> bool {
     let mut stop = false;
 
-    event_loop.poll_events(|event| {
+    let mut process = |event: glutin::Event| {
         if stop {
             return;
         }
@@ -124,12 +135,20 @@ fn process_events(gui: &mut lithium_core::Gui, event_loop: &glutin::EventsLoop,
                 _ => ()
             }
         }
-    });
+    };
+
+    if wait {
+        event_loop.run_forever(|event| {
+            process(event);
+            event_loop.interrupt();
+        });
+    }
+    event_loop.poll_events(process);
 
     !stop
 }
 
-fn render(gui: &lithium_core::Gui, pipeline_id: PipelineId, (width, height): (u32, u32), epoch: Epoch, api: &RenderApi) {
+fn render(gui: &lithium_core::Gui, pipeline_id: PipelineId, (width, height): (u32, u32), epoch: Epoch, api: &RenderApi, images: &mut lithium_webrender::Images) {
     let layout_size = LayoutSize::new(width as f32, height as f32);
     let mut builder = webrender_api::DisplayListBuilder::new(pipeline_id, layout_size);
     let bounds = LayoutRect::new(LayoutPoint::zero(), layout_size);
@@ -143,10 +162,12 @@ fn render(gui: &lithium_core::Gui, pipeline_id: PipelineId, (width, height): (u3
         Vec::new()
     );
 
-    lithium_webrender::build(Vec2::new(width as f64, height as f64), gui.scene.commands(), &mut builder);
+    lithium_webrender::build(Vec2::new(width as f64, height as f64), gui.scene.commands(), api, images, &mut builder);
     
     builder.pop_stacking_context();
 
+    api.update_resources(images.take_updates());
+
     let root_background_color = ColorF::new(0.0, 0.0, 0.0, 1.0);
     api.set_display_list(
         Some(root_background_color),
@@ -1,8 +1,12 @@
 extern crate webrender_api;
 extern crate lithium_core;
 
+use std::mem::replace;
 use webrender_api::{DisplayListBuilder, ColorF, GradientStop, LayoutPoint, LayoutSize, LayoutRect, ComplexClipRegion};
-use lithium_core::gui::scene::Command;
+use webrender_api::{RenderApi, ResourceUpdates, ImageKey, ImageDescriptor, ImageFormat, ImageData, ImageRendering};
+use webrender_api::{DeviceUintPoint, DeviceUintSize, DeviceUintRect};
+use lithium_core::gui::scene::{self, Command, Sampling};
+use lithium_core::image::{Image, ImagesWatcher, ImageState};
 use lithium_core::theme::ElementStyle;
 use lithium_core::theme::element_style::{BackgroundImage, LengthOrPercentage, ColorStop, Border, BorderStyle, PositionCoordinate, RadialGradientShape, border, corner};
 use lithium_core::{Color, Vec2, Rect};
@@ -20,17 +24,95 @@ fn to_layout_size(v: Vec2<f64>) -> LayoutSize {
     LayoutSize::new(v.x as f32, v.y as f32)
 }
 
-pub fn build(layout_size: Vec2<f64>, commands: &[Command], builder: &mut DisplayListBuilder) {
+fn to_layout_rect(rect: Rect<f64>) -> LayoutRect {
+    LayoutRect::new(to_layout_point(rect.top_left()), to_layout_size(rect.size()))
+}
+
+/// Images uploaded to webrender. Only the changed regions of the images are uploaded again.
+pub struct Images {
+    watcher: ImagesWatcher<ImageKey>,
+    updates: ResourceUpdates,
+}
+
+impl Images {
+    pub fn new() -> Self {
+        Images {
+            watcher: ImagesWatcher::new(),
+            updates: ResourceUpdates::new(),
+        }
+    }
+
+    /// Resource updates needed by the display lists built since the previous call, they should be sent with
+    /// `RenderApi::update_resources` before these display lists. Images dropped since then are deleted as well.
+    pub fn take_updates(&mut self) -> ResourceUpdates {
+        for key in self.watcher.deleted() {
+            self.updates.delete_image(key);
+        }
+
+        replace(&mut self.updates, ResourceUpdates::new())
+    }
+
+    fn key(&mut self, api: &RenderApi, image: &Image) -> ImageKey {
+        match self.watcher.get_image_state(image, || api.generate_image_key()) {
+            ImageState::New(key) => {
+                self.updates.add_image(key, image_descriptor(image), ImageData::new(image_bytes(image)), None);
+                key
+            }
+            ImageState::Same(key) => key,
+            ImageState::Updated(key, region) => {
+                let dirty_rect = DeviceUintRect::new(
+                    DeviceUintPoint::new(region.left as u32, region.top as u32),
+                    DeviceUintSize::new(region.width() as u32, region.height() as u32),
+                );
+                self.updates.update_image(key, image_descriptor(image), ImageData::new(image_bytes(image)), Some(dirty_rect));
+                key
+            }
+        }
+    }
+}
+
+fn image_descriptor(image: &Image) -> ImageDescriptor {
+    let opaque = image.pixels().iter().all(|pixel| pixel.a >= 1.0);
+    ImageDescriptor::new(image.size().x as u32, image.size().y as u32, ImageFormat::BGRA8, opaque)
+}
+
+/// Premultiplied BGRA bytes of the image.
+fn image_bytes(image: &Image) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(image.pixels().len()*4);
+
+    for pixel in image.pixels() {
+        let (r, g, b, a) = pixel.to_rgba32();
+        let premultiply = |value: u8| ((value as u32*a as u32 + 127)/255) as u8;
+        bytes.extend_from_slice(&[premultiply(b), premultiply(g), premultiply(r), a]);
+    }
+
+    bytes
+}
+
+pub fn build(layout_size: Vec2<f64>, commands: &[Command], api: &RenderApi, images: &mut Images, builder: &mut DisplayListBuilder) {
+    // Whether each of the currently open elements clips its children. Commands are reversed so elements are opened by
+    // `CloseElement` and closed by `StartElement`.
+    let mut clips = Vec::new();
+
     for command in commands.iter().rev() {
         match command {
             &Command::CloseElement(ref element) => {
                 build_element(layout_size, element.place, &element.style, builder);
+
+                let clip = element.style.clips();
+                if clip {
+                    push_clip(layout_size, element.place, builder);
+                }
+                clips.push(clip);
             }
             &Command::StartElement => {
-
+                if clips.pop() == Some(true) {
+                    builder.pop_clip_node();
+                }
             }
             &Command::Text(ref _text) => {}
             &Command::Mesh(ref _mesh) => {}
+            &Command::Image(ref image) => build_image(image, api, images, builder),
         }
     }
 }
@@ -211,6 +293,33 @@ fn build_element(layout_size: Vec2<f64>, place: Rect<f64>, style: &ElementStyle,
     }
 }
 
+fn build_image(image: &scene::Image, api: &RenderApi, images: &mut Images, builder: &mut DisplayListBuilder) {
+    let key = images.key(api, &image.image);
+
+    let rendering = match image.sampling {
+        Sampling::Smooth => ImageRendering::Auto,
+        Sampling::Pixelated => ImageRendering::Pixelated,
+    };
+
+    let rect = to_layout_rect(image.place);
+    builder.push_image(rect, to_layout_rect(image.clip), rect.size, LayoutSize::zero(), rendering, key);
+}
+
+fn push_clip(layout_size: Vec2<f64>, place: Rect<f64>, builder: &mut DisplayListBuilder) {
+    use webrender_api::BorderRadius;
+
+    let layout_rect = LayoutRect::new(
+        LayoutPoint::zero(),
+        to_layout_size(layout_size),
+    );
+
+    let clip_region = ComplexClipRegion::new(LayoutRect::new(
+        to_layout_point(place.top_left()),
+        to_layout_size(place.size()),
+    ), BorderRadius::zero());
+    builder.push_clip_node(None, layout_rect, layout_rect, std::iter::once(clip_region), None);
+}
+
 fn position(position: Vec2<PositionCoordinate>, place: Rect<f64>) -> Vec2<f64> {
     Vec2::new(position_coordinate(position.x, place.left, place.right), position_coordinate(position.y, place.top, place.bottom))
 }
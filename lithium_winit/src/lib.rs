@@ -2,9 +2,14 @@ extern crate lithium_core;
 extern crate winit;
 
 use lithium_core::Vec2;
-use lithium_core::gui::input::{MouseButton, Key};
-use lithium_core::gui::input::{Event, TouchEvent, TouchPhase};
+use lithium_core::gui::input::{MouseButton, Key, ScanCode};
+use lithium_core::gui::input::{Event, KeyEvent, WheelDelta, TouchEvent, TouchPhase};
 
+/// Convert winit event into the lithium one.
+///
+/// This version of winit has no input method API so `Input::ime_preedit` and `Input::ime_commit` are never called: composed
+/// text is delivered character by character through `ReceivedCharacter` which is converted into `Event::Char`. For the same
+/// reason the caret position reported by `Input::ime_caret` cannot be forwarded to the platform.
 pub fn winit_event_to_lithium(event: &winit::WindowEvent, scale: f64) -> Option<Event> {
     match *event {
         winit::WindowEvent::MouseEntered => Some(Event::MouseEntered),
@@ -22,11 +27,20 @@ pub fn winit_event_to_lithium(event: &winit::WindowEvent, scale: f64) -> Option<
 
             Some(Event::MouseButton(button, state == winit::ElementState::Pressed))
         }
-        winit::WindowEvent::KeyboardInput(state, _scancode, Some(key), _modfiers) => {
-            let key = winit_key_to_lithium(key);
-
-            Some(Event::Key(key, state == winit::ElementState::Pressed))
+        winit::WindowEvent::MouseWheel(delta, _phase) => {
+            Some(Event::MouseWheel(match delta {
+                winit::MouseScrollDelta::LineDelta(x, y) => WheelDelta::Lines(Vec2::new(x as f64, y as f64)),
+                winit::MouseScrollDelta::PixelDelta(x, y) => WheelDelta::Pixels(Vec2::new(x as f64, y as f64) * (1.0 / scale)),
+            }))
+        }
+        winit::WindowEvent::KeyboardInput(state, scancode, key, _modfiers) => {
+            Some(Event::Key(KeyEvent {
+                key: key.map(winit_key_to_lithium),
+                scan_code: ScanCode(scancode as u32),
+                pressed: state == winit::ElementState::Pressed,
+            }))
         }
+        winit::WindowEvent::ReceivedCharacter(char) => Some(Event::Char(char)),
         winit::WindowEvent::Touch(touch) => {
             Some(Event::Touch(TouchEvent {
                 phase: match touch.phase {